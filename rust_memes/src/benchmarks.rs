@@ -0,0 +1,86 @@
+use crate::eight::common::tokenizing::lexing;
+use log::LevelFilter;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+fn collect_corpus(path: &str) -> Vec<(String, String)> {
+    // Gather (name, code) for the given file, or every '.8' file in the given directory
+    let mut corpus = Vec::new();
+    let p = Path::new(path);
+
+    let mut files = Vec::new();
+    if p.is_dir() {
+        let entries = std::fs::read_dir(p).expect(format!("Error reading directory '{}'!", path).as_str());
+        for entry in entries {
+            let file = entry.unwrap().path();
+            if file.extension().map_or(false, |ext| ext == "8") {
+                files.push(file);
+            }
+        }
+        files.sort();
+    } else {
+        files.push(p.to_path_buf());
+    }
+
+    for file in files {
+        let name = file.display().to_string();
+        let code = std::fs::read_to_string(&file).expect(format!("Error reading file '{}'!", name).as_str());
+        corpus.push((name, code));
+    }
+    return corpus;
+}
+
+fn time_lexing(name: &str, code: &str, iterations: u32) {
+    let mut total = Duration::new(0, 0);
+    let mut token_count = 0;
+
+    for _ in 0..iterations {
+        let input = String::from(code);
+        let start = Instant::now();
+        token_count = match lexing::parse(input) {
+            Ok(tokens) => tokens.len(),
+            Err(e) => {
+                println!("{} failed to lex:\n{}", name, e.render(name, code));
+                return;
+            }
+        };
+        total += start.elapsed();
+    }
+
+    let per_iter = total / iterations;
+    let mb_per_sec = (code.len() as f64 / (1024.0 * 1024.0)) / per_iter.as_secs_f64().max(f64::EPSILON);
+    println!(
+        "{:<32} {:>10} bytes {:>8} tokens {:>12.3?}/iter {:>10.2} MB/s",
+        name,
+        code.len(),
+        token_count,
+        per_iter,
+        mb_per_sec
+    );
+}
+
+pub fn bench_lexer(path: &str, iterations: u32) {
+    // Lexing benchmark over the script corpus, run with `--bench-lexer -f testing`
+    log::set_max_level(LevelFilter::Off); // Logging every token would dominate the timings
+
+    let corpus = collect_corpus(path);
+    if corpus.is_empty() {
+        println!("No '.8' files found in '{}'", path);
+        return;
+    }
+
+    println!("Lexing benchmark, {} iterations each", iterations);
+    for (name, code) in &corpus {
+        time_lexing(name, code, iterations);
+    }
+
+    // Repeat the whole corpus to check that lexing time grows linearly with input size
+    let mut joined = String::new();
+    for (_, code) in &corpus {
+        joined.push_str(code);
+        joined.push('\n');
+    }
+    for scale in &[1, 10, 100] {
+        time_lexing(format!("corpus x{}", scale).as_str(), joined.repeat(*scale).as_str(), iterations);
+    }
+}
@@ -2,6 +2,16 @@ use common::parsing::context::Context;
 use values::namespaces::database::{CSV, JSON};
 use values::namespaces::model::Model;
 use enum_dispatch::enum_dispatch;
+use expressions::primary::assignment::Assignment;
+use expressions::primary::exprstatement::ExprStatement;
+use expressions::primary::forexpr::ForExpr;
+use expressions::primary::funcdecl::FuncDecl;
+use expressions::primary::structdecl::StructDecl;
+use expressions::primary::enumdecl::EnumDecl;
+use expressions::primary::matchexpr::MatchExpr;
+use expressions::primary::ifexpr::IfExpr;
+use expressions::primary::loopcontrol::LoopControl;
+use expressions::primary::whileexpr::WhileExpr;
 use expressions::primary::variable::Variable;
 
 use crate::eight::common::parsing::ast::RelationEntry;
@@ -13,6 +23,7 @@ use literals::basic::number::NumberVal;
 use literals::basic::string::StringVal;
 use literals::basic::variableref::VariableTokenRef;
 
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
 use crate::eight::common::parsing::util::{consume_until_symbol, consume_until_symboltype};
 use crate::eight::common::parsing::ParsableExpression;
 use crate::eight::common::tokenizing::lexing;
@@ -33,65 +44,174 @@ pub mod expressions;
 pub mod literals;
 pub mod runners;
 
-pub fn parse_expr(context: &mut Context) -> Option<Expression> {
+pub fn parse_expr(context: &mut Context) -> ParseResult<Option<Expression>> {
     match context.get_safe() {
         Ok(symbol) => {
             debug!("Found symbol: {:?}", symbol);
 
             //Multi-line Comment parsing
             if symbol == Symbols::MultiCommentStart {
+                let comment_span = context.get_span();
                 context.increment();
-                consume_until_symbol(context, Symbols::MultiCommentEnd);
+                if consume_until_symbol(context, Symbols::MultiCommentEnd).is_err() {
+                    return Err(Diagnostic::error(String::from("Unterminated multi-line comment, expected '*/'"), comment_span));
+                }
                 debug!("Multi comment found");
-                return None;
+                return Ok(None);
             }
 
             // Single-line Comment parsing
             if symbol == Symbols::SingleComment {
                 context.increment();
-                consume_until_symboltype(context, SymbolType::Newline);
+                consume_until_symboltype(context, SymbolType::Newline)?;
                 debug!("Single comment found");
-                return None;
+                return Ok(None);
+            }
+
+            // Functions
+            match FuncDecl::parse(context)? {
+                Some(f) => {
+                    return Ok(Some(f));
+                }
+                None => {
+                    trace!("Didn't find FuncDecl");
+                }
+            }
+
+            match StructDecl::parse(context)? {
+                Some(s) => {
+                    return Ok(Some(s));
+                }
+                None => {
+                    trace!("Didn't find StructDecl");
+                }
+            }
+
+            match EnumDecl::parse(context)? {
+                Some(e) => {
+                    return Ok(Some(e));
+                }
+                None => {
+                    trace!("Didn't find EnumDecl");
+                }
+            }
+
+            match ReturnExpr::parse(context)? {
+                Some(r) => {
+                    return Ok(Some(r));
+                }
+                None => {
+                    trace!("Didn't find Return");
+                }
+            }
+
+            // Control flow
+            match IfExpr::parse(context)? {
+                Some(i) => {
+                    return Ok(Some(i));
+                }
+                None => {
+                    trace!("Didn't find If");
+                }
+            }
+
+            match WhileExpr::parse(context)? {
+                Some(w) => {
+                    return Ok(Some(w));
+                }
+                None => {
+                    trace!("Didn't find While");
+                }
+            }
+
+            match ForExpr::parse(context)? {
+                Some(f) => {
+                    return Ok(Some(f));
+                }
+                None => {
+                    trace!("Didn't find For");
+                }
+            }
+
+            match MatchExpr::parse(context)? {
+                Some(m) => {
+                    return Ok(Some(m));
+                }
+                None => {
+                    trace!("Didn't find Match");
+                }
+            }
+
+            match LoopControl::parse(context)? {
+                Some(l) => {
+                    return Ok(Some(l));
+                }
+                None => {
+                    trace!("Didn't find break or continue");
+                }
             }
 
             // Variable Expression parsing
-            match Variable::parse(context) {
+            match Variable::parse(context)? {
                 Some(Expression::VariableTokenExpr(vt)) => {
-                    return Some(Expression::VariableTokenExpr(vt));
+                    return Ok(Some(Expression::VariableTokenExpr(vt)));
                 }
                 _ => {
                     trace!("Didn't find VariableToken");
                 }, // Line didn't match
             }
 
-            match Print::parse(context) {
+            // Reassignment of an existing variable, 'a = ...;' or 'a += ...;'
+            match Assignment::parse(context)? {
+                Some(a) => {
+                    return Ok(Some(a));
+                }
+                None => {
+                    trace!("Didn't find Assignment");
+                }
+            }
+
+            match Print::parse(context)? {
                 Some(p) => {
-                    return Some(p);
+                    return Ok(Some(p));
                 }
                 _ => (),
             }
-            panic!("{}", context.get_panic_message("Unknown code fragment"));
+
+            // Any other expression, run for its side effects
+            match ExprStatement::parse(context)? {
+                Some(e) => {
+                    return Ok(Some(e));
+                }
+                None => {
+                    trace!("Didn't find expression statement");
+                }
+            }
+            return Err(context.error(String::from("Unknown code fragment")));
         }
         Err(e) => {
             debug!("Ran into EOF! Done?");
-            return Some(Expression::EndParsingExpr(NoOp {}));
+            return Ok(Some(Expression::EndParsingExpr(NoOp {})));
         }
     }
 }
 
-pub fn parse_exprs(context: &mut Context) -> Vec<Expression> {
+pub fn parse_exprs(context: &mut Context) -> Result<Vec<Expression>, Vec<Diagnostic>> {
+    // Parse every statement, on an error skip to the next statement so several errors can be reported at once
     debug!("----------PARSING START----------");
     let mut exprs = Vec::new();
+    let mut diagnostics = Vec::new();
 
     loop {
+        let statement_start = context.get_index();
         let expr = parse_expr(context);
         trace!("Looping");
         match expr {
-            Some(Expression::EndParsingExpr(_)) => {
+            Ok(Some(Expression::EndParsingExpr(_))) => {
                 debug!("Reached EOF");
                 break;
             }
-            Some(x) => match x {
+            Ok(Some(x)) => match x {
                 Expression::VariableTokenExpr(expr) => {
                     let val_typ = expr.get_var_type();
                     match val_typ {
@@ -100,8 +220,7 @@ pub fn parse_exprs(context: &mut Context) -> Vec<Expression> {
                             exprs.push(Expression::VariableTokenExpr(expr));
                         }
                         Err(e) => {
-                            // context index might be off by one right here, removing decrement()
-                            panic!("{}", context.get_panic_smessage(e));
+                            diagnostics.push(Diagnostic::error(e, context.span_since(statement_start)));
                         }
                     }
                 }
@@ -109,23 +228,311 @@ pub fn parse_exprs(context: &mut Context) -> Vec<Expression> {
                     exprs.push(ex);
                 }
             },
-            None => (),
+            Ok(None) => (),
+            Err(diag) => {
+                debug!("Parse error, recovering at the next statement {:?}", diag);
+                diagnostics.push(diag);
+                context.synchronize(statement_start);
+            }
         }
 
         debug!("Exprs parsed {:?}", exprs);
         context.print_vartable()
     }
     debug!("----------PARSING END----------");
-    return exprs;
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+    return Ok(exprs);
 }
 
-pub fn start_parse<'a>(code: String) -> Vec<Expression> {
-    let symbols = lexing::parse(
+pub fn start_parse<'a>(filename: String, code: String) -> Result<Vec<Expression>, Vec<Diagnostic>> {
+    let lexed = lexing::parse(
         format!("{}\n", code.clone()) // Add newline to end of file to make parsing go smoother
     );
+    let tokens = match lexed {
+        Ok(t) => t,
+        Err(e) => {
+            return Err(vec![e]);
+        }
+    };
+
+    let mut context = Context::new(code.clone(), filename, tokens, 0);
+
+    return parse_exprs(&mut context);
+}
 
-    let mut context = Context::new(code.clone(), symbols, 0);
+#[cfg(test)]
+mod tests {
+    use super::start_parse;
+    use crate::eight::common::diagnostics::render_all;
+
+    pub fn parse_errors(code: &str) -> Vec<String> {
+        // The message of every diagnostic reported for the code, empty when it parses
+        return match start_parse(String::from("test.8"), String::from(code)) {
+            Ok(_) => Vec::new(),
+            Err(diagnostics) => diagnostics.into_iter().map(|d| d.message).collect(),
+        };
+    }
+
+    #[test]
+    fn recovers_after_broken_block() {
+        // The statements after a block with an error are still checked
+        let code = "if true { let q = 1 + \"s\"; } let b = zz; let c = yy;";
+        let diagnostics = start_parse(String::from("test.8"), String::from(code)).unwrap_err();
+        assert_eq!(
+            render_all(&diagnostics, "test.8", code),
+            concat!(
+                "error: Operator '+' needs two Number or two Float typed operands! Found Number and String\n",
+                " --> test.8:1:19\n",
+                "  |\n",
+                "1 | if true { let q = 1 + \"s\"; } let b = zz; let c = yy;\n",
+                "  |                   ^^^^^^^\n",
+                "\n",
+                "error: Cannot find variable 'zz' in this scope\n",
+                " --> test.8:1:38\n",
+                "  |\n",
+                "1 | if true { let q = 1 + \"s\"; } let b = zz; let c = yy;\n",
+                "  |                                      ^^\n",
+                "\n",
+                "error: Cannot find variable 'yy' in this scope\n",
+                " --> test.8:1:50\n",
+                "  |\n",
+                "1 | if true { let q = 1 + \"s\"; } let b = zz; let c = yy;\n",
+                "  |                                                  ^^\n",
+                "\n",
+                "error: aborting due to 3 previous errors\n",
+            )
+        );
+    }
+
+    #[test]
+    fn recovers_after_broken_function_body() {
+        // The declaration right after the broken function parses normally
+        let errors = parse_errors("func f() -> Number { return g(); } func g() -> Number { return 1; }");
+        assert_eq!(errors, vec![String::from("Cannot find function 'g' in this scope")]);
+    }
 
-    let exprs = parse_exprs(&mut context);
-    return exprs;
+    #[test]
+    fn recovers_after_broken_declarations() {
+        let errors = parse_errors("struct S { a: Nope } let b = zz;\nenum E { A(Nope) } let c = yy;");
+        assert_eq!(errors, vec![
+            String::from("Unknown type 'Nope'"),
+            String::from("Cannot find variable 'zz' in this scope"),
+            String::from("Unknown type 'Nope'"),
+            String::from("Cannot find variable 'yy' in this scope"),
+        ]);
+    }
+
+    #[test]
+    fn recovers_at_next_statement() {
+        let errors = parse_errors("let a = zz;\nlet b = 1;\nlet c = yy;");
+        assert_eq!(errors, vec![
+            String::from("Cannot find variable 'zz' in this scope"),
+            String::from("Cannot find variable 'yy' in this scope"),
+        ]);
+    }
+
+    #[test]
+    fn functions_return_on_every_path() {
+        let errors = parse_errors("func f(a: Number) -> Number { let b = a; }");
+        assert_eq!(errors, vec![String::from("Function 'f' doesn't return a Number on every path")]);
+        let errors = parse_errors("func f(a: Number) -> Number { if a > 0 { return 1; } }");
+        assert_eq!(errors, vec![String::from("Function 'f' doesn't return a Number on every path")]);
+
+        assert!(parse_errors("func f(a: Number) -> Number { if a > 0 { return 1; } else if a < 0 { return 2; } else { return 3; } }").is_empty());
+        assert!(parse_errors("func f(a: Number) -> Number { if a > 0 { return 1; } return 2; }").is_empty());
+        assert!(parse_errors("func f(a: Number) { let b = a; }").is_empty());
+        assert!(parse_errors("enum E { A, B }\nfunc f(e: E) -> Number { match e { E::A => { return 1; } _ => { return 2; } } }").is_empty());
+    }
+
+    #[test]
+    fn empty_collections_are_refined_where_declared() {
+        let errors = parse_errors("let xs = [];\nif true { xs = [1]; }\nxs = [\"a\"];");
+        assert_eq!(errors, vec![String::from("Mismatched types, can't assign a List<String> value to 'xs' of type List<Number>")]);
+        let errors = parse_errors("let m = {};\nif true { m[\"a\"] = 1; }\nm[\"b\"] = \"s\";");
+        assert_eq!(errors, vec![String::from("Mismatched types, can't assign a String value to the element of 'm' of type Map<String, Number>")]);
+        let errors = parse_errors("let s = set([]);\nif true { insert(s, 1); }\ninsert(s, \"a\");");
+        assert_eq!(errors, vec![String::from("Invalid call to 'insert', expected a Number value, found String")]);
+
+        // A shadowing declaration in the block keeps its own type
+        assert!(parse_errors("let xs = [];\nif true { let xs = []; xs = [1]; }\nxs = [\"a\"];").is_empty());
+    }
+
+    #[test]
+    fn unknown_element_types_are_reported() {
+        let errors = parse_errors("let xs = [];\nlet a = xs[0];\nfor x in xs { println(fmt(x)); }\nlet m = {};\nlet b = m[\"k\"];");
+        assert_eq!(errors, vec![
+            String::from("The element type of this empty List is not known yet"),
+            String::from("The element type of this empty List is not known yet"),
+            String::from("The element type of this empty Map is not known yet"),
+        ]);
+        assert!(parse_errors("let xs: List<Number> = [];\nfor x in xs { println(fmt(x + 1)); }").is_empty());
+    }
+
+    #[test]
+    fn reserved_words_are_not_names() {
+        let errors = parse_errors("let true = 5;\nlet if = 5;\nfor false in [1] { }\nfunc f(true: Number) { }\nlet trueish = 1;");
+        assert_eq!(errors, vec![
+            String::from("'true' is a reserved keyword and can't be used as a variable name"),
+            String::from("'if' is a reserved keyword and can't be used as a variable name"),
+            String::from("'false' is a reserved keyword and can't be used as a variable name"),
+            String::from("'true' is a reserved keyword and can't be used as a parameter name"),
+        ]);
+    }
+
+    #[test]
+    fn match_value_arms_agree() {
+        let enum_s = "enum S { A, B(Number) }\nlet s = S::A;\n";
+        let errors = parse_errors(format!("{}let a = match s {{ S::A => 1, S::B(n) => \"two\" }};\nlet b = zz;", enum_s).as_str());
+        assert_eq!(errors, vec![
+            String::from("Mismatched types, match arms give Number and String"),
+            String::from("Cannot find variable 'zz' in this scope"),
+        ]);
+        let errors = parse_errors(format!("{}let a = match s {{ S::A => {{ 1 }} _ => 2 }};", enum_s).as_str());
+        assert_eq!(errors, vec![String::from("Expected a value after '=>', blocks don't give one")]);
+        let errors = parse_errors(format!("{}let a = match s {{ S::A => println(\"a\"), _ => 2 }};", enum_s).as_str());
+        assert_eq!(errors, vec![String::from("Expected a value after '=>', printing doesn't give one")]);
+        let errors = parse_errors(format!("{}match s {{ S::A => println(\"a\") S::B(n) => println(\"b\") }}", enum_s).as_str());
+        assert_eq!(errors, vec![String::from("Expected ',' or '}' after the match arm")]);
+    }
+
+    #[test]
+    fn number_literals_out_of_range() {
+        let errors = parse_errors(concat!(
+            "let a = -9223372036854775808;\n",
+            "let b = 9223372036854775808;\n",
+            "let c = 0x1_0000_0000_0000_0000;\n",
+            "let d = 1e400;\n",
+            "let e = 1e-400;\n",
+            "let f = 12ab;\n",
+            "let g = 1_;\n",
+        ));
+        assert_eq!(errors, vec![
+            String::from("Number literal '9223372036854775808' is out of range, Numbers are 64-bit signed integers between -9223372036854775808 and 9223372036854775807"),
+            String::from("Number literal '0x1_0000_0000_0000_0000' is out of range, Numbers are 64-bit signed integers between -9223372036854775808 and 9223372036854775807"),
+            String::from("Float literal '1e400' is out of range, Floats are 64-bit with a magnitude of at most 1.7976931348623157e308"),
+            String::from("Float literal '1e-400' is too small and rounds to zero, Floats are 64-bit with a magnitude of at least 5e-324"),
+            String::from("Invalid digit 'a' in decimal literal '12ab'"),
+            String::from("Number literal '1_' can't end with a '_' separator"),
+        ]);
+    }
+
+    #[test]
+    fn over_deep_expressions_are_reported() {
+        // Parsing recurses once per level, so give it the stack the binary runs with rather than a test thread's
+        let errors = std::thread::Builder::new().stack_size(64 * 1024 * 1024).spawn(|| {
+            let chain = format!("let a = 1{};", " + 1".repeat(10_000));
+            let parens = format!("let b = {}1{};", "(".repeat(130), ")".repeat(130));
+            let flat = format!("let c = 1{};", " + 1".repeat(1000));
+            return (parse_errors(chain.as_str()), parse_errors(parens.as_str()), parse_errors(flat.as_str()));
+        }).unwrap().join().unwrap();
+        assert_eq!(errors.0, vec![String::from("Expression chains too many operators, at most 2000 deep are supported")]);
+        assert_eq!(errors.1, vec![String::from("Expression is nested too deeply, at most 128 levels are supported")]);
+        assert_eq!(errors.2, Vec::<String>::new());
+    }
+
+    #[test]
+    fn functions_and_namespace_values_are_not_equatable() {
+        let errors = parse_errors(concat!(
+            "let f = |a: Number| a;\n",
+            "let g = |a: Number| a;\n",
+            "let d = Database::csv(\"testing/iris.csv\");\n",
+            "let a = f == g;\n",
+            "let b = d != d;\n",
+            "let c = [f] == [g];\n",
+        ));
+        assert_eq!(errors, vec![
+            String::from("Operator '==' can't compare functions or namespace values! Found Func(Number) -> Number and Func(Number) -> Number"),
+            String::from("Operator '!=' can't compare functions or namespace values! Found Database::CSV and Database::CSV"),
+            String::from("Operator '==' can't compare functions or namespace values! Found List<Func(Number) -> Number> and List<Func(Number) -> Number>"),
+        ]);
+    }
+
+    #[test]
+    fn unsupported_database_formats_are_reported() {
+        let errors = parse_errors("let d = Database::json(\"data.json\");\nlet e = zz;");
+        assert_eq!(errors, vec![
+            String::from("'Database::json' is not supported yet, only 'Database::csv' is"),
+            String::from("Cannot find variable 'zz' in this scope"),
+        ]);
+    }
+
+    #[test]
+    fn annotation_mismatches_point_at_both_sides() {
+        let code = "let xs: List<Number> = [\"a\"];\nlet m: Map<String, Float> = {\"k\": 1};";
+        let diagnostics = start_parse(String::from("test.8"), String::from(code)).unwrap_err();
+        assert_eq!(
+            render_all(&diagnostics, "test.8", code),
+            concat!(
+                "error: Mismatched types, 'xs' is annotated as List<Number> but its value is List<String>\n",
+                " --> test.8:1:24\n",
+                "  |\n",
+                "1 | let xs: List<Number> = [\"a\"];\n",
+                "  |         ------------   ^^^^^ expected List<Number>, found List<String>\n",
+                "  |         |\n",
+                "  |         expected because of this annotation\n",
+                "\n",
+                "error: Mismatched types, 'm' is annotated as Map<String, Float> but its value is Map<String, Number>\n",
+                " --> test.8:2:29\n",
+                "  |\n",
+                "2 | let m: Map<String, Float> = {\"k\": 1};\n",
+                "  |        ------------------   ^^^^^^^^ expected Map<String, Float>, found Map<String, Number>\n",
+                "  |        |\n",
+                "  |        expected because of this annotation\n",
+                "\n",
+                "error: aborting due to 2 previous errors\n",
+            )
+        );
+        let errors = parse_errors("let xs = [1];\nlet ys = xs.push(2);");
+        assert_eq!(errors, vec![String::from("No method 'push' on a value of type List<Number>")]);
+    }
+
+    #[test]
+    fn unary_operators_check_their_operand() {
+        let errors = parse_errors("let a = !1;\nlet b = -\"s\";\nlet c = -true;\nlet d = !fmt(1);\nlet e = !(1 < 2) || -1.5 < 0.0;");
+        assert_eq!(errors, vec![
+            String::from("Operator '!' needs a Boolean typed operand! Found Number"),
+            String::from("Operator '-' needs a Number or Float typed operand! Found String"),
+            String::from("Operator '-' needs a Number or Float typed operand! Found Boolean"),
+            String::from("Operator '!' needs a Boolean typed operand! Found String"),
+        ]);
+    }
+
+    #[test]
+    fn loop_control_needs_a_loop() {
+        let errors = parse_errors("break;\nfunc f() -> Number { for i in 0..2 { let g = || 1; } continue; return 1; }\nwhile 1 { }");
+        assert_eq!(errors, vec![
+            String::from("'break' can only be used inside a loop"),
+            String::from("'continue' can only be used inside a loop"),
+            String::from("Mismatched types, 'while' condition must be a Boolean, found Number"),
+        ]);
+    }
+
+    #[test]
+    fn closure_calls_check_their_signature() {
+        let errors = parse_errors("let f = |x: Number| x;\nlet a = f(\"s\");\nlet b = map(|x: String| x, [1]);");
+        assert_eq!(errors, vec![
+            String::from("Invalid function call, argument 0 does not match signature. Given 'String' Expected 'Number'"),
+            String::from("Invalid call to 'map', the function must take a single Number, found Func(String) -> String"),
+        ]);
+    }
+
+    #[test]
+    fn struct_fields_are_checked() {
+        let errors = parse_errors(concat!(
+            "struct P { x: Number }\n",
+            "let a = P { x: \"s\" };\n",
+            "let b = P { y: 1 };\n",
+            "let c = P { x: 1 };\n",
+            "let d = c.z;\n",
+            "c.x = 1.5;\n",
+        ));
+        assert_eq!(errors, vec![
+            String::from("Mismatched types, field 'x' of 'P' is Number but found String"),
+            String::from("'P' has no field 'y'"),
+            String::from("'P' has no field 'z', the fields are 'x'"),
+            String::from("Mismatched types, can't assign a Float value to field 'x' of 'c' of type P"),
+        ]);
+    }
 }
@@ -1,12 +1,12 @@
 pub mod compiling;
+pub mod diagnostics;
 pub mod logging;
 pub mod parsing;
 pub mod running;
 pub mod tokenizing;
 
-use lazy_static::lazy_static;
 use crate::eight::values::Value;
-use regex::Regex;
+use unicode_ident::{is_xid_continue, is_xid_start};
 
 pub trait AccessibleValue {
     // Values accessible by
@@ -20,8 +20,17 @@ pub trait AccessibleValue {
 pub fn is_varname_valid(varname: &str) -> bool {
     //TODO Put rules for var names here
     // Also checks function names, since they follow the same rules
-    lazy_static! {
-        static ref VARNAME_PAT: Regex = Regex::new("^[a-zA-Z]+\\w*$").unwrap();
-    }
-    return VARNAME_PAT.is_match(varname);
+    // Names follow Unicode XID, a start char followed by any number of continue chars
+    let mut chars = varname.chars();
+    return match chars.next() {
+        Some(first) => is_xid_start(first) && chars.all(is_xid_continue),
+        None => false,
+    };
+}
+
+// Words lexed as plain names that still stand for a value, they're reserved like the keywords
+const RESERVED_LITERALS: [&str; 2] = ["true", "false"];
+
+pub fn is_reserved_literal(name: &str) -> bool {
+    return RESERVED_LITERALS.contains(&name);
 }
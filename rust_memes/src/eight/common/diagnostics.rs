@@ -0,0 +1,181 @@
+use crate::eight::common::parsing::util::slice_str;
+use crate::eight::common::tokenizing::token::Span;
+
+pub type ParseResult<T> = Result<T, Diagnostic>;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Severity {
+    Error,
+}
+
+impl Severity {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub(crate) severity: Severity,
+    pub(crate) span: Span,
+    pub(crate) message: String,
+    pub(crate) label: Option<String>,           // Printed after the carets of the main span
+    pub(crate) secondary: Vec<(Span, String)>, // Other related spans, underlined with '-'
+    pub(crate) notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: String, span: Span) -> Self {
+        Diagnostic {
+            severity,
+            span,
+            message,
+            label: None,
+            secondary: vec![],
+            notes: vec![],
+        }
+    }
+
+    pub fn error(message: String, span: Span) -> Self {
+        Diagnostic::new(Severity::Error, message, span)
+    }
+
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn with_label(mut self, label: String) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn with_secondary(mut self, span: Span, label: String) -> Self {
+        self.secondary.push((span, label));
+        self
+    }
+
+    pub fn render(&self, filename: &str, source: &str) -> String {
+        // Render in the style of rustc
+        // error: message
+        //  --> file.8:2:11
+        //   |
+        // 2 | let x: Number = "a";
+        //   |        ------   ^^^ label
+        //   |        |
+        //   |        secondary label
+        //   = note: ...
+        let mut labels = vec![(&self.span, '^', self.label.clone().unwrap_or_default())];
+        for (span, label) in &self.secondary {
+            labels.push((span, '-', label.clone()));
+        }
+        labels.sort_by_key(|(span, _, _)| (span.line, span.col));
+
+        let widest_line = labels.iter().map(|(span, _, _)| span.line).max().unwrap_or(self.span.line);
+        let gutter = " ".repeat(widest_line.to_string().len());
+
+        let mut out = String::new();
+        out.push_str(format!("{}: {}\n", self.severity.to_str(), self.message).as_str());
+        out.push_str(format!("{}--> {}:{}:{}\n", gutter, filename, self.span.line, self.span.col).as_str());
+        out.push_str(format!("{} |\n", gutter).as_str());
+
+        let mut idx = 0;
+        while idx < labels.len() {
+            // Every label on the same line shares one copy of the source line
+            let line_no = labels[idx].0.line;
+            let mut end = idx;
+            while end < labels.len() && labels[end].0.line == line_no {
+                end += 1;
+            }
+            let line = source.lines().nth(line_no.saturating_sub(1)).unwrap_or("");
+            out.push_str(format!("{:>width$} | {}\n", line_no, line, width = gutter.len()).as_str());
+            out.push_str(render_label_rows(&labels[idx..end], line, source, gutter.as_str()).as_str());
+            idx = end;
+        }
+
+        for note in &self.notes {
+            out.push_str(format!("{} = note: {}\n", gutter, note).as_str());
+        }
+        return out;
+    }
+}
+
+fn render_label_rows(labels: &[(&Span, char, String)], line: &str, source: &str, gutter: &str) -> String {
+    // Markers for every label on one line, the rightmost label's text goes at the end of the marker row
+    // and the others hang below their markers
+    let mut markers = String::new();
+    let mut cols: Vec<String> = Vec::new();
+    for (span, marker, _) in labels {
+        let pad = padding_to(line, span.col);
+        if pad.len() < markers.len() {
+            break; // Overlapping labels, only the first one is drawn
+        }
+        markers.push_str(&pad[markers.len()..]);
+        // Only underline up to the end of the first line of the span
+        let underlined = slice_str(source, span.start, span.end);
+        let count = underlined.lines().next().map_or(0, |l| l.chars().count()).max(1);
+        markers.push_str(marker.to_string().repeat(count).as_str());
+        cols.push(pad);
+    }
+
+    let mut rows = vec![markers];
+    let last_label = &labels[cols.len() - 1].2;
+    if !last_label.is_empty() {
+        rows[0].push(' ');
+        rows[0].push_str(last_label);
+    }
+    for i in (0..cols.len() - 1).rev() {
+        if labels[i].2.is_empty() {
+            continue;
+        }
+        rows.push(pipes_row(&cols[..=i]));
+        let mut text_row = pipes_row(&cols[..i]);
+        text_row.push_str(&cols[i][text_row.len()..]);
+        text_row.push_str(labels[i].2.as_str());
+        rows.push(text_row);
+    }
+
+    let mut out = String::new();
+    for row in rows {
+        out.push_str(format!("{} | {}\n", gutter, row).as_str());
+    }
+    return out;
+}
+
+fn pipes_row(cols: &[String]) -> String {
+    // A '|' under the start of every label
+    let mut row = String::new();
+    for pad in cols {
+        row.push_str(&pad[row.len()..]);
+        row.push('|');
+    }
+    return row;
+}
+
+fn padding_to(line: &str, col: usize) -> String {
+    // Keep tabs in the padding so the markers line up under the source line
+    return line
+        .chars()
+        .take(col.saturating_sub(1))
+        .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+        .collect();
+}
+
+pub fn render_all(diagnostics: &[Diagnostic], filename: &str, source: &str) -> String {
+    // Render every diagnostic followed by a summary line when any are errors
+    let mut out = String::new();
+    for diag in diagnostics {
+        out.push_str(diag.render(filename, source).as_str());
+        out.push('\n');
+    }
+
+    let error_count = diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+    if error_count == 1 {
+        out.push_str("error: aborting due to 1 previous error\n");
+    } else if error_count > 1 {
+        out.push_str(format!("error: aborting due to {} previous errors\n", error_count).as_str());
+    }
+    return out;
+}
@@ -1,7 +1,9 @@
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
+use crate::eight::common::tokenizing::token::Span;
 use crate::eight::expressions::primary::Expression;
 use crate::eight::expressions::secondary::callfunc::CallFunc;
-use crate::eight::expressions::secondary::{organize_secondary_expression_list, partial_parse_secondary_expression, SecondaryExpression, TransitionExpression};
+use crate::eight::expressions::secondary::SecondaryExpression;
 use crate::eight::literals::basic::functions::args::FuncArgs;
 use crate::eight::literals::Literal;
 use crate::eight::expressions::secondary::operators::binary::{BinaryOperator, BinaryOperators};
@@ -11,16 +13,17 @@ pub mod context;
 pub mod util;
 
 pub trait ParsableExpression {
-    fn parse<'a, 'c>(context: &'c mut Context) -> Option<Expression>;
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>>;
 }
 
 pub trait ParsableLiteral {
-    fn parse<'a>(context: &mut Context) -> Option<Literal>;
+    fn parse<'a>(context: &mut Context) -> ParseResult<Option<Literal>>;
 
     fn create_class_func_call<'a>(name: &str, args: FuncArgs) -> Result<CallFunc, String>;
 }
 
 pub trait ParsableOperator {
-    // Take the expression list and simplify it given the idx of the operator
-    fn parse(context: &mut Context, expr_list: &mut Vec<TransitionExpression>, idx: usize, op: BinaryOperators);
+    // Combine the already parsed operands into the operator's expression, type checking both sides
+    // span covers the whole operation, for reporting type errors
+    fn parse(lhs: SecondaryExpression, rhs: SecondaryExpression, op: BinaryOperators, span: Span) -> ParseResult<SecondaryExpression>;
 }
@@ -193,6 +193,18 @@ impl RelationEntry {
         }
     }
 
+    pub fn collect_references(&self, out: &mut Vec<String>) {
+        // Every name referenced by this entry and its sub-entries, without duplicates
+        for r in &self.reference {
+            if !out.contains(r) {
+                out.push(r.clone());
+            }
+        }
+        for en in &self.entries {
+            en.collect_references(out);
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         if self.create.len() == 0 && self.entries.len() == 0 && self.reference.len() == 0 {
             return true;
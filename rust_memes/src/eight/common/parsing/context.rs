@@ -1,16 +1,31 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
 use crate::eight::common::tokenizing::symbols::{SymbolType, Symbols};
+use crate::eight::common::tokenizing::token::{Span, Token};
 use log::{debug, trace};
 use std::cmp::{max, min};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use crate::eight::literals::basic::functions::signature::FuncSignature;
 use crate::eight::values::ValueTypes;
+use crate::eight::literals::basic::structs::StructDef;
+use crate::eight::literals::basic::enums::EnumDef;
 
 #[derive(Debug)]
 pub struct Context<'a> {
     pub(crate) raw_code: String,
+    pub(crate) filename: String,
     n: &'a usize, // todo remove temp val to keep lifetime annotations?
     index: usize,
-    symbols: Vec<Symbols>,
+    tokens: Vec<Token>,
     pub(crate) vartable: HashMap<String, ValueTypes>,
+    scopes: Vec<(HashMap<String, ValueTypes>, HashSet<String>)>, // Outer vartables saved while parsing a block, with the names declared in each
+    declared: HashSet<String>, // Names declared in the current scope, rather than visible from an outer one
+    loop_depth: usize, // How many loop bodies are being parsed, 'break' and 'continue' need at least one
+    expr_depth: usize, // How many expressions are being parsed inside each other, '(1 + f(2 * 3))'
+    pub(crate) functions: HashMap<String, FuncSignature>, // Declared user functions
+    current_func: Option<FuncSignature>, // Signature of the function body being parsed, for 'return'
+    pub(crate) structs: HashMap<String, StructDef>, // Declared structs, by name
+    pub(crate) enums: HashMap<String, EnumDef>, // Declared enums, by name
+    recovered_at: Option<usize>, // Index just past a '}' that error recovery already skipped to
 }
 
 impl<'a> Context<'a> {
@@ -26,12 +41,12 @@ impl<'a> Context<'a> {
             prev = max(self.index - 2, 0);
         }
 
-        let end = min(self.symbols.len(), self.index + 4);
+        let end = min(self.tokens.len(), self.index + 4);
         for i in prev..end {
             if i == self.index {
-                trace!("cur:{:?}, ", self.symbols[i]);
+                trace!("cur:{:?}, ", self.tokens[i].symbol);
             } else {
-                trace!("{}:{:?}, ", i, self.symbols[i]);
+                trace!("{}:{:?}, ", i, self.tokens[i].symbol);
             }
         }
         trace!("...\n");
@@ -43,20 +58,110 @@ impl<'a> Context<'a> {
         }
     }
 
-    pub fn new(raw_code: String, symbols: Vec<Symbols>, index: usize) -> Context<'a> {
+    pub fn new(raw_code: String, filename: String, tokens: Vec<Token>, index: usize) -> Context<'a> {
         return Context {
             raw_code,
+            filename,
             n: &5, // TODO Remove temp lifetime placeholder?
             index,
-            symbols,
+            tokens,
             vartable: HashMap::new(),
+            scopes: Vec::new(),
+            declared: HashSet::new(),
+            loop_depth: 0,
+            expr_depth: 0,
+            functions: HashMap::new(),
+            current_func: None,
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            recovered_at: None,
         };
     }
 
     pub fn put_var(&mut self, s: String, typ: ValueTypes) {
+        self.declared.insert(s.clone());
         self.vartable.insert(s, typ);
     }
 
+    pub fn refine_var(&mut self, name: &str, typ: ValueTypes) {
+        // The type of an empty collection is filled in up to the scope that declared it, so it outlives the current block
+        self.vartable.insert(name.to_string(), typ.clone());
+        if self.declared.contains(name) {
+            return;
+        }
+        for (vartable, declared) in self.scopes.iter_mut().rev() {
+            if let Some(t) = vartable.get_mut(name) {
+                *t = typ.clone();
+            }
+            if declared.contains(name) {
+                return;
+            }
+        }
+    }
+
+    pub fn push_scope(&mut self) {
+        // Variables declared after this are forgotten again by pop_scope
+        let declared = std::mem::take(&mut self.declared);
+        self.scopes.push((self.vartable.clone(), declared));
+    }
+
+    pub fn pop_scope(&mut self) {
+        match self.scopes.pop() {
+            Some((vartable, declared)) => {
+                self.vartable = vartable;
+                self.declared = declared;
+            }
+            None => {
+                panic!("Shouldn't be here! Attempted to pop the outermost parsing scope");
+            }
+        }
+    }
+
+    pub fn is_top_level(&self) -> bool {
+        return self.scopes.is_empty();
+    }
+
+    pub fn enter_function(&mut self, sig: FuncSignature, params: Vec<(String, ValueTypes)>) {
+        // Function bodies only see their own parameters, outer variables are hidden until exit_function
+        self.push_scope();
+        self.vartable = params.into_iter().collect();
+        self.declared = self.vartable.keys().cloned().collect();
+        self.current_func = Some(sig);
+    }
+
+    pub fn exit_function(&mut self) {
+        self.current_func = None;
+        self.pop_scope();
+    }
+
+    pub fn get_current_func(&self) -> Option<&FuncSignature> {
+        return self.current_func.as_ref();
+    }
+
+    pub fn enter_loop(&mut self) {
+        self.loop_depth += 1;
+    }
+
+    pub fn exit_loop(&mut self) {
+        self.loop_depth -= 1;
+    }
+
+    pub fn in_loop(&self) -> bool {
+        return self.loop_depth > 0;
+    }
+
+    pub fn enter_expression(&mut self) {
+        self.expr_depth += 1;
+    }
+
+    pub fn exit_expression(&mut self) {
+        self.expr_depth -= 1;
+    }
+
+    pub fn expression_depth(&self) -> usize {
+        return self.expr_depth;
+    }
+
     pub fn get_varnames(&self) -> Vec<String> {
         let mut v = Vec::new();
         let k = self.vartable.keys();
@@ -67,15 +172,15 @@ impl<'a> Context<'a> {
     }
 
     pub fn get_raw(&self) -> Symbols {
-        if self.index >= self.symbols.len() {
+        if self.index >= self.tokens.len() {
             panic!("Reached EOF in get_raw!");
         }
-        return self.symbols[self.index].clone();
+        return self.tokens[self.index].symbol.clone();
     }
 
     pub fn back_to_linestart(&mut self) {
         while self.index > 0 {
-            let cur_sym = &self.symbols[self.index].clone();
+            let cur_sym = &self.tokens[self.index].symbol;
             if cur_sym == &Symbols::NewLine || cur_sym == &Symbols::WindowsNewLine {
                 break;
             } else {
@@ -127,20 +232,14 @@ impl<'a> Context<'a> {
         let mut gotten_symbs = Vec::new();
         let mut whitespace_count = 0;
 
-        let start_line = self.get_line_no();
-        let snipp = self.get_snippet();
-
         while gotten_count < len {
-            if self.index + whitespace_count + gotten_count >= self.symbols.len() {
-                gotten_symbs.push(Err(format!(
-                    "Reached EOF while looking for non-whitespace token! Started on line {} snippet: '{}'",
-                    start_line, snipp
-                )));
+            if self.index + whitespace_count + gotten_count >= self.tokens.len() {
+                gotten_symbs.push(Err(String::from("Reached EOF while looking for a non-whitespace token")));
                 return gotten_symbs;
             }
 
-            while self.index + whitespace_count + gotten_count < self.symbols.len() && gotten_count < len {
-                let cur_sym = &self.symbols[self.index + whitespace_count + gotten_count].clone();
+            while self.index + whitespace_count + gotten_count < self.tokens.len() && gotten_count < len {
+                let cur_sym = &self.tokens[self.index + whitespace_count + gotten_count].symbol;
                 let mut found_whitespace = false;
 
                 for sym in &whitesp_syms {
@@ -169,21 +268,21 @@ impl<'a> Context<'a> {
         self.get_safe_multiple(1).remove(0)
     }
 
-    pub fn get_multiple(&mut self, len: usize) -> Vec<Symbols> {
+    pub fn get_multiple(&mut self, len: usize) -> ParseResult<Vec<Symbols>> {
         let mut v = Vec::new();
         for val in self.get_safe_multiple(len) {
             match val {
                 Ok(s) => v.push(s),
                 Err(s) => {
-                    panic!("{}", self.get_panic_smessage(s))
+                    return Err(self.error(s));
                 }
             }
         }
-        return v;
+        return Ok(v);
     }
 
-    pub fn get(&mut self) -> Symbols {
-        self.get_multiple(1).remove(0)
+    pub fn get(&mut self) -> ParseResult<Symbols> {
+        Ok(self.get_multiple(1)?.remove(0))
     }
 
     pub fn has_next_nonwhitespace(&self) -> bool {
@@ -191,7 +290,7 @@ impl<'a> Context<'a> {
     }
 
     pub fn has_next(&self) -> bool {
-        if self.index + 1 > self.symbols.len() {
+        if self.index + 1 > self.tokens.len() {
             return false;
         }
         return true;
@@ -205,67 +304,141 @@ impl<'a> Context<'a> {
         self.increment_raw();
         let whitesp_syms = Symbols::get_symbols_by_type(SymbolType::Whitespace);
         loop {
-            if self.index == self.symbols.len() {
+            if self.index >= self.tokens.len() {
                 return; // EOF reached
             }
-            for sym in &whitesp_syms {
-                if &self.symbols[self.index].clone() == sym {
-                    self.increment_raw();
-                    break;
-                }
+            if whitesp_syms.contains(&self.tokens[self.index].symbol) {
+                self.increment_raw(); // Skip over every whitespace symbol, not just the first
+            } else {
+                return;
             }
-            return;
         }
     }
 
-    pub fn get_panic_smessage(&self, msg: String) -> String {
-        return self.get_panic_message(msg.as_str());
-    }
-
-    pub fn get_panic_message(&self, msg: &str) -> String {
-        trace!("---------PANIC---------");
-        trace!("--VARTABLE AT PANIC--");
+    pub fn error(&self, msg: String) -> Diagnostic {
+        // Create an error diagnostic pointing at the current token
+        trace!("---------PARSE ERROR---------");
+        trace!("--VARTABLE AT ERROR--");
         self.print_vartable();
 
-        trace!("--SYMBOLS AT PANIC--");
+        trace!("--SYMBOLS AT ERROR--");
         self.print_symbols_current();
 
-        let mut message = String::new();
-        message.push_str("\n--\n");
-        message.push_str(format!("Parsing failed: {:?}\n", msg).as_str());
-        message.push_str(format!("On line: {:?}\n", self.get_line_no()).as_str());
-        message.push_str(format!("Snippet: \n`{}`", self.get_snippet()).as_str());
-        message.push_str("\n--\n");
-        return message;
-    }
-
-    pub fn get_line_no(&self) -> i32 {
-        let mut line_count = 0;
-        let whitespace_syms =
-            Symbols::sort_by_longest(Symbols::get_symbols_by_type(SymbolType::Newline));
-
-        for idx in 0..self.index {
-            for sym_idx in 0..whitespace_syms.len() {
-                if self.symbols[idx] == whitespace_syms[sym_idx] {
-                    line_count += 1;
-                    break;
-                }
+        return Diagnostic::error(msg, self.get_span());
+    }
+
+    fn is_whitespace_at(&self, idx: usize) -> bool {
+        return Symbols::get_symbols_by_type(SymbolType::Whitespace).contains(&self.tokens[idx].symbol);
+    }
+
+    fn next_nonwhitespace_index(&self, idx: usize) -> usize {
+        let mut idx = idx;
+        while idx < self.tokens.len() && self.is_whitespace_at(idx) {
+            idx += 1;
+        }
+        return idx;
+    }
+
+    pub fn span_since(&self, start_index: usize) -> Span {
+        // Span from the first token at or after start_index up to the last token consumed before the current index
+        let start = self.next_nonwhitespace_index(start_index);
+        let mut end = min(self.index, self.tokens.len());
+        while end > start + 1 && self.is_whitespace_at(end - 1) {
+            end -= 1;
+        }
+        if start >= self.tokens.len() || end <= start {
+            return self.get_span();
+        }
+        return self.tokens[start].span.to(&self.tokens[end - 1].span);
+    }
+
+    pub fn span_after_previous(&self) -> Span {
+        // Empty span just past the last consumed token, where a missing ';' or ')' belongs
+        let mut idx = min(self.index, self.tokens.len());
+        while idx > 0 && self.is_whitespace_at(idx - 1) {
+            idx -= 1;
+        }
+        if idx == 0 {
+            return self.get_span();
+        }
+        let prev = self.tokens[idx - 1].span;
+        let width = self.raw_code.get(prev.start..prev.end).map_or(0, |s| s.chars().count());
+        return Span::new(prev.end, prev.end, prev.line, prev.col + width);
+    }
+
+    pub fn mark_recovered(&mut self) {
+        // Called after skipping past the '}' of a broken block, so synchronize doesn't skip any further
+        self.recovered_at = Some(self.index);
+    }
+
+    pub fn synchronize(&mut self, statement_start: usize) {
+        // Recover from a parse error by skipping to just past the next ';' or newline
+        // When the broken statement consumed tokens, a newline right before the failing token counts as its end
+        // so a missing ';' doesn't also swallow the following line
+        if self.recovered_at.take() == Some(self.index) {
+            // A broken block was already skipped up to its '}', the next statement starts right after it
+            // or after the ';' ending a statement like 'let a = match ... { ... };'
+            let mut idx = self.next_nonwhitespace_index(self.index);
+            if idx < self.tokens.len() && self.tokens[idx].symbol == Symbols::Semicolon {
+                idx += 1;
+            }
+            self.index = self.next_nonwhitespace_index(idx);
+            return;
+        }
+        let statement_start = self.next_nonwhitespace_index(statement_start);
+        let mut idx = self.next_nonwhitespace_index(self.index);
+        if idx > statement_start {
+            while idx > statement_start && self.is_whitespace_at(idx - 1) {
+                idx -= 1;
+            }
+        }
+
+        let newline_syms = Symbols::get_symbols_by_type(SymbolType::Newline);
+        while idx < self.tokens.len() {
+            let sym = &self.tokens[idx].symbol;
+            idx += 1;
+            if sym == &Symbols::Semicolon || newline_syms.contains(sym) {
+                break;
             }
         }
-        return line_count + 1; // line count starts at 0
+        self.index = self.next_nonwhitespace_index(idx);
     }
 
-    pub fn get_snippet(&self) -> String {
-        let mut symbs = Vec::new();
-        for idx in 0..min(self.symbols.len() - self.index, 5) {
-            symbs.push(self.symbols[self.index + idx].clone());
+    fn get_current_token(&self) -> Option<&Token> {
+        // The current index may sit on whitespace, the token of interest is the next non-whitespace one
+        let whitespace_syms = Symbols::get_symbols_by_type(SymbolType::Whitespace);
+
+        for token in &self.tokens[min(self.index, self.tokens.len())..] {
+            if !whitespace_syms.contains(&token.symbol) {
+                return Some(token);
+            }
         }
-        let mut st = String::new();
+        return self.tokens.last();
+    }
 
-        for symb in symbs {
-            st.push_str(symb.to_str().as_str());
+    pub fn get_span(&self) -> Span {
+        return match self.get_current_token() {
+            Some(token) => token.span,
+            None => Span::empty(),
+        };
+    }
+
+    pub fn get_line_no(&self) -> usize {
+        return self.get_span().line;
+    }
+
+    pub fn get_col_no(&self) -> usize {
+        return self.get_span().col;
+    }
+
+    pub fn get_snippet(&self) -> String {
+        let start = min(self.index, self.tokens.len());
+        let end = min(self.tokens.len(), start + 5);
+        if start == end {
+            return String::new();
         }
-        return st;
+        let span = self.tokens[start].span.to(&self.tokens[end - 1].span);
+        return String::from(&self.raw_code[span.start..min(span.end, self.raw_code.len())]);
     }
 
 }
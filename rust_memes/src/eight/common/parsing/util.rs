@@ -1,61 +1,58 @@
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::tokenizing::symbols::{SymbolType, Symbols};
 
 pub fn slice_str(s: &str, start: usize, endi: usize) -> &str {
-    if endi == usize::MAX {
-        return &s[start..];
-    }
-
-    let end: usize = endi as usize;
+    // Slice by byte offsets, widening to the surrounding char boundaries so a multi-byte char is never split
     let l = s.len();
-    if start > l {
+    let mut start = start.min(l);
+    let mut end = endi.min(l);
+    if start >= end {
         return "";
-    } else if end > l {
-        return &s[start..];
     }
 
+    while !s.is_char_boundary(start) {
+        start -= 1;
+    }
+    while !s.is_char_boundary(end) {
+        end += 1;
+    }
     return &s[start..end];
 }
 
-pub fn consume_until_symboltype(context: &mut Context, symtyp: SymbolType) {
+pub fn consume_until_symboltype(context: &mut Context, symtyp: SymbolType) -> ParseResult<()> {
     let syms = Symbols::get_symbols_by_type(symtyp);
 
 
     if !context.has_next() {
-        panic!(
-            "{}",
-            context.get_panic_smessage(format!(
-                "Reached EOF Looking for SymbolType '{:?}'",
-                symtyp
-            ))
-        );
+        return Err(context.error(format!(
+            "Reached EOF Looking for SymbolType '{:?}'",
+            symtyp
+        )));
     }
 
     loop {
         for sym in &syms {
             if &context.get_raw() == sym {
                 context.increment(); // Increment past matching symbol
-                return;
+                return Ok(());
             }
         }
+        context.increment_raw(); // Don't skip whitespace, it may be the symbol type being looked for
         if !context.has_next() {
-            panic!(
-                "{}",
-                context.get_panic_smessage(format!(
-                    "Reached EOF Looking for SymbolType '{:?}'",
-                    symtyp
-                ))
-            );
+            return Err(context.error(format!(
+                "Reached EOF Looking for SymbolType '{:?}'",
+                symtyp
+            )));
         }
-        context.increment();
     }
 }
 
-pub fn consume_until_symbol(context: &mut Context, sym: Symbols) {
+pub fn consume_until_symbol(context: &mut Context, sym: Symbols) -> ParseResult<()> {
     loop {
-        if context.get() == sym {
+        if context.get()? == sym {
             context.increment(); // Increment past matching symbol
-            break;
+            return Ok(());
         } else {
             context.increment();
         }
@@ -1,2 +1,4 @@
+pub mod error;
 pub mod interpreter;
 pub mod memory;
+pub mod output;
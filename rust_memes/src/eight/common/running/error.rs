@@ -0,0 +1,24 @@
+use std::fmt::{Display, Formatter};
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub(crate) message: String,
+}
+
+impl RuntimeError {
+    pub fn new(message: String) -> Self {
+        RuntimeError { message }
+    }
+
+    pub fn raise(self) -> ! {
+        // Unwinds out of the running script, the runner catches it and hands it back to the host
+        std::panic::panic_any(self)
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Runtime Exception: {}", self.message)
+    }
+}
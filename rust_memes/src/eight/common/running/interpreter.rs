@@ -2,49 +2,118 @@ use std::borrow::BorrowMut;
 use crate::eight::expressions::primary::Expression;
 use crate::eight::expressions::secondary::{RunnableSecondaryExpression, SecondaryExpression};
 use crate::eight::literals::Literal;
-use crate::eight::values::Value;
+use crate::eight::values::{Value, ValueTypes};
+use crate::eight::literals::basic::functions::Func;
+use crate::eight::literals::basic::functions::content::FuncContent;
+use std::collections::HashMap;
+use std::rc::Rc;
 use crate::eight::common::AccessibleValue;
+use crate::eight::common::running::error::RuntimeError;
 use crate::eight::common::running::memory::frame::Frame;
 use crate::eight::common::running::memory::memref::MemRef;
 use log::{debug, trace};
 use crate::eight::common::running::memory::stack::Stack;
+use crate::eight::common::running::output::OutputSink;
 use crate::eight::expressions::primary::noop::NoOp;
+use crate::eight::expressions::secondary::index::{check_index, missing_key};
+use crate::eight::literals::basic::number::NumberVal;
 
 
+#[derive(Debug)]
+pub enum PathKey {
+    Index(Value),  // A List index or Map key
+    Field(String), // A struct field
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ControlFlow {
+    Normal,
+    Break,    // Leave the innermost loop
+    Continue, // Skip to the next iteration of the innermost loop
+    Return,   // Leave the current function, the value is in return_value
+}
+
+impl ControlFlow {
+    pub fn exits_loop(&self) -> bool {
+        matches!(self, ControlFlow::Break | ControlFlow::Return)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OutputStream {
+    Out, // 'print' and 'println'
+    Err, // 'eprint' and 'eprintln'
+}
+
+const MAX_CALL_DEPTH: usize = 256;
+
 pub struct EightInterpreter {
     frames: Vec<Frame>,
     working_memory: Stack,
-    empty: Value
+    empty: Value,
+    control_flow: ControlFlow, // Set by 'break'/'continue', blocks stop running until a loop handles it
+    return_value: Option<Value>,
+    functions: HashMap<String, Rc<Func>>, // User funcs, registered when their declaration runs
+    call_bases: Vec<usize>, // Index of the first frame of each running user func, lookups stop there
+    out: OutputSink, // Where 'print' and 'println' go
+    err: OutputSink, // Where 'eprint' and 'eprintln' go
 }
 
 impl EightInterpreter {
     pub fn new() -> Self {
+        EightInterpreter::with_sinks(OutputSink::stdout(), OutputSink::stderr())
+    }
+
+    pub fn with_sinks(out: OutputSink, err: OutputSink) -> Self {
         let mut frames = Vec::new();
         frames.push(Frame::new());
 
         EightInterpreter {
             frames,
             working_memory: Stack::new(),
-            empty: Value::None(NoOp{})
+            empty: Value::None(NoOp{}),
+            control_flow: ControlFlow::Normal,
+            return_value: None,
+            functions: HashMap::new(),
+            call_bases: Vec::new(),
+            out,
+            err,
         }
     }
 
-    pub fn step(&mut self, mut expr: SecondaryExpression) -> MemRef {
+    pub fn step(&mut self, expr: &SecondaryExpression) -> MemRef {
         trace!("Stepping '{:?}'", expr);
         let o = expr.run_secondary_expr(self);
         trace!("Step Result -> {:?}", o);
         return o;
     }
 
-    pub fn run(&mut self, mut exprs: Vec<Expression>) {
-        let mut frame = Frame::new();
-        let l = exprs.len();
-
-        for _ in 0..l {
-            let mut ex = exprs.remove(0);
+    pub fn run(&mut self, exprs: Vec<Expression>) {
+        for ex in &exprs {
             debug!("Expression being run: '{:?}'", ex);
             ex.run_expr(self);
         }
+        self.flush_output();
+    }
+
+    pub fn write_output(&mut self, stream: OutputStream, s: &str) {
+        let res = match stream {
+            OutputStream::Out => self.out.write_str(s),
+            OutputStream::Err => {
+                // Anything printed before this should show up before it
+                let _ = self.out.flush();
+                self.err.write_str(s)
+            }
+        };
+        if let Err(e) = res {
+            // A closed pipe or full disk ends the script like any other error, rather than crashing the host
+            RuntimeError::new(format!("Failed to write output, {}", e)).raise();
+        }
+    }
+
+    pub fn flush_output(&mut self) {
+        let _ = self.out.flush();
+        let _ = self.err.flush();
     }
 
     pub fn add_val_to_stack(&mut self, val: Value) -> MemRef {
@@ -55,69 +124,278 @@ impl EightInterpreter {
         if mem.is_stack_ref() {
             self.working_memory.take(mem)
         } else if mem.is_frame_ref() {
-            self.frames[0].remove(mem.get_as_frame())
+            let idx = self.find_frame_idx(mem.get_as_frame());
+            self.frames[idx].remove(mem.get_as_frame())
         } else {
             Value::None(NoOp{})
         }
     }
 
+    pub fn copy_val(&mut self, mem: MemRef) -> Value {
+        // Take temporaries off the stack, but copy frame values so the variable stays readable
+        if mem.is_frame_ref() {
+            self.get_val(&mem).copy()
+        } else {
+            self.take_val(mem)
+        }
+    }
+
     pub fn pop_stack_vals(&mut self, mem: MemRef) {
         if mem.is_stack_ref() {
             self.working_memory.take(mem);
         }
     }
 
-    pub fn add_mem_to_frame(&mut self, mem: MemRef, val: Value) {
-        // Add a previously removed frame value back to the frame
-        // ignore any stack values
-        if mem.is_stack_ref() { return; }
-        self.add_val_to_frame(mem.get_as_frame().to_string(), val);
-    }
-
     pub fn new_frame(&mut self) {
-        self.frames.insert(0, Frame::new());
+        // The innermost frame is always the last one
+        self.frames.push(Frame::new());
     }
 
     pub fn pop_or_clear_frame(&mut self) {
-        if self.frames.len() == 1 {
-            self.frames.pop();
+        self.frames.pop();
+        if self.frames.is_empty() {
             self.frames.push(Frame::new());
-        } else {
-            self.frames.pop();
         }
     }
 
-    pub fn add_val_to_frame(&mut self, name: String, val: Value) {
-        self.frames[0].add_var(name, val);
+    pub fn run_block(&mut self, exprs: &[Expression]) {
+        self.run_block_with_vars(exprs, vec![]);
     }
 
-    pub fn get_val_typed_from_frame<T: AccessibleValue>(&self, name: &str) -> &T {
-        self.frames[0].get_typed(name)
+    pub fn run_block_with_vars(&mut self, exprs: &[Expression], vars: Vec<(String, Value)>) {
+        // Run the expressions in their own frame so declared variables don't leak out of the block
+        self.new_frame();
+        for (name, val) in vars {
+            self.add_val_to_frame(name, val);
+        }
+        for ex in exprs {
+            debug!("Block expression being run: '{:?}'", ex);
+            ex.run_expr(self);
+            if self.control_flow != ControlFlow::Normal {
+                break;
+            }
+        }
+        self.pop_or_clear_frame();
     }
 
-    pub fn get_val_from_frame(&self, name: &str) -> MemRef {
-        match self.frames[0].contains_var(name) {
-            Some(m) => m,
+    pub fn step_with_vars(&mut self, expr: &SecondaryExpression, vars: Vec<(String, Value)>) -> Value {
+        // Like run_block_with_vars for a single value, the arms of a 'match' see their bindings and the outer variables
+        self.new_frame();
+        for (name, val) in vars {
+            self.add_val_to_frame(name, val);
+        }
+        let m = self.step(expr);
+        let val = self.copy_val(m);
+        self.pop_or_clear_frame();
+        return val;
+    }
+
+    pub fn register_func(&mut self, func: Rc<Func>) {
+        self.functions.insert(func.name.clone(), func);
+    }
+
+    pub fn call_user_func(&mut self, name: &str, args: Vec<Value>) -> MemRef {
+        let func = match self.functions.get(name) {
+            Some(f) => Rc::clone(f),
             None => {
-                // TODO Runtime exceptions
-                panic!("Runtime exception, cannot get var '{:?}' from frame!", name);
+                RuntimeError::new(format!("Function '{}' was called before its declaration ran", name)).raise();
             }
+        };
+        self.check_call_depth(name);
+
+        let body = match &func.content {
+            FuncContent::Func(body) => body,
+            o => {
+                panic!("Shouldn't be here! User func '{}' has no body, found {:?}", name, o);
+            }
+        };
+        let vars = func.params.iter().cloned().zip(args).collect();
+
+        self.call_bases.push(self.frames.len());
+        self.run_block_with_vars(body, vars);
+        self.call_bases.pop();
+
+        if self.control_flow == ControlFlow::Return {
+            self.control_flow = ControlFlow::Normal;
+        }
+        let val = self.return_value.take().unwrap_or_else(Value::gen_noop);
+        if !func.sig.get_output_type().accepts(&ValueTypes::convert_to_type(&val)) {
+            RuntimeError::new(format!("Function '{}' finished without returning a {:?}", name, func.sig.get_output_type())).raise();
         }
+        return self.add_val_to_stack(val);
+    }
+
+    pub fn call_func_value(&mut self, func: &Func, args: Vec<Value>) -> MemRef {
+        // Function values are either closures or declared funcs referred to by name
+        return match &func.content {
+            FuncContent::Closure(body) => self.call_closure(func, body, args),
+            FuncContent::Declared => self.call_user_func(func.name.as_str(), args),
+            o => {
+                panic!("Shouldn't be here! Function value '{}' can't be called, found {:?}", func.name, o);
+            }
+        };
+    }
+
+    pub fn call_func_var(&mut self, name: &str, args: Vec<Value>) -> MemRef {
+        let mem = self.get_val_from_frame(name);
+        let func = match self.get_val(&mem) {
+            Value::Function(f) => f.copy(),
+            o => {
+                RuntimeError::new(format!("'{}' holds {:?} which can't be called", name, o)).raise();
+            }
+        };
+        return self.call_func_value(&func, args);
+    }
+
+    fn call_closure(&mut self, func: &Func, body: &SecondaryExpression, args: Vec<Value>) -> MemRef {
+        self.check_call_depth(func.name.as_str());
+
+        // Like user funcs, closures only see their own frame, captures are copied into it on every call
+        let mut vars: Vec<(String, Value)> = func.captures.iter().map(|(n, v)| (n.clone(), v.copy())).collect();
+        vars.extend(func.params.iter().cloned().zip(args));
+
+        self.call_bases.push(self.frames.len());
+        self.new_frame();
+        for (name, val) in vars {
+            self.add_val_to_frame(name, val);
+        }
+        let m = self.step(body);
+        let val = self.copy_val(m);
+        self.pop_or_clear_frame();
+        self.call_bases.pop();
+
+        return self.add_val_to_stack(val);
+    }
+
+    fn check_call_depth(&self, name: &str) {
+        if self.call_bases.len() >= MAX_CALL_DEPTH {
+            RuntimeError::new(format!("Calling '{}' exceeded the maximum call depth of {}", name, MAX_CALL_DEPTH)).raise();
+        }
+    }
+
+    pub fn set_return_value(&mut self, val: Value) {
+        self.return_value = Some(val);
+        self.control_flow = ControlFlow::Return;
+    }
+
+    pub fn set_control_flow(&mut self, flow: ControlFlow) {
+        self.control_flow = flow;
+    }
+
+    pub fn take_loop_control(&mut self) -> ControlFlow {
+        // Loops call this after every iteration, a pending 'break' or 'continue' is handled and cleared
+        let flow = self.control_flow;
+        match flow {
+            ControlFlow::Break | ControlFlow::Continue => self.control_flow = ControlFlow::Normal,
+            ControlFlow::Normal | ControlFlow::Return => (),
+        }
+        return flow;
+    }
+
+    fn find_frame_idx(&self, name: &str) -> usize {
+        // Search from the innermost frame outwards so inner declarations shadow outer ones
+        // Inside a user func the callers frames are out of reach
+        let base = *self.call_bases.last().unwrap_or(&0);
+        for idx in (base..self.frames.len()).rev() {
+            if self.frames[idx].contains_var(name).is_some() {
+                return idx;
+            }
+        }
+        RuntimeError::new(format!("Cannot get var '{:?}' from frame!", name)).raise();
+    }
+
+    fn current_frame(&mut self) -> &mut Frame {
+        let idx = self.frames.len() - 1;
+        &mut self.frames[idx]
+    }
+
+    pub fn add_val_to_frame(&mut self, name: String, val: Value) {
+        // A second 'let' of the same name shadows the old value
+        self.current_frame().set_var(name, val);
+    }
+
+    pub fn assign_val_in_frame(&mut self, name: &str, val: Value) {
+        *self.get_mut_val_from_frame(name) = val;
+    }
+
+    pub fn get_path_in_frame(&self, name: &str, path: &[PathKey]) -> Value {
+        // Copy of the value at an already evaluated path, so 'xs[f()] += 1' only calls 'f' once
+        let mem = self.get_val_from_frame(name);
+        let mut target = self.get_val(&mem);
+        for key in path {
+            target = match (target, key) {
+                (Value::ListVal(l), PathKey::Index(i)) => {
+                    let idx = check_index(i.get_val::<NumberVal>().value, l.data.len(), "List");
+                    &l.data[idx]
+                }
+                (Value::MapVal(m), PathKey::Index(i)) => match m.get(i) {
+                    Some(v) => v,
+                    None => missing_key(i),
+                },
+                (Value::StructVal(s), PathKey::Field(f)) => s.get(f.as_str()),
+                (o, _) => {
+                    RuntimeError::new(format!("Cannot access {:?} of {:?}", key, o)).raise();
+                }
+            };
+        }
+        return target.copy();
+    }
+
+    pub fn assign_path_in_frame(&mut self, name: &str, path: &[PathKey], val: Value) {
+        // 'xs[i][j] = v', 'm[k] = v' or 'p.x = v', the value held by the variable is changed in place
+        let (last, path) = path.split_last().unwrap();
+        let mut target = self.get_mut_val_from_frame(name);
+        for key in path {
+            target = match (target, key) {
+                (Value::ListVal(l), PathKey::Index(i)) => {
+                    let idx = check_index(i.get_val::<NumberVal>().value, l.data.len(), "List");
+                    &mut l.data[idx]
+                }
+                (Value::MapVal(m), PathKey::Index(i)) => match m.get_mut(i) {
+                    Some(v) => v,
+                    None => missing_key(i),
+                },
+                (Value::StructVal(s), PathKey::Field(f)) => s.get_mut(f.as_str()),
+                (o, _) => {
+                    RuntimeError::new(format!("Cannot access {:?} of {:?}", key, o)).raise();
+                }
+            };
+        }
+        match (target, last) {
+            (Value::ListVal(l), PathKey::Index(i)) => {
+                let idx = check_index(i.get_val::<NumberVal>().value, l.data.len(), "List");
+                l.data[idx] = val;
+            }
+            (Value::MapVal(m), PathKey::Index(i)) => m.insert(i.copy(), val), // New keys are added
+            (Value::StructVal(s), PathKey::Field(f)) => *s.get_mut(f.as_str()) = val,
+            (o, _) => {
+                RuntimeError::new(format!("Cannot access {:?} of {:?}", last, o)).raise();
+            }
+        }
+    }
+
+    pub fn get_val_typed_from_frame<T: AccessibleValue>(&self, name: &str) -> &T {
+        self.frames[self.find_frame_idx(name)].get_typed(name)
+    }
+
+    pub fn get_val_from_frame(&self, name: &str) -> MemRef {
+        self.find_frame_idx(name); // Unknown vars are an error here rather than on first access
+        return MemRef::frame(name.to_string());
     }
 
     pub fn get_val(&self, mem: &MemRef) -> &Value {
         if mem.is_stack_ref() {
             return self.working_memory.get_ref(mem);
         } else if mem.is_frame_ref() {
-            return self.frames[0].get(mem.get_as_frame());
+            let name = mem.get_as_frame();
+            return self.frames[self.find_frame_idx(name)].get(name);
         } else {
             &self.empty
         }
     }
 
     pub fn get_mut_val_from_frame(&mut self, name: &str) -> &mut Value {
-        let m = self.get_val_from_frame(name);
-        let s = m.get_as_frame();
-        self.frames[0].get_mut(name)
+        let idx = self.find_frame_idx(name);
+        self.frames[idx].get_mut(name)
     }
 }
@@ -58,6 +58,10 @@ impl Frame {
     pub fn add_var(&mut self, name: String, value: Value) -> bool {
         return self.vartable.add_var(name, value);
     }
+
+    pub fn set_var(&mut self, name: String, value: Value) -> Option<Value> {
+        return self.vartable.set_var(name, value);
+    }
 }
 
 impl VarTable {
@@ -85,4 +89,9 @@ impl VarTable {
             }
         };
     }
+
+    pub fn set_var(&mut self, name: String, value: Value) -> Option<Value> {
+        // Insert or overwrite, returning the previous value if there was one
+        return self.lookup.insert(name, value);
+    }
 }
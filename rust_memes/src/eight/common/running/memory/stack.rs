@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use crate::eight::common::running::error::RuntimeError;
 use crate::eight::common::running::memory::memref::{MemData, MemRef};
 use crate::eight::expressions::primary::noop::NoOp;
 use crate::eight::values::Value;
@@ -62,8 +63,7 @@ impl Stack {
         match self.data.get(idx) {
             Some(v) => v,
             None => {
-                // TODO Runtime exceptions
-                panic!("Invalid stack memory access! Stack idx {:?} doesn't exist", idx);
+                RuntimeError::new(format!("Invalid stack memory access! Stack idx {:?} doesn't exist", idx)).raise();
             }
         }
     }
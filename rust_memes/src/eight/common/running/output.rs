@@ -0,0 +1,57 @@
+use std::cell::RefCell;
+use std::fmt::{Debug, Formatter};
+use std::io::{self, BufWriter, Write};
+use std::rc::Rc;
+
+
+pub enum OutputSink {
+    Writer(Box<dyn Write>),          // Any writer, stdout and stderr by default
+    Buffer(Rc<RefCell<String>>),     // Collected in memory, the host keeps a handle to read it back
+    Callback(Box<dyn FnMut(&str)>),  // Called with every piece of text as it's printed
+}
+
+impl OutputSink {
+    pub fn stdout() -> Self {
+        OutputSink::Writer(Box::new(BufWriter::new(io::stdout())))
+    }
+
+    pub fn stderr() -> Self {
+        OutputSink::Writer(Box::new(io::stderr()))
+    }
+
+    pub fn buffer() -> (Self, Rc<RefCell<String>>) {
+        let buf = Rc::new(RefCell::new(String::new()));
+        (OutputSink::Buffer(Rc::clone(&buf)), buf)
+    }
+
+    pub fn write_str(&mut self, s: &str) -> io::Result<()> {
+        match self {
+            OutputSink::Writer(w) => w.write_all(s.as_bytes()),
+            OutputSink::Buffer(b) => {
+                b.borrow_mut().push_str(s);
+                Ok(())
+            }
+            OutputSink::Callback(f) => {
+                f(s);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self {
+            OutputSink::Writer(w) => w.flush(),
+            OutputSink::Buffer(_) | OutputSink::Callback(_) => Ok(()),
+        }
+    }
+}
+
+impl Debug for OutputSink {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            OutputSink::Writer(_) => write!(f, "OutputSink(Writer)"),
+            OutputSink::Buffer(b) => write!(f, "OutputSink(Buffer {} bytes)", b.borrow().len()),
+            OutputSink::Callback(_) => write!(f, "OutputSink(Callback)"),
+        };
+    }
+}
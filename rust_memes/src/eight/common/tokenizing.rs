@@ -1,3 +1,4 @@
 pub mod lexing;
 pub mod symbols;
+pub mod token;
 pub mod whitespace;
@@ -1,116 +1,442 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
 use crate::eight::common::parsing::util::slice_str;
-use crate::eight::common::tokenizing::symbols::Symbols;
-use log::{debug, info, trace};
+use crate::eight::common::tokenizing::symbols::{SymbolType, Symbols};
+use crate::eight::common::tokenizing::token::{Span, Token};
+use lazy_static::lazy_static;
+use log::{info, trace};
+use std::collections::HashMap;
 
-pub fn check_symbol(string: &str, symbol: Symbols) -> Option<Symbols> {
-    let sym = symbol.to_str();
-    let sym_len = sym.len();
+lazy_static! {
+    // Keywords are left out of the trie, they're only matched against whole words so 'letter' stays an identifier
+    static ref SYMBOL_TRIE: SymbolTrie = SymbolTrie::new(
+        Symbols::get_all()
+            .into_iter()
+            .filter(|sym| !KEYWORDS.contains_key(&sym.to_str()))
+            .collect()
+    );
+    static ref KEYWORDS: HashMap<String, Symbols> = Symbols::get_symbols_by_type(SymbolType::Keyword)
+        .into_iter()
+        .map(|sym| (sym.to_str(), sym))
+        .collect();
+}
+
+#[derive(Debug)]
+struct TrieNode {
+    children: [Option<usize>; 128], // Indexed by ASCII byte
+    symbol: Option<Symbols>,
+}
+
+#[derive(Debug)]
+pub struct SymbolTrie {
+    nodes: Vec<TrieNode>, // nodes[0] is the root
+}
+
+impl TrieNode {
+    fn new() -> Self {
+        TrieNode {
+            children: [None; 128],
+            symbol: None,
+        }
+    }
+}
+
+impl SymbolTrie {
+    pub fn new(symbols: Vec<Symbols>) -> Self {
+        let mut trie = SymbolTrie {
+            nodes: vec![TrieNode::new()],
+        };
+
+        for sym in symbols {
+            let mut node = 0;
+            for byte in sym.to_str().bytes() {
+                node = match trie.nodes[node].children[byte as usize] {
+                    Some(child) => child,
+                    None => {
+                        trie.nodes.push(TrieNode::new());
+                        let child = trie.nodes.len() - 1;
+                        trie.nodes[node].children[byte as usize] = Some(child);
+                        child
+                    }
+                };
+            }
+            trie.nodes[node].symbol = Some(sym);
+        }
+        trie
+    }
 
-    let sliced = slice_str(string, 0, sym_len);
+    pub fn longest_match(&self, s: &[u8], idx: usize) -> Option<(&Symbols, usize)> {
+        // Walk the trie from idx, remembering the last node that completed a symbol
+        let mut node = 0;
+        let mut found = None;
+        let mut offset = 0;
+
+        while idx + offset < s.len() && s[idx + offset] < 128 {
+            node = match self.nodes[node].children[s[idx + offset] as usize] {
+                Some(child) => child,
+                None => break,
+            };
+            offset += 1;
+
+            if let Some(sym) = &self.nodes[node].symbol {
+                found = Some((sym, offset));
+            }
+        }
+        found
+    }
+}
 
-    return if sliced == sym { Some(symbol) } else { None };
+#[derive(Clone)]
+struct SpanTracker {
+    line: usize,
+    col: usize, // column of pos, counted in chars rather than bytes
+    pos: usize,
 }
 
-pub fn get_possible_symbols(s: &str, idx: usize, symbols: &Vec<Symbols>) -> Vec<Symbols> {
-    let mut possible_syms = Vec::new();
+impl SpanTracker {
+    fn new() -> Self {
+        SpanTracker {
+            line: 1,
+            col: 1,
+            pos: 0,
+        }
+    }
 
-    for sym in symbols {
-        let snip = slice_str(s, idx, usize::MAX);
-        match check_symbol(snip, sym.clone()) {
-            Some(s) => {
-                possible_syms.push(s);
+    fn advance_to(&mut self, s: &str, target: usize) {
+        // Move the line and column position forward to the byte offset target
+        for ch in slice_str(s, self.pos, target).chars() {
+            if ch == '\n' {
+                self.line += 1;
+                self.col = 1;
+            } else {
+                self.col += 1;
+            }
+        }
+        self.pos = self.pos.max(target);
+    }
+
+    fn span(&mut self, s: &str, start: usize, end: usize) -> Span {
+        // Create the span for s[start..end] and move the line position past it
+        self.advance_to(s, start);
+        let span = Span::new(start, end, self.line, self.col);
+        self.advance_to(s, end);
+        span
+    }
+
+    fn span_at(&self, s: &str, start: usize, end: usize) -> Span {
+        // Create the span for s[start..end] without moving the line position, start must not be behind it
+        let mut tracker = self.clone();
+        tracker.advance_to(s, start);
+        Span::new(start, end, tracker.line, tracker.col)
+    }
+}
+
+fn parse_unicode_escape(s: &str, idx: usize) -> Option<(char, usize)> {
+    // Parse the '{XXXX}' part of a '\u{XXXX}' escape starting at idx, returning the char and the length consumed
+    let rest = slice_str(s, idx, usize::MAX);
+    if !rest.starts_with('{') {
+        return None;
+    }
+    let close = rest.find('}')?;
+    let hex = &rest[1..close];
+    if hex.is_empty() || hex.len() > 6 {
+        return None;
+    }
+    let code = u32::from_str_radix(hex, 16).ok()?;
+    return std::char::from_u32(code).map(|ch| (ch, close + 1));
+}
+
+fn lex_string(s: &str, start: usize, delimiter: &str, raw: bool, tracker: &SpanTracker) -> ParseResult<(String, usize)> {
+    // Read string contents from start up to the closing delimiter, processing escapes unless raw
+    // Returns the contents and the index just past the closing delimiter
+    let mut value = String::new();
+    let mut idx = start;
+
+    loop {
+        let rest = slice_str(s, idx, usize::MAX);
+        if rest.starts_with(delimiter) {
+            return Ok((value, idx + delimiter.len()));
+        }
+
+        let ch = match rest.chars().next() {
+            Some(ch) => ch,
+            None => {
+                return Err(Diagnostic::error(
+                    format!("Reached EOF while parsing a string, expected closing '{}'", delimiter),
+                    tracker.span_at(s, start - delimiter.len(), idx),
+                ));
+            }
+        };
+
+        if ch != '\\' || raw {
+            value.push(ch);
+            idx += ch.len_utf8();
+            continue;
+        }
+
+        // Escape sequence
+        let escaped = match rest[1..].chars().next() {
+            Some(e) => e,
+            None => {
+                return Err(Diagnostic::error(
+                    String::from("Reached EOF while parsing an escape sequence"),
+                    tracker.span_at(s, idx, idx + 1),
+                ));
+            }
+        };
+        let mut escape_len = 1 + escaped.len_utf8();
+
+        match escaped {
+            'n' => value.push('\n'),
+            't' => value.push('\t'),
+            'r' => value.push('\r'),
+            '0' => value.push('\0'),
+            '\\' => value.push('\\'),
+            '"' => value.push('"'),
+            '\'' => value.push('\''),
+            'u' => match parse_unicode_escape(s, idx + 2) {
+                Some((unicode_ch, len)) => {
+                    value.push(unicode_ch);
+                    escape_len += len;
+                }
+                None => {
+                    return Err(Diagnostic::error(
+                        String::from("Invalid unicode escape, expected '\\u{XXXX}' with 1 to 6 hex digits naming a valid char"),
+                        tracker.span_at(s, idx, idx + escape_len),
+                    ));
+                }
+            },
+            other => {
+                return Err(Diagnostic::error(
+                    format!("Unknown escape sequence '\\{}'", other),
+                    tracker.span_at(s, idx, idx + escape_len),
+                ));
             }
-            None => (),
         }
+        idx += escape_len;
     }
-    return possible_syms;
 }
 
-pub fn parse(s: String) -> Vec<Symbols> {
+fn lex_number(bytes: &[u8], start: usize) -> usize {
+    // Scan a numeric literal starting at a digit, returning the index just past it
+    // Validating the digits is left to the parser, so any trailing letters are kept to give a precise error
+    let at = |idx: usize| if idx < bytes.len() { bytes[idx] } else { 0 };
+    let is_digits = |b: u8| b.is_ascii_digit() || b == b'_';
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut idx = start;
+
+    // Hex, binary and octal, 0x.. 0b.. 0o..
+    if at(idx) == b'0' && b"xXbBoO".contains(&at(idx + 1)) {
+        idx += 2;
+        while is_word(at(idx)) {
+            idx += 1;
+        }
+        return idx;
+    }
+
+    while is_digits(at(idx)) {
+        idx += 1;
+    }
+    // Fractional part, only if a digit follows so '1..5' and '1.method()' aren't swallowed
+    if at(idx) == b'.' && at(idx + 1).is_ascii_digit() {
+        idx += 1;
+        while is_digits(at(idx)) {
+            idx += 1;
+        }
+    }
+    // Exponent, 1e5 1e-3 1E+10
+    if at(idx) == b'e' || at(idx) == b'E' {
+        let mut exp_idx = idx + 1;
+        if at(exp_idx) == b'+' || at(exp_idx) == b'-' {
+            exp_idx += 1;
+        }
+        if at(exp_idx).is_ascii_digit() {
+            idx = exp_idx;
+            while is_digits(at(idx)) {
+                idx += 1;
+            }
+        }
+    }
+    while is_word(at(idx)) {
+        idx += 1;
+    }
+    idx
+}
+
+fn push_literal(s: &str, start: Option<usize>, end: usize, tracker: &mut SpanTracker, parsed: &mut Vec<Token>) {
+    // Push any pending run of non-symbol characters as a literal, or as a keyword if the whole run is one
+    if let Some(start) = start {
+        let word = slice_str(s, start, end);
+        let sym = match KEYWORDS.get(word) {
+            Some(keyword) => keyword.clone(),
+            None => Symbols::LiteralSymb(String::from(word)),
+        };
+        parsed.push(Token::new(sym, tracker.span(s, start, end)));
+    }
+}
+
+pub fn parse(s: String) -> ParseResult<Vec<Token>> {
     info!("----------LEXING START----------");
     trace!("Starting lexing parsing on '{:?}'", s);
     info!("Found {} chars", s.len());
 
-    let symbols = Symbols::get_all();
+    let bytes = s.as_bytes();
     let mut parsed = Vec::new();
-    let mut outer_done = false;
+    let mut tracker = SpanTracker::new();
+    let mut literal_start: Option<usize> = None;
     let mut idx: usize = 0;
-    while !outer_done {
-        if idx >= s.len() {
-            break;
-        }
-        let mut possible_syms = get_possible_symbols(s.as_str(), idx, &symbols);
-        if possible_syms.len() == 0 {
-            let mut done = false;
-            let mut collect_offset = 0;
-            while !done {
-                if idx + collect_offset == s.len() {
-                    done = true;
-                    outer_done = true;
-                    collect_offset -= 1;
-                }
 
-                let next = get_possible_symbols(s.as_str(), idx + collect_offset, &symbols);
-                if next.len() == 0 {
-                    collect_offset += 1;
-                    continue;
-                } else {
-                    done = true;
-                    parsed.push(Symbols::LiteralSymb(String::from(slice_str(
-                        s.as_str(),
-                        idx,
-                        idx + collect_offset,
-                    ))));
-                    idx += collect_offset;
+    // Symbols are all ASCII, so matching on bytes never splits a multi-byte character
+    while idx < bytes.len() {
+        // A digit outside of a literal run starts a number, 'x1' is still an identifier
+        if literal_start.is_none() && bytes[idx].is_ascii_digit() {
+            let number_end = lex_number(bytes, idx);
+            parsed.push(Token::new(
+                Symbols::NumberLiteral(String::from(slice_str(s.as_str(), idx, number_end))),
+                tracker.span(s.as_str(), idx, number_end),
+            ));
+            idx = number_end;
+            trace!("Lexed {:?}", parsed.last());
+            continue;
+        }
+
+        let (sym, sym_len) = match SYMBOL_TRIE.longest_match(bytes, idx) {
+            Some((sym, sym_len)) => (sym.clone(), sym_len),
+            None => {
+                if literal_start.is_none() {
+                    literal_start = Some(idx);
                 }
+                idx += 1;
+                continue;
             }
+        };
+
+        let mut sym_start = idx;
+        let is_quote = sym == Symbols::Quote || sym == Symbols::TripleQuote;
+
+        // A lone 'r' directly before a quote marks a raw string, r"..." or r"""..."""
+        let raw = is_quote && literal_start.map_or(false, |start| slice_str(s.as_str(), start, idx) == "r");
+        if raw {
+            sym_start = literal_start.take().unwrap();
+        }
+        push_literal(s.as_str(), literal_start.take(), idx, &mut tracker, &mut parsed);
+        idx += sym_len;
+
+        if is_quote {
+            let (value, string_end) = lex_string(s.as_str(), idx, sym.to_str().as_str(), raw, &tracker)?;
+            parsed.push(Token::new(
+                Symbols::StringLiteral(value),
+                tracker.span(s.as_str(), sym_start, string_end),
+            ));
+            idx = string_end;
         } else {
-            trace!("Possible symbols: {:?}", possible_syms);
-            let mut longest_symbol_len = 0;
-            let mut sym_idx = 0;
-            for i in 0..possible_syms.len() {
-                let sym_len = possible_syms[i].to_str().len();
-
-                if sym_len > longest_symbol_len {
-                    longest_symbol_len = sym_len;
-                    sym_idx = i;
-                }
-            }
-            idx += longest_symbol_len;
-            let chosen_sym = possible_syms.remove(sym_idx);
-            match chosen_sym {
-                Symbols::Quote => {
-                    let mut quote_offset = 0;
-                    loop {
-                        if idx + quote_offset >= s.len() {
-                            panic!("Reached EOF while parsing a quote! Idx: '{}'", idx);
-                            // TODO Error messaging during lexing
-                        }
-                        let sym_str = slice_str(s.as_str(), idx + quote_offset, usize::MAX);
-                        // println!("Checking sym_str '{:?}'", sym_str);
-                        match check_symbol(sym_str, Symbols::Quote) {
-                            Some(_) => {
-                                trace!("Should break");
-                                break;
-                            }
-                            None => (),
-                        }
-                        quote_offset += 1;
-                    }
-                    trace!("Broke from loop");
-                    parsed.push(Symbols::StringLiteral(String::from(slice_str(
-                        s.as_str(),
-                        idx,
-                        idx + quote_offset,
-                    ))));
-                    idx += quote_offset + 1;
-                }
-                s => parsed.push(s),
-            }
-            trace!("Parsed {:?}", parsed);
+            parsed.push(Token::new(sym, tracker.span(s.as_str(), sym_start, idx)));
         }
+        trace!("Lexed {:?}", parsed.last());
     }
+    push_literal(s.as_str(), literal_start.take(), idx, &mut tracker, &mut parsed);
+
     info!("Lexed {} symbols", parsed.len());
     info!("----------LEXING END----------");
-    parsed
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::eight::common::tokenizing::symbols::Symbols;
+
+    fn symbols(code: &str) -> Vec<Symbols> {
+        parse(String::from(code)).unwrap().into_iter().map(|t| t.symbol).collect()
+    }
+
+    #[test]
+    fn lexes_the_corpus_like_before() {
+        // testing/lexed holds the symbols of each corpus script, one per line, any change to them has to be on purpose
+        let corpus = [
+            ("basic.8", include_str!("../../../../testing/basic.8"), include_str!("../../../../testing/lexed/basic.symbols")),
+            ("generic.8", include_str!("../../../../testing/generic.8"), include_str!("../../../../testing/lexed/generic.symbols")),
+            ("test.8", include_str!("../../../../testing/test.8"), include_str!("../../../../testing/lexed/test.symbols")),
+        ];
+        for (name, code, expected) in corpus.iter() {
+            let lexed: Vec<String> = symbols(code).iter().map(|sym| format!("{:?}", sym)).collect();
+            assert_eq!(lexed, expected.lines().collect::<Vec<&str>>(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn strings_process_escapes_unless_raw() {
+        let string = |s: &str| Symbols::StringLiteral(String::from(s));
+        assert_eq!(symbols(r#""a\n\t\\\"\u{e9}\0""#), vec![string("a\n\t\\\"\u{e9}\0")]);
+        assert_eq!(symbols(r##"r"a\n\""##), vec![string("a\\n\\")]);
+        assert_eq!(symbols("\"\"\"one\n\"two\"\n\"\"\""), vec![string("one\n\"two\"\n")]);
+        assert_eq!(symbols(r##"r"""a\""""##), vec![string("a\\")]);
+
+        let error = |code: &str| {
+            let d = parse(String::from(code)).unwrap_err();
+            (d.message, d.span.start, d.span.end)
+        };
+        assert_eq!(error(r#"let s = "a\q";"#), (String::from("Unknown escape sequence '\\q'"), 10, 12));
+        assert_eq!(error(r#""\u{110000}""#).0, "Invalid unicode escape, expected '\\u{XXXX}' with 1 to 6 hex digits naming a valid char");
+        assert_eq!(error("let s = \"open\nline"), (String::from("Reached EOF while parsing a string, expected closing '\"'"), 8, 18));
+    }
+
+    #[test]
+    fn spans_count_bytes_for_offsets_and_chars_for_columns() {
+        let tokens = parse(String::from("let café = \"ü\"; // ñ\nnaïve+é")).unwrap();
+        let spans: Vec<(Symbols, usize, usize, usize, usize)> = tokens.into_iter()
+            .filter(|t| t.symbol != Symbols::Space)
+            .map(|t| (t.symbol, t.span.start, t.span.end, t.span.line, t.span.col))
+            .collect();
+        let literal = |s: &str| Symbols::LiteralSymb(String::from(s));
+        assert_eq!(spans, vec![
+            (Symbols::Let, 0, 3, 1, 1),
+            (literal("café"), 4, 9, 1, 5),
+            (Symbols::Equal, 10, 11, 1, 10),
+            (Symbols::StringLiteral(String::from("ü")), 12, 16, 1, 12),
+            (Symbols::Semicolon, 16, 17, 1, 15),
+            (Symbols::SingleComment, 18, 20, 1, 17),
+            (literal("ñ"), 21, 23, 1, 20),
+            (Symbols::NewLine, 23, 24, 1, 21),
+            (literal("naïve"), 24, 30, 2, 1),
+            (Symbols::Add, 30, 31, 2, 6),
+            (literal("é"), 31, 33, 2, 7),
+        ]);
+    }
+
+    #[test]
+    fn keywords_are_whole_words() {
+        let literal = |s: &str| Symbols::LiteralSymb(String::from(s));
+        let words = |code: &str| -> Vec<Symbols> { symbols(code).into_iter().filter(|sym| *sym != Symbols::Space).collect() };
+        assert_eq!(
+            words("letter in_x iffy print returned x2for"),
+            vec![literal("letter"), literal("in_x"), literal("iffy"), literal("print"), literal("returned"), literal("x2for")]
+        );
+        assert_eq!(
+            words("for x in xs{if(x)else}"),
+            vec![
+                Symbols::For, literal("x"), Symbols::In, literal("xs"), Symbols::CurlyOpen,
+                Symbols::If, Symbols::ParenOpen, literal("x"), Symbols::ParenClose, Symbols::Else, Symbols::CurlyClose,
+            ]
+        );
+        assert_eq!(words("Let IF"), vec![literal("Let"), literal("IF")]);
+    }
+
+    #[test]
+    fn numbers_are_single_tokens() {
+        let number = |s: &str| Symbols::NumberLiteral(String::from(s));
+        let words = |code: &str| -> Vec<Symbols> { symbols(code).into_iter().filter(|sym| *sym != Symbols::Space).collect() };
+        assert_eq!(
+            words("1_000 0xff_ff 0b101 1.5e3 2E-3 3.6"),
+            vec![number("1_000"), number("0xff_ff"), number("0b101"), number("1.5e3"), number("2E-3"), number("3.6")]
+        );
+        // Ranges and methods keep their '.', trailing letters stay on the number so the parser can point at them
+        assert_eq!(words("1..5"), vec![number("1"), Symbols::Range, number("5")]);
+        assert_eq!(
+            words("1.abs() 5In x1"),
+            vec![
+                number("1"), Symbols::ValueAccessor, Symbols::LiteralSymb(String::from("abs")), Symbols::ParenOpen, Symbols::ParenClose,
+                number("5In"), Symbols::LiteralSymb(String::from("x1")),
+            ]
+        );
+    }
 }
@@ -9,6 +9,7 @@ macro_rules! symbols {
             pub enum Symbols {
                 LiteralSymb(String),
                 StringLiteral(String),
+                NumberLiteral(String),
                 $(
                     #[strum(serialize = $ch)]
                     $name
@@ -94,6 +95,9 @@ macro_rules! symbols {
                     Self::StringLiteral(s) => {
                       return s.clone();
                     },
+                    Self::NumberLiteral(s) => {
+                      return s.clone();
+                    },
                     $(
                     Self::$name => {
                         return String::from($ch);
@@ -142,25 +146,62 @@ symbols![
     (Multiply, "*", [SymbolType::Operator]),
     (Divide, "/", [SymbolType::Operator]),
     (Modulus, "%", [SymbolType::Operator]),
+    (Not, "!", [SymbolType::Operator]),
+    (Range, "..", [SymbolType::Operator]),
+    (Arrow, "->", [SymbolType::Operator]),
     // Assignment Operators
     (Equal, "=", [SymbolType::Assign]),
+    (AddAssign, "+=", [SymbolType::Assign]),
+    (SubAssign, "-=", [SymbolType::Assign]),
+    (MultiplyAssign, "*=", [SymbolType::Assign]),
+    (DivideAssign, "/=", [SymbolType::Assign]),
+    (ModulusAssign, "%=", [SymbolType::Assign]),
+    (ExponentAssign, "**=", [SymbolType::Assign]),
     // Boolean Operators
     (EqualityCheck, "==", [SymbolType::Operator]),
+    (NotEqual, "!=", [SymbolType::Operator]),
+    (LessThan, "<", [SymbolType::Operator]),
+    (LessThanOrEqual, "<=", [SymbolType::Operator]),
+    (GreaterThan, ">", [SymbolType::Operator]),
+    (GreaterThanOrEqual, ">=", [SymbolType::Operator]),
+    (And, "&&", [SymbolType::Operator]),
+    (Or, "||", [SymbolType::Operator]),
     // Accessors
     (ClassAccessor, "::", [SymbolType::Accessor]),
     (ValueAccessor, ".", [SymbolType::Accessor]),
     // Var create
     (Let, "let", [SymbolType::Keyword]),
+    // Control flow
+    (If, "if", [SymbolType::Keyword]),
+    (Else, "else", [SymbolType::Keyword]),
+    (While, "while", [SymbolType::Keyword]),
+    (For, "for", [SymbolType::Keyword]),
+    (In, "in", [SymbolType::Keyword]),
+    (Break, "break", [SymbolType::Keyword]),
+    (Continue, "continue", [SymbolType::Keyword]),
+    (Match, "match", [SymbolType::Keyword]),
+    (FatArrow, "=>", [SymbolType::Delimiter]), // Match arms, 'Algo::Linear => { ... }'
+    // Functions
+    (Func, "func", [SymbolType::Keyword]),
+    (Return, "return", [SymbolType::Keyword]),
+    (Pipe, "|", [SymbolType::Delimiter]), // Closure parameters, '|x: Number| x * 2'
+    // Types
+    (Struct, "struct", [SymbolType::Keyword]),
+    (Enum, "enum", [SymbolType::Keyword]),
     // End Expression Delimiter
     (Semicolon, ";", [SymbolType::Delimiter]),
     (Comma, ",", [SymbolType::Delimiter]),
+    (Colon, ":", [SymbolType::Delimiter]),
     // Brackets
     (ParenOpen, "(", [SymbolType::Bracket]),
     (ParenClose, ")", [SymbolType::Bracket]),
     (BracketOpen, "[", [SymbolType::Bracket]),
     (BracketClose, "]", [SymbolType::Bracket]),
+    (CurlyOpen, "{", [SymbolType::Bracket]),
+    (CurlyClose, "}", [SymbolType::Bracket]),
     // String quoting
     (Quote, "\"", [SymbolType::StringQuote]),
+    (TripleQuote, "\"\"\"", [SymbolType::StringQuote]),
     // Whitespace
     (NewLine, "\n", [SymbolType::Whitespace, SymbolType::Newline]),
     (LineReturn, "\r", [SymbolType::Whitespace, SymbolType::Newline]),
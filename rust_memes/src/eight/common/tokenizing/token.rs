@@ -0,0 +1,41 @@
+use crate::eight::common::tokenizing::symbols::Symbols;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Span {
+    pub(crate) start: usize, // byte offset into the source, inclusive
+    pub(crate) end: usize,   // byte offset into the source, exclusive
+    pub(crate) line: usize,  // line the span starts on, starting at 1
+    pub(crate) col: usize,   // column the span starts on in chars, starting at 1
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, col: usize) -> Self {
+        Span {
+            start,
+            end,
+            line,
+            col,
+        }
+    }
+
+    pub fn empty() -> Self {
+        Span::new(0, 0, 1, 1)
+    }
+
+    pub fn to(&self, other: &Span) -> Span {
+        // Create a span covering from the start of this span to the end of the other
+        Span::new(self.start, other.end.max(self.end), self.line, self.col)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Token {
+    pub(crate) symbol: Symbols,
+    pub(crate) span: Span,
+}
+
+impl Token {
+    pub fn new(symbol: Symbols, span: Span) -> Self {
+        Token { symbol, span }
+    }
+}
@@ -10,24 +10,13 @@ pub struct CodeWhitespace {
 pub struct CodeChunk {
     pub(crate) str_data: String,
     is_whitespace: bool,
-    newlines: i8,
+    newlines: usize,
 }
 
 pub fn check_is_whitespace(ch: &str) -> bool {
-    return if ch.len() == 1 {
-        if ch == " " || ch == "\n" || ch == "\r" || ch == "\t" || ch == "" {
-            true
-        } else {
-            false
-        }
-    } else {
-        for i in 0..ch.len() {
-            if !check_is_whitespace(&ch[i..i + 1]) {
-                return false;
-            }
-        }
-        true
-    };
+    return ch
+        .chars()
+        .all(|c| c == ' ' || c == '\n' || c == '\r' || c == '\t');
 }
 
 impl<'a> CodeWhitespace {
@@ -38,8 +27,9 @@ impl<'a> CodeWhitespace {
 
         let mut start_index = index;
 
-        while index < snippet.len() {
-            let ch: &str = &snippet[index..index + 1];
+        for (char_index, c) in snippet.char_indices() {
+            index = char_index;
+            let ch: &str = &snippet[index..index + c.len_utf8()];
             if check_is_whitespace(ch) {
                 if !is_whitespace {
                     is_whitespace = true;
@@ -62,8 +52,8 @@ impl<'a> CodeWhitespace {
                     start_index = index;
                 }
             }
-            index += 1;
         }
+        index = snippet.len();
         // Handle EOF
         let ch: &str = &snippet[start_index..index];
         s.push(CodeChunk::new(String::from(ch), check_is_whitespace(ch)));
@@ -100,7 +90,7 @@ impl<'a> CodeWhitespace {
         }
     }
 
-    pub fn newline_count(self) -> i8 {
+    pub fn newline_count(self) -> usize {
         let mut count = 0;
         for snip in self.snippets.iter() {
             count += snip.newlines;
@@ -112,7 +102,7 @@ impl<'a> CodeWhitespace {
         return self.cur_index;
     }
 
-    pub fn get_line_no_from_index(&self, index: usize) -> i8 {
+    pub fn get_line_no_from_index(&self, index: usize) -> usize {
         let mut count = 0;
 
         for i in 0..index {
@@ -121,7 +111,7 @@ impl<'a> CodeWhitespace {
         return count + 1;
     }
 
-    pub fn get_current_line_no(&self) -> i8 {
+    pub fn get_current_line_no(&self) -> usize {
         return self.get_line_no_from_index(self.cur_index);
     }
 
@@ -160,10 +150,10 @@ impl<'a> CodeWhitespace {
         }
     }
 
-    pub fn peek_multiple(&mut self, times: i8) -> bool {
+    pub fn peek_multiple(&mut self, times: usize) -> bool {
         // Ensure the next 'times' tokens are non-whitespace
         for i in 0..times {
-            if self.peek_by_idx(self.cur_index + i as usize) {
+            if self.peek_by_idx(self.cur_index + i) {
                 continue;
             } else {
                 return false;
@@ -260,7 +250,7 @@ impl CodeChunk {
         return self.is_whitespace;
     }
 
-    pub fn get_newlines(&self) -> i8 {
+    pub fn get_newlines(&self) -> usize {
         return self.newlines;
     }
 
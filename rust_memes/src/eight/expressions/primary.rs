@@ -1,17 +1,38 @@
 use crate::eight::common::running::interpreter::EightInterpreter;
 use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::expressions::primary::assignment::Assignment;
+use crate::eight::expressions::primary::enumdecl::EnumDecl;
+use crate::eight::expressions::primary::exprstatement::ExprStatement;
+use crate::eight::expressions::primary::forexpr::ForExpr;
+use crate::eight::expressions::primary::funcdecl::FuncDecl;
+use crate::eight::expressions::primary::ifexpr::IfExpr;
+use crate::eight::expressions::primary::loopcontrol::LoopControl;
+use crate::eight::expressions::primary::matchexpr::MatchExpr;
 use crate::eight::expressions::primary::noop::NoOp;
 use crate::eight::expressions::primary::print::Print;
 use crate::eight::expressions::primary::returnexpr::ReturnExpr;
+use crate::eight::expressions::primary::structdecl::StructDecl;
 use crate::eight::expressions::primary::variable::Variable;
+use crate::eight::expressions::primary::whileexpr::WhileExpr;
 use enum_dispatch::enum_dispatch;
 use crate::eight::expressions::primary::format::Format;
 
+pub mod assignment;
+pub mod block;
+pub mod enumdecl;
+pub mod exprstatement;
+pub mod forexpr;
+pub mod funcdecl;
+pub mod ifexpr;
+pub mod loopcontrol;
+pub mod matchexpr;
 pub mod noop;
 pub mod print;
 pub mod returnexpr;
+pub mod structdecl;
 pub mod variable;
 pub mod format;
+pub mod whileexpr;
 
 
 #[enum_dispatch]
@@ -20,28 +41,59 @@ pub trait ExpressionRelations {
 }
 
 pub trait RunnableExpression {
-    fn run_expr(self, interpreter: &mut EightInterpreter);
+    fn run_expr(&self, interpreter: &mut EightInterpreter);
 }
 
 #[enum_dispatch(ExpressionRelations)]
 #[derive(Debug)]
 pub enum Expression {
     VariableTokenExpr(Box<Variable>),
+    AssignmentExpr(Box<Assignment>),
+    IfExpr(Box<IfExpr>),
+    WhileExpr(Box<WhileExpr>),
+    ForExpr(Box<ForExpr>),
+    MatchExpr(Box<MatchExpr>),
+    LoopControlExpr(LoopControl),
+    FuncDeclExpr(Box<FuncDecl>),
+    StructDeclExpr(StructDecl),
+    EnumDeclExpr(EnumDecl),
     ReturnExpr(ReturnExpr),
     PrintExpr(Box<Print>),
+    ExprStatementExpr(Box<ExprStatement>),
     EndParsingExpr(NoOp)
 }
 
 impl Expression {
-    // Remember if you copy the trait func sig to remove the &mut -> mut to consume the expression
-    pub(crate) fn run_expr(mut self, interpreter: &mut EightInterpreter) {
+    // Expressions are borrowed while running so loop bodies can be run again
+    pub(crate) fn run_expr(&self, interpreter: &mut EightInterpreter) {
         match self {
-            Expression::VariableTokenExpr(mut v) => { v.run_expr(interpreter) }
-            Expression::PrintExpr(mut p) => { p.run_expr(interpreter) }
-            _ => {
-                // Add other expressions here
-                todo!()
-            }
+            Expression::VariableTokenExpr(v) => { v.run_expr(interpreter) }
+            Expression::AssignmentExpr(a) => { a.run_expr(interpreter) }
+            Expression::IfExpr(i) => { i.run_expr(interpreter) }
+            Expression::WhileExpr(w) => { w.run_expr(interpreter) }
+            Expression::ForExpr(f) => { f.run_expr(interpreter) }
+            Expression::MatchExpr(m) => { m.run_expr(interpreter) }
+            Expression::LoopControlExpr(l) => { l.run_expr(interpreter) }
+            Expression::PrintExpr(p) => { p.run_expr(interpreter) }
+            Expression::ExprStatementExpr(e) => { e.run_expr(interpreter) }
+            Expression::FuncDeclExpr(f) => { f.run_expr(interpreter) }
+            Expression::StructDeclExpr(s) => { s.run_expr(interpreter) }
+            Expression::EnumDeclExpr(e) => { e.run_expr(interpreter) }
+            Expression::ReturnExpr(r) => { r.run_expr(interpreter) }
+            Expression::EndParsingExpr(_) => {}
         };
     }
+
+    // Whether running this always ends in a 'return', functions with an output need it for their body
+    pub(crate) fn always_returns(&self) -> bool {
+        match self {
+            Expression::ReturnExpr(_) => true,
+            Expression::IfExpr(i) => {
+                i.else_block.as_ref().is_some_and(|b| b.always_returns())
+                    && i.branches.iter().all(|b| b.block.always_returns())
+            }
+            Expression::MatchExpr(m) => m.arms.iter().all(|a| a.body.always_returns()),
+            _ => false,
+        }
+    }
 }
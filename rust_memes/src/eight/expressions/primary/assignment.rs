@@ -0,0 +1,267 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::parsing::ParsableExpression;
+use crate::eight::common::running::interpreter::{EightInterpreter, PathKey};
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::primary::{Expression, ExpressionRelations, RunnableExpression};
+use crate::eight::expressions::secondary::fieldaccess::field_type;
+use crate::eight::expressions::secondary::operators::binary::{check_operand_types, run_binop, BinaryOperators};
+use crate::eight::expressions::secondary::{parse_secondary_expression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::values::ValueTypes;
+use log::{debug, trace};
+
+
+#[derive(Debug)]
+pub enum AccessStep {
+    Index(SecondaryExpression), // 'xs[i] = v' or 'm[k] = v'
+    Field(String),              // 'p.x = v'
+}
+
+#[derive(Debug)]
+pub struct Assignment {
+    pub(crate) name: String,
+    pub(crate) path: Vec<AccessStep>, // 'ps[0].x = v' assigns into the value held by the variable instead of replacing it
+    pub(crate) op: Option<BinaryOperators>, // 'xs[i] += v' applies the operator to the value already at the path
+    pub value: SecondaryExpression,
+}
+
+impl RunnableExpression for Assignment {
+    fn run_expr(&self, interpreter: &mut EightInterpreter) {
+        let mut path = Vec::new();
+        for step in &self.path {
+            path.push(match step {
+                AccessStep::Index(ix) => {
+                    let m = interpreter.step(ix);
+                    PathKey::Index(interpreter.copy_val(m))
+                }
+                AccessStep::Field(f) => PathKey::Field(f.clone()),
+            });
+        }
+
+        let v = interpreter.step(&self.value);
+        let mut val = interpreter.copy_val(v);
+        if let Some(op) = self.op {
+            // The path was evaluated once above, it's read through and then written back
+            let current = interpreter.get_path_in_frame(self.name.as_str(), &path);
+            val = run_binop(op, &current, &val);
+        }
+
+        if path.is_empty() {
+            interpreter.assign_val_in_frame(self.name.as_str(), val);
+        } else {
+            interpreter.assign_path_in_frame(self.name.as_str(), &path, val);
+        }
+    }
+}
+
+impl ExpressionRelations for Box<Assignment> {
+    fn get_expr_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        let indices = self.path.iter().filter_map(|step| match step {
+            AccessStep::Index(ix) => Some(ix),
+            AccessStep::Field(_) => None,
+        });
+        for ex in indices.chain(std::iter::once(&self.value)) {
+            for r in ex.get_references() {
+                rr.push(Box::from(r));
+            }
+        }
+
+        vec![RelationEntry::new(vec![], vec![self.name.clone()], rr)]
+    }
+}
+
+impl ParsableExpression for Assignment {
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>> {
+        debug!("Attempting Assignment parsing");
+        // '<name> <op> <val>' where op is '=' or a compound operator like '+='
+        let start_idx = context.get_index();
+
+        let name = match context.get()? {
+            Symbols::LiteralSymb(name) => name,
+            _ => {
+                trace!("No variable name found for assignment");
+                return Ok(None);
+            }
+        };
+        context.increment();
+
+        // 'xs[i] = v' or 'p.x = v', anything that doesn't parse as a path is left for the other expressions
+        let mut path = Vec::new();
+        loop {
+            match context.get_safe() {
+                Ok(Symbols::BracketOpen) => {
+                    context.increment();
+                    let ix_idx = context.get_index();
+                    let ix = match parse_secondary_expression(context) {
+                        Ok(ix) if context.get_safe() == Ok(Symbols::BracketClose) => ix,
+                        _ => {
+                            context.jump(start_idx);
+                            return Ok(None);
+                        }
+                    };
+                    path.push((AccessStep::Index(ix), context.span_since(ix_idx)));
+                    context.increment(); // past ']'
+                }
+                Ok(Symbols::ValueAccessor) => {
+                    context.increment();
+                    match context.get_safe() {
+                        Ok(Symbols::LiteralSymb(field)) => {
+                            path.push((AccessStep::Field(field), context.get_span()));
+                            context.increment();
+                        }
+                        _ => {
+                            context.jump(start_idx);
+                            return Ok(None);
+                        }
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        let assign_sym = context.get()?;
+        let compound_op = if assign_sym == Symbols::Equal {
+            None
+        } else {
+            match sym_to_compound_op(&assign_sym) {
+                Some(op) => Some(op),
+                None => {
+                    trace!("No assignment operator found after '{}'", name);
+                    context.jump(start_idx);
+                    return Ok(None);
+                }
+            }
+        };
+
+        if !context.get_varnames().contains(&name) {
+            context.jump(start_idx);
+            return Err(context.error(format!("Cannot assign to undeclared variable '{}'", name))
+                .with_note(format!("declare it first with 'let {} = ...;'", name)));
+        }
+        let var_typ = context.get_vartype(name.as_str());
+        let mut typ = var_typ.clone();
+        let mut refined_key = None;
+        for (step, span) in &path {
+            let ix = match step {
+                AccessStep::Index(ix) => ix,
+                AccessStep::Field(field) => {
+                    typ = match field_type(context, &typ, field.as_str()) {
+                        Ok(t) => t,
+                        Err(e) => return Err(Diagnostic::error(e, *span)),
+                    };
+                    continue;
+                }
+            };
+            let ix_typ = match ix.get_type() {
+                Ok(t) => t,
+                Err(s) => return Err(Diagnostic::error(format!("Error getting type of index, '{}'", s), *span)),
+            };
+            typ = match typ {
+                ValueTypes::ListType(inner) if ix_typ == ValueTypes::NumberType => *inner,
+                ValueTypes::ListType(_) => {
+                    return Err(Diagnostic::error(format!("Indices must be Number, found {}", ix_typ), *span));
+                }
+                ValueTypes::MapType(_, _) if !ix_typ.is_hashable() => {
+                    return Err(Diagnostic::error(format!("{} can't be used as a Map key", ix_typ), *span)
+                        .with_note(String::from("keys have to be String, Number or Boolean")));
+                }
+                ValueTypes::MapType(k, v) if *k == ValueTypes::NoneType && path.len() == 1 => {
+                    // 'm[k] = v' on an empty '{}' gives it its types, the value type is known once the rhs is parsed
+                    refined_key = Some(ix_typ);
+                    *v
+                }
+                ValueTypes::MapType(k, v) if *k == ValueTypes::NoneType || k.accepts(&ix_typ) => *v,
+                ValueTypes::MapType(k, _) => {
+                    return Err(Diagnostic::error(format!("Mismatched types, the Map has {} keys but the index is {}", k, ix_typ), *span));
+                }
+                ValueTypes::StringType => {
+                    context.jump(start_idx);
+                    return Err(context.error(format!("Cannot assign to an index of '{}', Strings can't be changed in place", name))
+                        .with_note(format!("build a new String and assign it with '{} = ...;'", name)));
+                }
+                o => {
+                    context.jump(start_idx);
+                    return Err(context.error(format!("Cannot index into '{}' of type {}", name, o))
+                        .with_note(String::from("only Lists, Strings and Maps can be indexed")));
+                }
+            };
+        }
+        let path: Vec<AccessStep> = path.into_iter().map(|(step, _)| step).collect();
+        context.increment(); // past the assignment operator
+
+        let value = parse_secondary_expression(context)?;
+        if let Some(op) = compound_op {
+            // 'a += b' is checked like 'a + b', the operator is applied when it runs
+            match value.get_type() {
+                Ok(t) => check_operand_types(op, &typ, &t, context.span_since(start_idx))?,
+                Err(s) => {
+                    return Err(Diagnostic::error(format!("Error getting type of expression, '{}'", s), context.span_since(start_idx)));
+                }
+            }
+        }
+
+        match value.get_type() {
+            Ok(t) if path.is_empty() && typ.is_unknown_collection() && typ.accepts(&t) => {
+                // 'let xs = [];' gets its element type from the first list assigned to it
+                context.refine_var(name.as_str(), t);
+            }
+            Ok(t) if refined_key.is_some() => {
+                let key = refined_key.take().unwrap();
+                context.refine_var(name.as_str(), ValueTypes::MapType(Box::new(key), Box::new(t)));
+            }
+            Ok(t) if typ.accepts(&t) => (),
+            Ok(t) if !path.is_empty() => {
+                let what = match path.last() {
+                    Some(AccessStep::Field(f)) => format!("field '{}'", f),
+                    _ => String::from("the element"),
+                };
+                return Err(Diagnostic::error(
+                    format!("Mismatched types, can't assign a {} value to {} of '{}' of type {}", t, what, name, var_typ),
+                    context.span_since(start_idx),
+                ).with_note(format!("{} is {}", what, typ)));
+            }
+            Ok(t) => {
+                return Err(Diagnostic::error(
+                    format!("Mismatched types, can't assign a {} value to '{}' of type {}", t, name, typ),
+                    context.span_since(start_idx),
+                ).with_note(format!("use 'let {} = ...;' to shadow it with a new type", name)));
+            }
+            Err(s) => {
+                return Err(Diagnostic::error(
+                    format!("Error getting type of expression, '{}'", s),
+                    context.span_since(start_idx),
+                ));
+            }
+        }
+
+        match context.get()? {
+            Symbols::Semicolon => {
+                context.increment();
+                return Ok(Some(Expression::AssignmentExpr(Box::new(Assignment {
+                    name,
+                    path,
+                    op: compound_op,
+                    value,
+                }))));
+            }
+            o => {
+                return Err(Diagnostic::error(String::from("Expected ';' at end of expression"), context.span_after_previous())
+                    .with_note(format!("found '{}' instead", o.to_str())));
+            }
+        }
+    }
+}
+
+fn sym_to_compound_op(sym: &Symbols) -> Option<BinaryOperators> {
+    return match sym {
+        Symbols::AddAssign => Some(BinaryOperators::Add),
+        Symbols::SubAssign => Some(BinaryOperators::Sub),
+        Symbols::MultiplyAssign => Some(BinaryOperators::Multiply),
+        Symbols::DivideAssign => Some(BinaryOperators::Divide),
+        Symbols::ModulusAssign => Some(BinaryOperators::Modulo),
+        Symbols::ExponentAssign => Some(BinaryOperators::Exponent),
+        _ => None,
+    };
+}
@@ -0,0 +1,110 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::common::tokenizing::token::Span;
+use crate::eight::expressions::primary::{Expression, ExpressionRelations};
+use crate::eight::parse_expr;
+use log::{debug, trace};
+
+
+#[derive(Debug)]
+pub struct Block {
+    pub(crate) exprs: Vec<Expression>,
+}
+
+impl Block {
+    pub fn always_returns(&self) -> bool {
+        // Anything after a statement that always returns is never reached
+        return self.exprs.iter().any(|e| e.always_returns());
+    }
+
+    pub fn run(&self, interpreter: &mut EightInterpreter) {
+        interpreter.run_block(&self.exprs);
+    }
+
+    pub fn get_references(&self) -> Vec<Box<RelationEntry>> {
+        let mut rr = Vec::new();
+        for ex in &self.exprs {
+            for r in ex.get_expr_references() {
+                rr.push(Box::from(r));
+            }
+        }
+        return rr;
+    }
+
+    pub fn parse(context: &mut Context) -> ParseResult<Option<Block>> {
+        // '{ <expr>* }', variables declared inside are only visible until the closing '}'
+        let open_span = context.get_span();
+        match context.get()? {
+            Symbols::CurlyOpen => (),
+            _ => {
+                trace!("No '{{' found for block");
+                return Ok(None);
+            }
+        }
+        context.increment(); // past '{'
+
+        context.push_scope();
+        let exprs = parse_block_exprs(context, open_span);
+        context.pop_scope();
+
+        return Ok(Some(Block { exprs: exprs? }));
+    }
+}
+
+fn parse_block_exprs(context: &mut Context, open_span: Span) -> ParseResult<Vec<Expression>> {
+    let mut exprs = Vec::new();
+    loop {
+        match context.get_safe() {
+            Ok(Symbols::CurlyClose) => {
+                context.increment();
+                return Ok(exprs);
+            }
+            Ok(_) => (),
+            Err(_) => {
+                return Err(unclosed_block_error(open_span));
+            }
+        }
+
+        match parse_expr(context) {
+            Ok(Some(Expression::EndParsingExpr(_))) => {
+                return Err(unclosed_block_error(open_span));
+            }
+            Ok(Some(x)) => exprs.push(x),
+            Ok(None) => (),
+            Err(e) => {
+                // Skip the rest of the block so parsing resumes after it rather than inside it
+                debug!("Parse error in block, skipping to its closing '}}'");
+                skip_past_block_end(context);
+                return Err(e);
+            }
+        }
+    }
+}
+
+fn unclosed_block_error(open_span: Span) -> Diagnostic {
+    Diagnostic::error(String::from("Unclosed '{', expected '}' before the end of the file"), open_span)
+}
+
+pub fn skip_past_block_end(context: &mut Context) {
+    // Move past the '}' matching an already consumed '{', counting any nested blocks
+    let mut depth = 1;
+    loop {
+        match context.get_safe() {
+            Ok(Symbols::CurlyOpen) => depth += 1,
+            Ok(Symbols::CurlyClose) => {
+                depth -= 1;
+                if depth == 0 {
+                    context.increment();
+                    context.mark_recovered();
+                    return;
+                }
+            }
+            Ok(_) => (),
+            Err(_) => return, // EOF
+        }
+        context.increment();
+    }
+}
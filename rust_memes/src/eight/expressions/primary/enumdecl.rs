@@ -0,0 +1,131 @@
+use crate::eight::common::diagnostics::ParseResult;
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::parsing::util::consume_until_symbol;
+use crate::eight::common::parsing::ParsableExpression;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::primary::funcdecl::parse_name;
+use crate::eight::expressions::primary::structdecl::check_type_name;
+use crate::eight::expressions::primary::{Expression, ExpressionRelations, RunnableExpression};
+use crate::eight::literals::basic::enums::EnumDef;
+use crate::eight::values::{parse_type, ValueTypes};
+use log::{debug, trace};
+
+
+#[derive(Debug)]
+pub struct EnumDecl {
+    pub(crate) def: EnumDef,
+}
+
+impl RunnableExpression for EnumDecl {
+    fn run_expr(&self, _interpreter: &mut EightInterpreter) {
+        // Only the parser needs the declaration, values carry their own variant
+        trace!("Skipping declaration of enum '{}'", self.def.name);
+    }
+}
+
+impl ExpressionRelations for EnumDecl {
+    fn get_expr_references(&self) -> Vec<RelationEntry> {
+        vec![]
+    }
+}
+
+impl ParsableExpression for EnumDecl {
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>> {
+        debug!("Attempting EnumDecl parsing");
+        // 'enum <Name> { <Variant>, <Variant>(<type>, ...), ... }'
+        match context.get()? {
+            Symbols::Enum => (),
+            _ => {
+                trace!("No enum keyword found");
+                return Ok(None);
+            }
+        }
+        if !context.is_top_level() {
+            return Err(context.error(String::from("Enums can only be declared at the top level")));
+        }
+        context.increment(); // past 'enum'
+
+        let def = parse_enum_def(context)?;
+        context.enums.insert(def.name.clone(), def.clone());
+        return Ok(Some(Expression::EnumDeclExpr(EnumDecl { def })));
+    }
+}
+
+fn parse_enum_def(context: &mut Context) -> ParseResult<EnumDef> {
+    let name_idx = context.get_index();
+    let name = parse_name(context, "enum name")?;
+    check_type_name(context, name.as_str(), name_idx)?;
+
+    match context.get()? {
+        Symbols::CurlyOpen => context.increment(),
+        o => {
+            return Err(context.error(format!("Expected '{{' and the variants of '{}'", name))
+                .with_note(format!("found '{}' instead", o.to_str())));
+        }
+    }
+
+    // On a bad variant skip the rest of the body, so parsing resumes after the declaration
+    let variants = parse_variants(context, name.as_str());
+    if variants.is_err() {
+        if consume_until_symbol(context, Symbols::CurlyClose).is_ok() {
+            context.mark_recovered();
+        }
+    }
+    let variants = variants?;
+    if variants.is_empty() {
+        context.jump(name_idx);
+        return Err(context.error(format!("Enum '{}' needs at least one variant", name)));
+    }
+    debug!("Declared enum '{}' with variants {:?}", name, variants);
+    return Ok(EnumDef { name, variants });
+}
+
+fn parse_variants(context: &mut Context, name: &str) -> ParseResult<Vec<(String, Vec<ValueTypes>)>> {
+    // '<Variant>' or '<Variant>(<type>, ...)', separated by ',' up to and past the '}'
+    let mut variants: Vec<(String, Vec<ValueTypes>)> = Vec::new();
+    loop {
+        if context.get()? == Symbols::CurlyClose {
+            context.increment();
+            return Ok(variants);
+        }
+
+        let variant_idx = context.get_index();
+        let variant = parse_name(context, "variant name")?;
+        if variants.iter().any(|(n, _)| n == &variant) {
+            context.jump(variant_idx);
+            return Err(context.error(format!("Variant '{}' is declared more than once", variant)));
+        }
+
+        let mut payload = Vec::new();
+        if context.get()? == Symbols::ParenOpen {
+            context.increment(); // past '('
+            loop {
+                payload.push(parse_type(context)?);
+                match context.get()? {
+                    Symbols::Comma => context.increment(),
+                    Symbols::ParenClose => {
+                        context.increment();
+                        break;
+                    }
+                    o => {
+                        return Err(context.error(format!("Expected ',' or ')' in the payload of '{}', found '{}'", variant, o.to_str())));
+                    }
+                }
+            }
+        }
+        variants.push((variant, payload));
+
+        match context.get()? {
+            Symbols::Comma => context.increment(),
+            Symbols::CurlyClose => {
+                context.increment();
+                return Ok(variants);
+            }
+            o => {
+                return Err(context.error(format!("Expected ',' or '}}' in the variants of '{}', found '{}'", name, o.to_str())));
+            }
+        }
+    }
+}
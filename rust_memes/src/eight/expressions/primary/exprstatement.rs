@@ -0,0 +1,62 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::parsing::ParsableExpression;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::primary::{Expression, ExpressionRelations, RunnableExpression};
+use crate::eight::expressions::secondary::{parse_operand, parse_secondary_expression, SecondaryExpression, TypedSecondaryExpression};
+use log::{debug, trace};
+
+
+#[derive(Debug)]
+pub struct ExprStatement {
+    pub(crate) expr: SecondaryExpression,
+}
+
+impl RunnableExpression for ExprStatement {
+    fn run_expr(&self, interpreter: &mut EightInterpreter) {
+        // Only run for its side effects, the result is dropped straight away
+        let m = interpreter.step(&self.expr);
+        interpreter.pop_stack_vals(m);
+    }
+}
+
+impl ExpressionRelations for Box<ExprStatement> {
+    fn get_expr_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        for r in self.expr.get_references() {
+            rr.push(Box::from(r));
+        }
+
+        vec![RelationEntry::new(vec![], vec![], rr)]
+    }
+}
+
+impl ParsableExpression for ExprStatement {
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>> {
+        debug!("Attempting expression statement parsing");
+        // '<expr>;', like 'save(model);'
+        let start_idx = context.get_index();
+
+        // Unknown names still go through the expression parser so they get a 'Cannot find' error
+        let is_expr = parse_operand(context)?.is_some() || matches!(context.get()?, Symbols::LiteralSymb(_));
+        context.jump(start_idx);
+        if !is_expr {
+            trace!("No expression found for statement");
+            return Ok(None);
+        }
+
+        let expr = parse_secondary_expression(context)?;
+        match context.get()? {
+            Symbols::Semicolon => {
+                context.increment();
+                return Ok(Some(Expression::ExprStatementExpr(Box::new(ExprStatement { expr }))));
+            }
+            o => {
+                return Err(Diagnostic::error(String::from("Expected ';' at end of expression"), context.span_after_previous())
+                    .with_note(format!("found '{}' instead", o.to_str())));
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::{is_reserved_literal, is_varname_valid};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::parsing::ParsableExpression;
+use crate::eight::common::running::error::RuntimeError;
+use crate::eight::common::running::interpreter::{ControlFlow, EightInterpreter};
+use crate::eight::common::tokenizing::symbols::{SymbolType, Symbols};
+use crate::eight::expressions::primary::block::Block;
+use crate::eight::expressions::primary::ifexpr::skip_to_block_start;
+use crate::eight::expressions::primary::{Expression, ExpressionRelations, RunnableExpression};
+use crate::eight::expressions::secondary::{parse_secondary_expression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::literals::basic::number::NumberVal;
+use crate::eight::literals::Literal;
+use crate::eight::values::{unknown_elem_error, Value, ValueTypes};
+use log::{debug, trace};
+
+
+#[derive(Debug)]
+pub enum ForIterable {
+    Range(SecondaryExpression, SecondaryExpression), // start..end, end is exclusive
+    Collection(SecondaryExpression), // The elements of a List or Set, or the keys of a Map
+}
+
+#[derive(Debug)]
+pub struct ForExpr {
+    pub(crate) varname: String,
+    pub(crate) iterable: ForIterable,
+    pub(crate) body: Block,
+}
+
+impl ForExpr {
+    fn run_iteration(&self, interpreter: &mut EightInterpreter, val: Value) -> ControlFlow {
+        // Every iteration binds the loop variable fresh in its own frame
+        interpreter.run_block_with_vars(&self.body.exprs, vec![(self.varname.clone(), val)]);
+        return interpreter.take_loop_control();
+    }
+}
+
+impl RunnableExpression for ForExpr {
+    fn run_expr(&self, interpreter: &mut EightInterpreter) {
+        match &self.iterable {
+            ForIterable::Range(start, end) => {
+                let m1 = interpreter.step(start);
+                let m2 = interpreter.step(end);
+                let start_val = interpreter.get_val(&m1).get_val::<NumberVal>().value;
+                let end_val = interpreter.get_val(&m2).get_val::<NumberVal>().value;
+                interpreter.pop_stack_vals(m1);
+                interpreter.pop_stack_vals(m2);
+
+                for i in start_val..end_val {
+                    let val = Value::Literal(Literal::Number(NumberVal { value: i }));
+                    if self.run_iteration(interpreter, val).exits_loop() {
+                        break;
+                    }
+                }
+            }
+            ForIterable::Collection(list) => {
+                let m = interpreter.step(list);
+                // Iterate what the collection held when the loop started, the body may reassign or grow it
+                let vals: Vec<Value> = match interpreter.get_val(&m) {
+                    Value::ListVal(l) => l.data.iter().map(|v| v.copy()).collect(),
+                    Value::SetVal(s) => s.entries.iter().map(|v| v.copy()).collect(),
+                    Value::MapVal(map) => map.entries.iter().map(|(k, _)| k.copy()).collect(),
+                    o => RuntimeError::new(format!("Cannot iterate over {:?}", o)).raise(),
+                };
+                for val in vals {
+                    if self.run_iteration(interpreter, val).exits_loop() {
+                        break;
+                    }
+                }
+                interpreter.pop_stack_vals(m);
+            }
+        }
+    }
+}
+
+impl ExpressionRelations for Box<ForExpr> {
+    fn get_expr_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        let iter_refs = match &self.iterable {
+            ForIterable::Range(start, end) => SecondaryExpression::combine_references(start, end),
+            ForIterable::Collection(list) => list.get_references(),
+        };
+        for r in iter_refs {
+            rr.push(Box::from(r));
+        }
+        rr.append(&mut self.body.get_references());
+
+        vec![RelationEntry::new(vec![self.varname.clone()], vec![], rr)]
+    }
+}
+
+impl ParsableExpression for ForExpr {
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>> {
+        debug!("Attempting For parsing");
+        // 'for <name> in <start>..<end> { ... }' or 'for <name> in <collection> { ... }'
+        match context.get()? {
+            Symbols::For => (),
+            _ => {
+                trace!("No for keyword found");
+                return Ok(None);
+            }
+        }
+        context.increment(); // past 'for'
+
+        // On a bad header still skip the body, so parsing resumes after the loop
+        let header = parse_for_header(context);
+        let elem_typ = match &header {
+            Ok((_, _, typ)) => typ.clone(),
+            Err(_) => {
+                skip_to_block_start(context);
+                ValueTypes::NoneType
+            }
+        };
+
+        context.push_scope();
+        if let Ok((varname, _, _)) = &header {
+            context.put_var(varname.clone(), elem_typ);
+        }
+        context.enter_loop();
+        let body = Block::parse(context);
+        context.exit_loop();
+        context.pop_scope();
+
+        let (varname, iterable, _) = header?;
+        return match body? {
+            Some(body) => Ok(Some(Expression::ForExpr(Box::new(ForExpr {
+                varname,
+                iterable,
+                body,
+            })))),
+            None => Err(context.error(String::from("Expected '{' after 'for' header"))),
+        };
+    }
+}
+
+fn parse_for_header(context: &mut Context) -> ParseResult<(String, ForIterable, ValueTypes)> {
+    // '<name> in <iterable>' up to the '{' of the body, returns the type bound to the loop variable
+    let varname = match context.get()? {
+        Symbols::LiteralSymb(name) if is_reserved_literal(name.as_str()) => {
+            return Err(context.error(format!("'{}' is a reserved keyword and can't be used as a variable name", name)));
+        }
+        Symbols::LiteralSymb(name) => {
+            if !is_varname_valid(name.as_str()) {
+                return Err(context.error(format!("Invalid varname '{}'", name.as_str())));
+            }
+            name
+        }
+        sym if Symbols::get_symbols_by_type(SymbolType::Keyword).contains(&sym) => {
+            return Err(context.error(format!(
+                "'{}' is a reserved keyword and can't be used as a variable name",
+                sym.to_str()
+            )));
+        }
+        _ => {
+            return Err(context.error(String::from("Expected a loop variable name after 'for'")));
+        }
+    };
+    context.increment();
+
+    match context.get()? {
+        Symbols::In => context.increment(),
+        o => {
+            return Err(context.error(format!("Expected 'in' after the loop variable '{}'", varname))
+                .with_note(format!("found '{}' instead", o.to_str())));
+        }
+    }
+
+    let start_idx = context.get_index();
+    let first = parse_secondary_expression(context)?;
+    let first_typ = get_expr_type(context, &first, start_idx)?;
+
+    let (iterable, elem_typ) = if context.get()? == Symbols::Range {
+        context.increment(); // past '..'
+        let end_idx = context.get_index();
+        let end = parse_secondary_expression(context)?;
+        let end_typ = get_expr_type(context, &end, end_idx)?;
+
+        if first_typ != ValueTypes::NumberType || end_typ != ValueTypes::NumberType {
+            return Err(Diagnostic::error(
+                format!("Range bounds must both be Number, found {} and {}", first_typ, end_typ),
+                context.span_since(start_idx),
+            ));
+        }
+        (ForIterable::Range(first, end), ValueTypes::NumberType)
+    } else {
+        match first_typ {
+            t if t.is_unknown_collection() => {
+                return Err(unknown_elem_error(&t, context.span_since(start_idx)));
+            }
+            ValueTypes::ListType(elem) | ValueTypes::SetType(elem) | ValueTypes::MapType(elem, _) => {
+                (ForIterable::Collection(first), *elem)
+            }
+            o => {
+                return Err(Diagnostic::error(
+                    format!("Cannot iterate over a value of type {}, expected a range 'start..end', a List, a Set or a Map", o),
+                    context.span_since(start_idx),
+                ));
+            }
+        }
+    };
+
+    match context.get()? {
+        Symbols::CurlyOpen => Ok((varname, iterable, elem_typ)),
+        o => Err(Diagnostic::error(String::from("Expected '{' after 'for' header"), context.span_after_previous())
+            .with_note(format!("found '{}' instead", o.to_str()))),
+    }
+}
+
+fn get_expr_type(context: &Context, expr: &SecondaryExpression, start_idx: usize) -> ParseResult<ValueTypes> {
+    match expr.get_type() {
+        Ok(t) => Ok(t),
+        Err(s) => Err(Diagnostic::error(
+            format!("Error getting type of expression, '{}'", s),
+            context.span_since(start_idx),
+        )),
+    }
+}
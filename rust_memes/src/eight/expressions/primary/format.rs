@@ -1,5 +1,6 @@
 use crate::eight::common::AccessibleValue;
 use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::parsing::ParsableExpression;
 use crate::eight::common::running::interpreter::EightInterpreter;
@@ -31,12 +32,10 @@ pub fn get_fmt_relations(exprs: &Vec<SecondaryExpression>) -> Vec<RelationEntry>
 }
 
 impl RunnableSecondaryExpression for Format {
-    fn run_secondary_expr(mut self, interpreter: &mut EightInterpreter) -> MemRef {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
         //TODO String interpolation here?
         let mut out_str = String::new();
-        let mut exprs = Vec::new();
-        std::mem::swap(&mut self.exprs, &mut exprs);
-        for exp in exprs {
+        for exp in &self.exprs {
             let val = interpreter.step(exp);
             out_str.push_str(
                 StringVal::explicit_cast_to(interpreter.get_val(&val)).value.as_str()
@@ -60,23 +59,23 @@ impl TypedSecondaryExpression for Box<Format> {
 
 impl Format {
     //pub fn parse<'a>(context: &mut Context) -> Option<SecondaryExpression>
-    pub fn parse<'a, 'c>(context: &'c mut Context) -> Option<SecondaryExpression> {
+    pub fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<SecondaryExpression>> {
         let idx = context.get_index();
 
-        let (name, args) = match parse_function_call(context) {
+        let (name, args) = match parse_function_call(context)? {
             Some(n) => n,
             None => {
-                return None;
+                return Ok(None);
             }
         };
 
         if name.eq("fmt") {
-            return Some(
+            return Ok(Some(
                 SecondaryExpression::FormatExpr(Box::from(
                     Format { exprs: args.to_vec() }
-                )));
+                ))));
         }
         context.jump(idx); // TODO?
-        None
+        Ok(None)
     }
 }
\ No newline at end of file
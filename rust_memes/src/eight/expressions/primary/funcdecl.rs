@@ -0,0 +1,211 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::{is_reserved_literal, is_varname_valid};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::parsing::ParsableExpression;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::tokenizing::symbols::{SymbolType, Symbols};
+use crate::eight::expressions::primary::block::Block;
+use crate::eight::expressions::primary::ifexpr::skip_to_block_start;
+use crate::eight::expressions::primary::{Expression, ExpressionRelations, RunnableExpression};
+use crate::eight::literals::basic::functions::signature::FuncSignature;
+use crate::eight::literals::basic::functions::Func;
+use crate::eight::values::{parse_type, ValueTypes};
+use log::{debug, trace};
+use std::rc::Rc;
+
+
+const BUILTIN_FUNC_NAMES: [&str; 12] = [
+    "print", "println", "eprint", "eprintln", "fmt", "map", "reduce", "keys", "values", "set", "contains", "insert",
+];
+
+type FuncHeader = (String, FuncSignature, Vec<(String, ValueTypes)>); // name, signature, named params
+
+#[derive(Debug)]
+pub struct FuncDecl {
+    pub(crate) func: Rc<Func>,
+}
+
+impl RunnableExpression for FuncDecl {
+    fn run_expr(&self, interpreter: &mut EightInterpreter) {
+        interpreter.register_func(Rc::clone(&self.func));
+    }
+}
+
+impl ExpressionRelations for Box<FuncDecl> {
+    fn get_expr_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        if let Some(body) = self.func.get_body() {
+            for ex in body {
+                for r in ex.get_expr_references() {
+                    rr.push(Box::from(r));
+                }
+            }
+        }
+
+        vec![RelationEntry::new(vec![self.func.name.clone()], vec![], rr)]
+    }
+}
+
+impl ParsableExpression for FuncDecl {
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>> {
+        debug!("Attempting FuncDecl parsing");
+        // 'func <name>(<arg>: <type>, ...) -> <type> { ... }', the '-> <type>' is optional
+        match context.get()? {
+            Symbols::Func => (),
+            _ => {
+                trace!("No func keyword found");
+                return Ok(None);
+            }
+        }
+        if !context.is_top_level() {
+            return Err(context.error(String::from("Functions can only be declared at the top level")));
+        }
+        context.increment(); // past 'func'
+
+        // On a bad header still skip the body, so parsing resumes after the function
+        let name_span = context.get_span();
+        let header = parse_func_header(context);
+        let (sig, params) = match &header {
+            Ok((name, sig, params)) => {
+                // Registered before the body is parsed so the function can call itself
+                context.functions.insert(name.clone(), sig.clone());
+                (sig.clone(), params.clone())
+            }
+            Err(_) => {
+                skip_to_block_start(context);
+                (FuncSignature::empty(), vec![])
+            }
+        };
+
+        context.enter_function(sig, params);
+        let body = Block::parse(context);
+        context.exit_function();
+
+        let (name, sig, params) = header?;
+        return match body? {
+            Some(body) => {
+                if sig.get_output_type() != ValueTypes::NoneType && !body.always_returns() {
+                    return Err(Diagnostic::error(format!("Function '{}' doesn't return a {} on every path", name, sig.get_output_type()), name_span)
+                        .with_label(String::from("the body can finish without a 'return'"))
+                        .with_note(String::from("end the body with a 'return', or return from every branch of its last 'if' or 'match'")));
+                }
+                let param_names = params.into_iter().map(|(n, _)| n).collect();
+                Ok(Some(Expression::FuncDeclExpr(Box::new(FuncDecl {
+                    func: Rc::new(Func::new_user(name, param_names, sig, body.exprs)),
+                }))))
+            }
+            None => Err(context.error(format!("Expected '{{' to start the body of '{}'", name))),
+        };
+    }
+}
+
+pub fn parse_name(context: &mut Context, what: &str) -> ParseResult<String> {
+    match context.get()? {
+        Symbols::LiteralSymb(name) if is_reserved_literal(name.as_str()) => {
+            return Err(context.error(format!("'{}' is a reserved keyword and can't be used as a {}", name, what)));
+        }
+        Symbols::LiteralSymb(name) => {
+            if !is_varname_valid(name.as_str()) {
+                return Err(context.error(format!("Invalid {} '{}'", what, name.as_str())));
+            }
+            context.increment();
+            return Ok(name);
+        }
+        sym if Symbols::get_symbols_by_type(SymbolType::Keyword).contains(&sym) => {
+            return Err(context.error(format!(
+                "'{}' is a reserved keyword and can't be used as a {}",
+                sym.to_str(),
+                what
+            )));
+        }
+        o => {
+            return Err(context.error(format!("Expected a {}, found '{}'", what, o.to_str())));
+        }
+    }
+}
+
+pub fn parse_params(context: &mut Context, close: Symbols, owner: &str) -> ParseResult<Vec<(String, ValueTypes)>> {
+    // '<arg>: <type>, ...' up to and past the closing symbol, the opening one has already been passed
+    let mut params: Vec<(String, ValueTypes)> = Vec::new();
+    if context.get()? == close {
+        context.increment();
+        return Ok(params);
+    }
+
+    loop {
+        let param_idx = context.get_index();
+        let param = parse_name(context, "parameter name")?;
+        if params.iter().any(|(n, _)| n == &param) {
+            context.jump(param_idx);
+            return Err(context.error(format!("Parameter '{}' is declared more than once", param)));
+        }
+
+        match context.get()? {
+            Symbols::Colon => context.increment(),
+            o => {
+                return Err(context.error(format!("Expected ':' and a type after parameter '{}'", param))
+                    .with_note(format!("found '{}' instead", o.to_str())));
+            }
+        }
+        params.push((param, parse_type(context)?));
+
+        match context.get()? {
+            Symbols::Comma => context.increment(),
+            sym if sym == close => {
+                context.increment();
+                return Ok(params);
+            }
+            o => {
+                return Err(context.error(format!(
+                    "Expected ',' or '{}' in the parameters of {}, found '{}'",
+                    close.to_str(),
+                    owner,
+                    o.to_str()
+                )));
+            }
+        }
+    }
+}
+
+fn parse_func_header(context: &mut Context) -> ParseResult<FuncHeader> {
+    // Everything between 'func' and the '{' of the body
+    let name_idx = context.get_index();
+    let name = parse_name(context, "function name")?;
+    if BUILTIN_FUNC_NAMES.contains(&name.as_str()) {
+        context.jump(name_idx);
+        return Err(context.error(format!("'{}' is a built in function and can't be redefined", name)));
+    }
+    if context.functions.contains_key(&name) {
+        context.jump(name_idx);
+        return Err(context.error(format!("Function '{}' is already defined", name)));
+    }
+
+    match context.get()? {
+        Symbols::ParenOpen => context.increment(),
+        o => {
+            return Err(context.error(format!("Expected '(' after function name '{}'", name))
+                .with_note(format!("found '{}' instead", o.to_str())));
+        }
+    }
+
+    let params = parse_params(context, Symbols::ParenClose, format!("'{}'", name).as_str())?;
+
+    let output = if context.get()? == Symbols::Arrow {
+        context.increment(); // past '->'
+        parse_type(context)?
+    } else {
+        ValueTypes::NoneType
+    };
+
+    match context.get()? {
+        Symbols::CurlyOpen => (),
+        o => {
+            return Err(Diagnostic::error(format!("Expected '{{' to start the body of '{}'", name), context.span_after_previous())
+                .with_note(format!("found '{}' instead", o.to_str())));
+        }
+    }
+
+    let sig = FuncSignature::new(params.iter().map(|(_, t)| t.clone()).collect(), output);
+    return Ok((name, sig, params));
+}
@@ -0,0 +1,171 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::parsing::ParsableExpression;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::primary::block::Block;
+use crate::eight::expressions::primary::{Expression, ExpressionRelations, RunnableExpression};
+use crate::eight::expressions::secondary::{parse_secondary_expression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::literals::basic::bool::BoolVal;
+use crate::eight::values::ValueTypes;
+use log::{debug, trace};
+
+
+#[derive(Debug)]
+pub struct IfBranch {
+    pub(crate) condition: SecondaryExpression,
+    pub(crate) block: Block,
+}
+
+#[derive(Debug)]
+pub struct IfExpr {
+    pub(crate) branches: Vec<IfBranch>, // 'if' followed by every 'else if', in order
+    pub(crate) else_block: Option<Block>,
+}
+
+impl RunnableExpression for IfExpr {
+    fn run_expr(&self, interpreter: &mut EightInterpreter) {
+        for branch in &self.branches {
+            let m = interpreter.step(&branch.condition);
+            let cond = interpreter.get_val(&m).get_val::<BoolVal>().value;
+            interpreter.pop_stack_vals(m);
+
+            if cond {
+                branch.block.run(interpreter);
+                return;
+            }
+        }
+
+        if let Some(block) = &self.else_block {
+            block.run(interpreter);
+        }
+    }
+}
+
+impl ExpressionRelations for Box<IfExpr> {
+    fn get_expr_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        for branch in &self.branches {
+            for r in branch.condition.get_references() {
+                rr.push(Box::from(r));
+            }
+            rr.append(&mut branch.block.get_references());
+        }
+        if let Some(block) = &self.else_block {
+            rr.append(&mut block.get_references());
+        }
+
+        vec![RelationEntry::new(vec![], vec![], rr)]
+    }
+}
+
+impl ParsableExpression for IfExpr {
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>> {
+        debug!("Attempting If parsing");
+        // 'if <cond> { ... } else if <cond> { ... } else { ... }'
+        match context.get()? {
+            Symbols::If => (),
+            _ => {
+                trace!("No if keyword found");
+                return Ok(None);
+            }
+        }
+
+        // Keep going after an error so the whole chain is skipped, only the first error is reported
+        let mut error: Option<Diagnostic> = None;
+        let mut branches = Vec::new();
+        let mut else_block = None;
+
+        loop {
+            context.increment(); // past 'if'
+
+            let condition = match parse_condition(context, "if") {
+                Ok(c) => Some(c),
+                Err(e) => {
+                    error = error.or(Some(e));
+                    skip_to_block_start(context);
+                    None
+                }
+            };
+
+            match Block::parse(context) {
+                Ok(Some(block)) => {
+                    if let Some(condition) = condition {
+                        branches.push(IfBranch { condition, block });
+                    }
+                }
+                Ok(None) => break, // Missing '{' was already reported by parse_condition
+                Err(e) => {
+                    error = error.or(Some(e));
+                }
+            }
+
+            if context.get_safe() != Ok(Symbols::Else) {
+                break;
+            }
+            context.increment(); // past 'else'
+
+            if context.get_safe() == Ok(Symbols::If) {
+                continue;
+            }
+
+            match Block::parse(context) {
+                Ok(Some(block)) => else_block = Some(block),
+                Ok(None) => {
+                    error = error.or(Some(context.error(String::from("Expected '{' or 'if' after 'else'"))));
+                }
+                Err(e) => {
+                    error = error.or(Some(e));
+                }
+            }
+            break;
+        }
+
+        if let Some(e) = error {
+            return Err(e);
+        }
+        return Ok(Some(Expression::IfExpr(Box::new(IfExpr {
+            branches,
+            else_block,
+        }))));
+    }
+}
+
+pub fn parse_condition(context: &mut Context, keyword: &str) -> ParseResult<SecondaryExpression> {
+    // A Boolean expression directly followed by the '{' of a block, shared by 'if' and 'while'
+    let start_idx = context.get_index();
+    let condition = parse_secondary_expression(context)?;
+
+    match condition.get_type() {
+        Ok(ValueTypes::BooleanType) => (),
+        Ok(t) => {
+            return Err(Diagnostic::error(
+                format!("Mismatched types, '{}' condition must be a Boolean, found {}", keyword, t),
+                context.span_since(start_idx),
+            ));
+        }
+        Err(s) => {
+            return Err(Diagnostic::error(
+                format!("Error getting type of expression, '{}'", s),
+                context.span_since(start_idx),
+            ));
+        }
+    }
+
+    match context.get()? {
+        Symbols::CurlyOpen => Ok(condition),
+        o => Err(Diagnostic::error(format!("Expected '{{' after '{}' condition", keyword), context.span_after_previous())
+            .with_note(format!("found '{}' instead", o.to_str()))),
+    }
+}
+
+pub fn skip_to_block_start(context: &mut Context) {
+    // Stop at the '{' of the block so it can still be skipped as a whole, or at the end of a statement
+    loop {
+        match context.get_safe() {
+            Ok(Symbols::CurlyOpen) | Ok(Symbols::Semicolon) | Ok(Symbols::CurlyClose) | Err(_) => return,
+            Ok(_) => context.increment(),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::parsing::ParsableExpression;
+use crate::eight::common::running::interpreter::{ControlFlow, EightInterpreter};
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::primary::{Expression, ExpressionRelations, RunnableExpression};
+use log::debug;
+
+
+#[derive(Debug)]
+pub struct LoopControl {
+    pub(crate) flow: ControlFlow, // Break or Continue
+}
+
+impl RunnableExpression for LoopControl {
+    fn run_expr(&self, interpreter: &mut EightInterpreter) {
+        interpreter.set_control_flow(self.flow);
+    }
+}
+
+impl ExpressionRelations for LoopControl {
+    fn get_expr_references(&self) -> Vec<RelationEntry> {
+        vec![]
+    }
+}
+
+impl ParsableExpression for LoopControl {
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>> {
+        // 'break;' or 'continue;'
+        let sym = context.get()?;
+        let flow = match sym {
+            Symbols::Break => ControlFlow::Break,
+            Symbols::Continue => ControlFlow::Continue,
+            _ => return Ok(None),
+        };
+        debug!("Found loop control '{}'", sym.to_str());
+
+        if !context.in_loop() {
+            return Err(context.error(format!("'{}' can only be used inside a loop", sym.to_str())));
+        }
+        context.increment();
+
+        match context.get()? {
+            Symbols::Semicolon => {
+                context.increment();
+                return Ok(Some(Expression::LoopControlExpr(LoopControl { flow })));
+            }
+            o => {
+                return Err(Diagnostic::error(String::from("Expected ';' at end of expression"), context.span_after_previous())
+                    .with_note(format!("found '{}' instead", o.to_str())));
+            }
+        }
+    }
+}
@@ -0,0 +1,374 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::{is_reserved_literal, is_varname_valid};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::parsing::ParsableExpression;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::error::RuntimeError;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::common::tokenizing::token::Span;
+use crate::eight::expressions::primary::block::{skip_past_block_end, Block};
+use crate::eight::expressions::primary::ifexpr::skip_to_block_start;
+use crate::eight::expressions::primary::print::Print;
+use crate::eight::expressions::primary::{Expression, ExpressionRelations, RunnableExpression};
+use crate::eight::expressions::secondary::{parse_secondary_expression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::literals::basic::enums::EnumDef;
+use crate::eight::values::{Value, ValueTypes};
+use log::{debug, trace};
+
+
+#[derive(Debug)]
+pub enum MatchPattern {
+    Variant(String, Vec<Option<String>>), // 'Algo::Tree(depth)', '_' in place of a name skips that value
+    Wildcard,                             // '_', matches every variant not handled by an earlier arm
+}
+
+#[derive(Debug)]
+pub enum ArmBody {
+    Block(Block),               // '=> { ... }'
+    Statement(Expression),      // '=> println("a")', a print without its ';'
+    Value(SecondaryExpression), // '=> 1', the only kind allowed when the match is used as a value
+}
+
+#[derive(Debug)]
+pub struct MatchArm {
+    pub(crate) pattern: MatchPattern,
+    pub(crate) body: ArmBody,
+}
+
+#[derive(Debug)]
+pub struct MatchExpr {
+    pub(crate) target: SecondaryExpression,
+    pub(crate) arms: Vec<MatchArm>,
+}
+
+impl RunnableExpression for MatchExpr {
+    fn run_expr(&self, interpreter: &mut EightInterpreter) {
+        let m = interpreter.step(&self.target);
+        let val = interpreter.copy_val(m);
+        let (body, vars) = select_arm(&self.arms, val);
+        match body {
+            ArmBody::Block(block) => interpreter.run_block_with_vars(&block.exprs, vars),
+            ArmBody::Statement(ex) => interpreter.run_block_with_vars(std::slice::from_ref(ex), vars),
+            ArmBody::Value(ex) => {
+                interpreter.step_with_vars(ex, vars); // Only run for its side effects
+            }
+        }
+    }
+}
+
+pub fn select_arm(arms: &[MatchArm], val: Value) -> (&ArmBody, Vec<(String, Value)>) {
+    // The first arm matching the variant, with its payload bound to the names in the pattern
+    let (variant, payload) = match val {
+        Value::EnumVal(e) => (e.variant, e.payload),
+        o => {
+            RuntimeError::new(format!("Cannot match on {:?}", o)).raise();
+        }
+    };
+
+    for arm in arms {
+        match &arm.pattern {
+            MatchPattern::Variant(name, bindings) if *name == variant => {
+                let vars = bindings.iter().zip(payload)
+                    .filter_map(|(b, v)| b.clone().map(|b| (b, v)))
+                    .collect();
+                return (&arm.body, vars);
+            }
+            MatchPattern::Wildcard => return (&arm.body, vec![]),
+            MatchPattern::Variant(_, _) => (),
+        }
+    }
+    RuntimeError::new(format!("No match arm for variant '{}'", variant)).raise();
+}
+
+impl ArmBody {
+    pub fn always_returns(&self) -> bool {
+        return match self {
+            ArmBody::Block(block) => block.always_returns(),
+            ArmBody::Statement(ex) => ex.always_returns(),
+            ArmBody::Value(_) => false,
+        };
+    }
+
+    pub fn get_references(&self) -> Vec<Box<RelationEntry>> {
+        return match self {
+            ArmBody::Block(block) => block.get_references(),
+            ArmBody::Statement(ex) => ex.get_expr_references().into_iter().map(Box::from).collect(),
+            ArmBody::Value(ex) => ex.get_references().into_iter().map(Box::from).collect(),
+        };
+    }
+}
+
+impl ExpressionRelations for Box<MatchExpr> {
+    fn get_expr_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        for r in self.target.get_references() {
+            rr.push(Box::from(r));
+        }
+        let mut bound = Vec::new();
+        for arm in &self.arms {
+            if let MatchPattern::Variant(_, bindings) = &arm.pattern {
+                bound.extend(bindings.iter().flatten().cloned());
+            }
+            rr.append(&mut arm.body.get_references());
+        }
+
+        vec![RelationEntry::new(bound, vec![], rr)]
+    }
+}
+
+impl ParsableExpression for MatchExpr {
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>> {
+        debug!("Attempting Match parsing");
+        // 'match <enum value> { <Enum>::<Variant>(<name>, ...) => { ... } _ => println(...), }', arms are blocks, prints or expressions
+        match context.get()? {
+            Symbols::Match => (),
+            _ => {
+                trace!("No match keyword found");
+                return Ok(None);
+            }
+        }
+        let (target, arms, _) = parse_match(context, false)?;
+        return Ok(Some(Expression::MatchExpr(Box::new(MatchExpr { target, arms }))));
+    }
+}
+
+pub fn parse_match(context: &mut Context, as_value: bool) -> ParseResult<(SecondaryExpression, Vec<MatchArm>, ValueTypes)> {
+    // From the 'match' keyword past the closing '}', as a value every arm gives an expression and their types are unified
+    let match_idx = context.get_index();
+    context.increment(); // past 'match'
+
+    // On a bad target still skip the arms, so parsing resumes after the match
+    let target = parse_match_target(context);
+    if target.is_err() {
+        skip_to_block_start(context);
+        if context.get_safe() == Ok(Symbols::CurlyOpen) {
+            context.increment();
+            skip_past_block_end(context);
+        }
+    }
+    let (target, def) = target?;
+    context.increment(); // past '{'
+
+    let arms = parse_arms(context, &def, as_value);
+    if arms.is_err() {
+        skip_past_block_end(context);
+    }
+    let (arms, typ) = arms?;
+
+    // Every variant needs an arm unless there's a '_' arm
+    if !arms.iter().any(|a| matches!(a.pattern, MatchPattern::Wildcard)) {
+        let missing: Vec<String> = def.variants.iter()
+            .filter(|(v, _)| !arms.iter().any(|a| matches!(&a.pattern, MatchPattern::Variant(n, _) if n == v)))
+            .map(|(v, _)| format!("'{}::{}'", def.name, v))
+            .collect();
+        if !missing.is_empty() {
+            return Err(Diagnostic::error(format!("Non-exhaustive match, {} not covered", missing.join(", ")), context.span_since(match_idx))
+                .with_note(String::from("add an arm for each missing variant or a '_ => ...' arm")));
+        }
+    }
+    return Ok((target, arms, typ));
+}
+
+fn parse_match_target(context: &mut Context) -> ParseResult<(SecondaryExpression, EnumDef)> {
+    // The enum value being matched, directly followed by the '{' of the arms
+    let start_idx = context.get_index();
+    let target = parse_secondary_expression(context)?;
+    let def = match target.get_type() {
+        Ok(ValueTypes::EnumType(name)) => context.enums.get(&name).unwrap().clone(),
+        Ok(t) => {
+            return Err(Diagnostic::error(format!("Cannot match on a value of type {}, only enums can be matched", t), context.span_since(start_idx)));
+        }
+        Err(s) => {
+            return Err(Diagnostic::error(format!("Error getting type of expression, '{}'", s), context.span_since(start_idx)));
+        }
+    };
+
+    match context.get()? {
+        Symbols::CurlyOpen => Ok((target, def)),
+        o => Err(Diagnostic::error(String::from("Expected '{' and the arms after the 'match' value"), context.span_after_previous())
+            .with_note(format!("found '{}' instead", o.to_str()))),
+    }
+}
+
+fn parse_arms(context: &mut Context, def: &EnumDef, as_value: bool) -> ParseResult<(Vec<MatchArm>, ValueTypes)> {
+    // '<pattern> => <body>' up to and past the '}' closing the match, arms may be separated by ','
+    // Returns the arms with the type every value arm agrees on
+    let mut arms: Vec<MatchArm> = Vec::new();
+    let mut typ: Option<(ValueTypes, Span)> = None;
+    loop {
+        match context.get()? {
+            Symbols::CurlyClose => {
+                context.increment();
+                return Ok((arms, typ.map_or(ValueTypes::NoneType, |(t, _)| t)));
+            }
+            Symbols::Comma => {
+                context.increment();
+                continue;
+            }
+            _ => (),
+        }
+
+        let arm_idx = context.get_index();
+        let pattern = parse_pattern(context, def)?;
+        let unreachable = arms.iter().any(|a| match (&a.pattern, &pattern) {
+            (MatchPattern::Wildcard, _) => true,
+            (MatchPattern::Variant(a, _), MatchPattern::Variant(b, _)) => a == b,
+            _ => false,
+        });
+        if unreachable {
+            return Err(Diagnostic::error(String::from("Unreachable match arm, an earlier arm already covers it"), context.span_since(arm_idx)));
+        }
+
+        match context.get()? {
+            Symbols::FatArrow => context.increment(),
+            o => {
+                return Err(context.error(String::from("Expected '=>' and the arm's body after the match pattern"))
+                    .with_note(format!("found '{}' instead", o.to_str())));
+            }
+        }
+
+        context.push_scope();
+        if let MatchPattern::Variant(variant, bindings) = &pattern {
+            for (b, typ) in bindings.iter().zip(def.variant(variant.as_str()).unwrap()) {
+                if let Some(b) = b {
+                    context.put_var(b.clone(), typ.clone());
+                }
+            }
+        }
+        let body_idx = context.get_index();
+        let body = parse_arm_body(context, as_value);
+        context.pop_scope();
+        let body = body?;
+
+        if let ArmBody::Value(ex) = &body {
+            let span = context.span_since(body_idx);
+            let t = match ex.get_type() {
+                Ok(t) => t,
+                Err(s) => return Err(Diagnostic::error(format!("Error getting type of expression, '{}'", s), span)),
+            };
+            typ = match typ {
+                None => Some((t, span)),
+                Some((prev, prev_span)) if prev.accepts(&t) => Some((prev, prev_span)),
+                Some((prev, _)) if t.accepts(&prev) => Some((t, span)), // '[]' in an earlier arm gets the type of a later '[1]'
+                Some((prev, prev_span)) if as_value => {
+                    return Err(Diagnostic::error(format!("Mismatched types, match arms give {} and {}", prev, t), span)
+                        .with_label(format!("expected {}, found {}", prev, t))
+                        .with_secondary(prev_span, format!("this arm gives {}", prev)));
+                }
+                Some(prev) => Some(prev), // Unused values of a match statement don't have to agree
+            };
+        }
+        if !matches!(body, ArmBody::Block(_)) {
+            // Only a block ends itself, anything else is followed by ',' or the closing '}'
+            match context.get()? {
+                Symbols::Comma | Symbols::CurlyClose => (),
+                o => {
+                    return Err(Diagnostic::error(String::from("Expected ',' or '}' after the match arm"), context.span_after_previous())
+                        .with_note(format!("found '{}' instead", o.to_str())));
+                }
+            }
+        }
+        arms.push(MatchArm { pattern, body });
+    }
+}
+
+fn parse_arm_body(context: &mut Context, as_value: bool) -> ParseResult<ArmBody> {
+    // A block, a print or an expression, a match used as a value only takes expressions
+    if context.get()? == Symbols::CurlyOpen {
+        if as_value {
+            return Err(context.error(String::from("Expected a value after '=>', blocks don't give one"))
+                .with_note(String::from("arms of a match used as a value are expressions like 'E::A => 1,'")));
+        }
+        return match Block::parse(context)? {
+            Some(block) => Ok(ArmBody::Block(block)),
+            None => Err(context.error(String::from("Expected '{' after '=>'"))),
+        };
+    }
+
+    let print_idx = context.get_index();
+    if let Some(print) = Print::parse_call(context)? {
+        if as_value {
+            return Err(Diagnostic::error(String::from("Expected a value after '=>', printing doesn't give one"), context.span_since(print_idx))
+                .with_note(String::from("use the match as a statement to print from its arms")));
+        }
+        return Ok(ArmBody::Statement(Expression::PrintExpr(Box::new(print))));
+    }
+    return Ok(ArmBody::Value(parse_secondary_expression(context)?));
+}
+
+fn parse_pattern(context: &mut Context, def: &EnumDef) -> ParseResult<MatchPattern> {
+    // '_', or '<Enum>::<Variant>' with one name per payload value
+    let start_idx = context.get_index();
+    match context.get()? {
+        Symbols::LiteralSymb(name) if name == "_" => {
+            context.increment();
+            return Ok(MatchPattern::Wildcard);
+        }
+        Symbols::LiteralSymb(name) if name == def.name => context.increment(),
+        o => {
+            return Err(context.error(format!("Expected a variant of '{}' like '{}::{}' or '_', found '{}'", def.name, def.name, def.variants[0].0, o.to_str())));
+        }
+    }
+    match context.get()? {
+        Symbols::ClassAccessor => context.increment(),
+        o => {
+            return Err(context.error(format!("Expected '::' and a variant after '{}', found '{}'", def.name, o.to_str())));
+        }
+    }
+
+    let variant = match context.get()? {
+        Symbols::LiteralSymb(variant) => variant,
+        o => {
+            return Err(context.error(format!("Expected a variant of '{}', found '{}'", def.name, o.to_str())));
+        }
+    };
+    let payload = match def.variant(variant.as_str()) {
+        Some(p) => p.len(),
+        None => {
+            return Err(context.error(format!("'{}' has no variant '{}'", def.name, variant))
+                .with_note(format!("the variants are {}", def.variant_names())));
+        }
+    };
+    context.increment(); // past the variant
+
+    let mut bindings = Vec::new();
+    if context.get()? == Symbols::ParenOpen {
+        context.increment(); // past '('
+        loop {
+            match context.get()? {
+                Symbols::LiteralSymb(b) if b == "_" => bindings.push(None),
+                Symbols::LiteralSymb(b) if is_reserved_literal(b.as_str()) => {
+                    return Err(context.error(format!("'{}' is a reserved keyword and can't be used as a variable name", b)));
+                }
+                Symbols::LiteralSymb(b) if is_varname_valid(b.as_str()) => {
+                    if bindings.contains(&Some(b.clone())) {
+                        return Err(context.error(format!("'{}' is bound more than once in this pattern", b)));
+                    }
+                    bindings.push(Some(b));
+                }
+                o => {
+                    return Err(context.error(format!("Expected a name to bind the value to, found '{}'", o.to_str())));
+                }
+            }
+            context.increment();
+            match context.get()? {
+                Symbols::Comma => context.increment(),
+                Symbols::ParenClose => {
+                    context.increment();
+                    break;
+                }
+                o => {
+                    return Err(context.error(format!("Expected ',' or ')' in the pattern, found '{}'", o.to_str())));
+                }
+            }
+        }
+    }
+    if bindings.len() != payload {
+        return Err(Diagnostic::error(
+            format!("'{}::{}' holds {} value(s) but the pattern binds {}", def.name, variant, payload, bindings.len()),
+            context.span_since(start_idx),
+        ));
+    }
+    return Ok(MatchPattern::Variant(variant, bindings));
+}
@@ -1,6 +1,7 @@
 use crate::eight::common::AccessibleValue;
-use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::interpreter::{EightInterpreter, OutputStream};
 use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::parsing::ParsableExpression;
 use crate::eight::common::tokenizing::symbols::Symbols;
@@ -11,27 +12,31 @@ use crate::eight::literals::basic::functions::parse_function_call;
 use crate::eight::literals::basic::string::StringVal;
 use crate::eight::values::Value;
 use crate::eight::expressions::secondary::TypedSecondaryExpression;
+use log::trace;
 
 
 #[derive(Debug)]
 pub struct Print {
-    exprs: Vec<SecondaryExpression>
+    exprs: Vec<SecondaryExpression>,
+    newline: bool,
+    stream: OutputStream,
 }
 
 impl RunnableExpression for Print {
-    fn run_expr(mut self, interpreter: &mut EightInterpreter) {
+    fn run_expr(&self, interpreter: &mut EightInterpreter) {
         //TODO String interpolation here?
         let mut out_str = String::new();
-        let mut exprs = Vec::new();
-        std::mem::swap(&mut self.exprs, &mut exprs);
-        for exp in exprs {
+        for exp in &self.exprs {
             let val = interpreter.step(exp);
             out_str.push_str(
                 StringVal::implicit_cast_to(interpreter.get_val(&val)).value.as_str()
             );
             interpreter.pop_stack_vals(val);
         }
-        println!("{}", out_str);
+        if self.newline {
+            out_str.push('\n');
+        }
+        interpreter.write_output(self.stream, out_str.as_str());
     }
 }
 
@@ -42,32 +47,55 @@ impl ExpressionRelations for Box<Print> {
 }
 
 impl ParsableExpression for Print {
-    fn parse<'a, 'c>(context: &'c mut Context) -> Option<Expression> {
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>> {
+        let print = match Print::parse_call(context)? {
+            Some(p) => p,
+            None => {
+                return Ok(None);
+            }
+        };
+        match context.get()? {
+            Symbols::Semicolon => {
+                context.increment();
+                return Ok(Some(Expression::PrintExpr(Box::from(print))));
+            },
+            o => {
+                return Err(Diagnostic::error(String::from("Expected ';' at end of print expression"), context.span_after_previous())
+                    .with_note(format!("found '{}' instead", o.to_str())));
+            }
+        }
+    }
+}
+
+impl Print {
+    pub fn parse_call(context: &mut Context) -> ParseResult<Option<Print>> {
+        // 'println(<args>)' up to but not past the ';', a match arm can also end it with ','
         let idx = context.get_index();
 
-        let (name, args) = match parse_function_call(context) {
+        let (name, args) = match parse_function_call(context)? {
             Some(n) => n,
             None => {
-                return None;
+                return Ok(None);
             }
         };
-        println!("IAMHERE");
+        trace!("Found call to '{}'", name);
         context.print_symbols_current();
 
-        if name.eq("println") || name.eq("print") { // TODO differenate between print and println
-            match context.get() {
-                Symbols::Semicolon => {
-                    context.increment();
-                    return Some(Expression::PrintExpr(Box::from(Print {
-                        exprs: args.to_vec(),
-                    })));
-                },
-                o => {
-                    panic!("{}", context.get_panic_smessage(format!("Expected ';' at end of print expression, got '{:?}'", o)));
-                }
+        // 'print' and 'eprint' leave the line open, the 'ln' versions end it
+        let (newline, stream) = match name.as_str() {
+            "print" => (false, OutputStream::Out),
+            "println" => (true, OutputStream::Out),
+            "eprint" => (false, OutputStream::Err),
+            "eprintln" => (true, OutputStream::Err),
+            _ => {
+                context.jump(idx); // jump back to before parsing function call, wrong function found TODO?
+                return Ok(None);
             }
-        }
-        context.jump(idx); // jump back to before parsing function call, wrong function found TODO?
-        return None;
+        };
+        return Ok(Some(Print {
+            exprs: args.to_vec(),
+            newline,
+            stream,
+        }));
     }
 }
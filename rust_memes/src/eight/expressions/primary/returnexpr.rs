@@ -1,17 +1,82 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
 use crate::eight::common::parsing::ast::RelationEntry;
-use crate::eight::expressions::primary::ExpressionRelations;
-use crate::eight::expressions::secondary::SecondaryExpression;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::parsing::ParsableExpression;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::primary::{Expression, ExpressionRelations, RunnableExpression};
+use crate::eight::expressions::secondary::{parse_secondary_expression, SecondaryExpression};
 use crate::eight::expressions::secondary::TypedSecondaryExpression;
+use crate::eight::values::ValueTypes;
+use log::debug;
 
 
 #[derive(Debug)]
 pub struct ReturnExpr {
-    expr: SecondaryExpression,
+    expr: SecondaryExpression, // NoOp for a bare 'return;'
 }
 
+impl RunnableExpression for ReturnExpr {
+    fn run_expr(&self, interpreter: &mut EightInterpreter) {
+        let m = interpreter.step(&self.expr);
+        let val = interpreter.copy_val(m);
+        interpreter.set_return_value(val);
+    }
+}
 
 impl<'a> ExpressionRelations for ReturnExpr {
     fn get_expr_references(&self) -> Vec<RelationEntry> {
         return self.expr.get_references();
     }
 }
+
+impl ParsableExpression for ReturnExpr {
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>> {
+        // 'return <expr>;' or 'return;'
+        match context.get()? {
+            Symbols::Return => (),
+            _ => return Ok(None),
+        }
+        debug!("Found return");
+
+        let sig = match context.get_current_func() {
+            Some(sig) => sig.clone(),
+            None => {
+                return Err(context.error(String::from("'return' can only be used inside a function")));
+            }
+        };
+        context.increment(); // past 'return'
+
+        let start_idx = context.get_index();
+        let (expr, typ) = if context.get()? == Symbols::Semicolon {
+            (SecondaryExpression::gen_noop(), ValueTypes::NoneType)
+        } else {
+            let expr = parse_secondary_expression(context)?;
+            match expr.get_type() {
+                Ok(t) => (expr, t),
+                Err(s) => {
+                    return Err(Diagnostic::error(
+                        format!("Error getting type of expression, '{}'", s),
+                        context.span_since(start_idx),
+                    ));
+                }
+            }
+        };
+
+        if let Err(mut e) = sig.match_output(context, typ) {
+            e.span = context.span_since(start_idx);
+            return Err(e);
+        }
+
+        match context.get()? {
+            Symbols::Semicolon => {
+                context.increment();
+                return Ok(Some(Expression::ReturnExpr(ReturnExpr { expr })));
+            }
+            o => {
+                return Err(Diagnostic::error(String::from("Expected ';' at end of expression"), context.span_after_previous())
+                    .with_note(format!("found '{}' instead", o.to_str())));
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use crate::eight::common::diagnostics::ParseResult;
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::parsing::util::consume_until_symbol;
+use crate::eight::common::parsing::ParsableExpression;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::primary::funcdecl::parse_name;
+use crate::eight::expressions::primary::{Expression, ExpressionRelations, RunnableExpression};
+use crate::eight::literals::basic::structs::StructDef;
+use crate::eight::values::namespaces::NamespaceValueTypes;
+use crate::eight::values::{parse_type, ValueTypes};
+use log::{debug, trace};
+
+
+const BUILTIN_TYPE_NAMES: [&str; 7] = ["Boolean", "String", "Number", "Float", "List", "Map", "Set"];
+
+#[derive(Debug)]
+pub struct StructDecl {
+    pub(crate) def: StructDef,
+}
+
+impl RunnableExpression for StructDecl {
+    fn run_expr(&self, _interpreter: &mut EightInterpreter) {
+        // Only the parser needs the declaration, values carry their own fields
+        trace!("Skipping declaration of struct '{}'", self.def.name);
+    }
+}
+
+impl ExpressionRelations for StructDecl {
+    fn get_expr_references(&self) -> Vec<RelationEntry> {
+        vec![]
+    }
+}
+
+impl ParsableExpression for StructDecl {
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>> {
+        debug!("Attempting StructDecl parsing");
+        // 'struct <Name> { <field>: <type>, ... }'
+        match context.get()? {
+            Symbols::Struct => (),
+            _ => {
+                trace!("No struct keyword found");
+                return Ok(None);
+            }
+        }
+        if !context.is_top_level() {
+            return Err(context.error(String::from("Structs can only be declared at the top level")));
+        }
+        context.increment(); // past 'struct'
+
+        let def = parse_struct_def(context)?;
+        context.structs.insert(def.name.clone(), def.clone());
+        return Ok(Some(Expression::StructDeclExpr(StructDecl { def })));
+    }
+}
+
+fn parse_struct_def(context: &mut Context) -> ParseResult<StructDef> {
+    let name_idx = context.get_index();
+    let name = parse_name(context, "struct name")?;
+    check_type_name(context, name.as_str(), name_idx)?;
+
+    match context.get()? {
+        Symbols::CurlyOpen => context.increment(),
+        o => {
+            return Err(context.error(format!("Expected '{{' and the fields of '{}'", name))
+                .with_note(format!("found '{}' instead", o.to_str())));
+        }
+    }
+
+    // On a bad field skip the rest of the body, so parsing resumes after the declaration
+    let fields = parse_fields(context, name.as_str());
+    if fields.is_err() {
+        if consume_until_symbol(context, Symbols::CurlyClose).is_ok() {
+            context.mark_recovered();
+        }
+    }
+    let fields = fields?;
+    if fields.is_empty() {
+        context.jump(name_idx);
+        return Err(context.error(format!("Struct '{}' needs at least one field", name)));
+    }
+    debug!("Declared struct '{}' with fields {:?}", name, fields);
+    return Ok(StructDef { name, fields });
+}
+
+pub fn check_type_name(context: &mut Context, name: &str, name_idx: usize) -> ParseResult<()> {
+    // Structs and enums share one namespace with the built in types
+    let error = if BUILTIN_TYPE_NAMES.contains(&name) || NamespaceValueTypes::is_namespace(name) || name == "Func" {
+        format!("'{}' is a built in type and can't be redefined", name)
+    } else if context.structs.contains_key(name) {
+        format!("'{}' is already defined as a struct", name)
+    } else if context.enums.contains_key(name) {
+        format!("'{}' is already defined as an enum", name)
+    } else {
+        return Ok(());
+    };
+    context.jump(name_idx);
+    return Err(context.error(error));
+}
+
+fn parse_fields(context: &mut Context, name: &str) -> ParseResult<Vec<(String, ValueTypes)>> {
+    // '<field>: <type>, ...' up to and past the '}', fields can't use the struct being declared
+    let mut fields: Vec<(String, ValueTypes)> = Vec::new();
+    loop {
+        if context.get()? == Symbols::CurlyClose {
+            context.increment();
+            return Ok(fields);
+        }
+
+        let field_idx = context.get_index();
+        let field = parse_name(context, "field name")?;
+        if fields.iter().any(|(n, _)| n == &field) {
+            context.jump(field_idx);
+            return Err(context.error(format!("Field '{}' is declared more than once", field)));
+        }
+        match context.get()? {
+            Symbols::Colon => context.increment(),
+            o => {
+                return Err(context.error(format!("Expected ':' and a type after field '{}'", field))
+                    .with_note(format!("found '{}' instead", o.to_str())));
+            }
+        }
+        fields.push((field, parse_type(context)?));
+
+        match context.get()? {
+            Symbols::Comma => context.increment(),
+            Symbols::CurlyClose => {
+                context.increment();
+                return Ok(fields);
+            }
+            o => {
+                return Err(context.error(format!("Expected ',' or '}}' in the fields of '{}', found '{}'", name, o.to_str())));
+            }
+        }
+    }
+}
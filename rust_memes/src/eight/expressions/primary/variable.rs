@@ -1,13 +1,15 @@
-use crate::eight::common::is_varname_valid;
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::{is_reserved_literal, is_varname_valid};
 use crate::eight::common::parsing::ast::RelationEntry;
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::parsing::{ParsableExpression};
-use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::common::tokenizing::symbols::{SymbolType, Symbols};
 use crate::eight::expressions::secondary::{parse_secondary_expression, TypedSecondaryExpression};
 use crate::eight::common::running::interpreter::{EightInterpreter};
 use crate::eight::expressions::primary::{Expression, ExpressionRelations, RunnableExpression};
 use crate::eight::expressions::secondary::SecondaryExpression;
-use crate::eight::values::ValueTypes;
+use crate::eight::common::tokenizing::token::Span;
+use crate::eight::values::{parse_type, ValueTypes};
 use log::{debug, trace};
 
 
@@ -15,18 +17,19 @@ use log::{debug, trace};
 pub struct Variable {
     pub(crate) name: String,
     pub value: SecondaryExpression,
+    pub(crate) typ: ValueTypes, // The annotated type if there is one, otherwise the type of value
 }
 
 impl Variable {
     pub fn get_var_type(&self) -> Result<ValueTypes, String> {
-        self.value.get_type()
+        Ok(self.typ.clone())
     }
 }
 
 impl RunnableExpression for Variable {
-    fn run_expr(self, interpreter: &mut EightInterpreter) {
-        let v = interpreter.step(self.value);
-        let val = interpreter.take_val(v);
+    fn run_expr(&self, interpreter: &mut EightInterpreter) {
+        let v = interpreter.step(&self.value);
+        let val = interpreter.copy_val(v);
 
         interpreter.add_val_to_frame(
             self.name.clone(),
@@ -48,16 +51,17 @@ impl ExpressionRelations for Box<Variable> {
 }
 
 impl ParsableExpression for Variable {
-    fn parse<'a, 'c>(context: &'c mut Context) -> Option<Expression> {
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>> {
         debug!("Attempting VariableToken parsing");
-        // 'let <name> <op> <val>'
+        // 'let <name> <op> <val>' or 'let <name>: <type> <op> <val>'
+        let start_idx = context.get_index();
 
         // Check for let keyword
-        match context.get() {
+        match context.get()? {
             Symbols::Let => (),
             _ => {
                 trace!("No let symbol found in VariableToken");
-                return None; // No 'let' keyword detected
+                return Ok(None); // No 'let' keyword detected
             }
         }
         context.increment(); // past 'let'
@@ -66,67 +70,91 @@ impl ParsableExpression for Variable {
 
         // Don't increment past delimiter so we can match the equality operator
 
-        match context.get() {
+        match context.get()? {
+            Symbols::LiteralSymb(name) if is_reserved_literal(name.as_str()) => {
+                return Err(context.error(format!("'{}' is a reserved keyword and can't be used as a variable name", name)));
+            }
             Symbols::LiteralSymb(name) => {
                 // Check variable name
                 if !is_varname_valid(name.as_str()) {
-                    panic!(
-                        "{}",
-                        context.get_panic_smessage(format!("Invalid varname '{}'", name.as_str()))
-                    );
+                    return Err(context.error(format!("Invalid varname '{}'", name.as_str())));
                 }
                 context.increment();
                 trace!("Found var '{}'", name);
 
-                match context.get() {
+                let annotation = if context.get()? == Symbols::Colon {
+                    context.increment(); // past ':'
+                    let ann_idx = context.get_index();
+                    let ann = parse_type(context)?;
+                    Some((ann, context.span_since(ann_idx)))
+                } else {
+                    None
+                };
+
+                match context.get()? {
                     Symbols::Equal => {
                         trace!("Attempting expr parse");
                         context.increment(); // Increment past equals
-                        let next_expr = parse_secondary_expression(context);
-                        let end_expr = context.get();
+                        let expr_idx = context.get_index();
+                        let next_expr = parse_secondary_expression(context)?;
+                        let end_expr = context.get()?;
 
                         // End expr check
                         match end_expr {
                             Symbols::Semicolon => {
-                                context.increment();
-                                match next_expr.get_type() {
-                                    Ok(typ) => {
-                                        context.put_var(String::from(&name), typ);
-                                    }
+                                let typ = match next_expr.get_type() {
+                                    Ok(typ) => match annotation {
+                                        Some((ann, ann_span)) => {
+                                            check_annotation(name.as_str(), ann, ann_span, typ, context.span_since(expr_idx))?
+                                        }
+                                        None => typ,
+                                    },
                                     Err(s) => {
-                                        panic!(
-                                            "{}",
-                                            context.get_panic_smessage(format!(
-                                                "Error getting type of expression, '{}'",
-                                                s
-                                            ))
-                                        );
+                                        return Err(Diagnostic::error(
+                                            format!("Error getting type of expression, '{}'", s),
+                                            context.span_since(start_idx),
+                                        ));
                                     }
-                                }
-                                return Some(Expression::VariableTokenExpr(Box::new(Variable {
+                                };
+                                // Redeclaring an existing name deliberately shadows it, type included
+                                context.put_var(String::from(&name), typ.clone());
+                                context.increment();
+                                return Ok(Some(Expression::VariableTokenExpr(Box::new(Variable {
                                     name,
                                     value: next_expr,
-                                })));
+                                    typ,
+                                }))));
                             }
-                            _ => {
-                                panic!(
-                                    "{}",
-                                    context.get_panic_message("Expected ';' at end of expression")
-                                );
+                            o => {
+                                return Err(Diagnostic::error(String::from("Expected ';' at end of expression"), context.span_after_previous())
+                                    .with_note(format!("found '{}' instead", o.to_str())));
                             }
                         }
                     }
                     f => {
-                        panic!("{}", context.get_panic_smessage(format!("Expected assignment operator after 'let' keyword, found '{:?}' instead", f)));
+                        return Err(context.error(format!("Expected assignment operator after 'let' keyword, found '{}' instead", f.to_str())));
                     }
                 }
             }
+            sym if Symbols::get_symbols_by_type(SymbolType::Keyword).contains(&sym) => {
+                return Err(context.error(format!(
+                    "'{}' is a reserved keyword and can't be used as a variable name",
+                    sym.to_str()
+                )));
+            }
             _ => {
-                panic!(
-                    "{}",
-                    context.get_panic_message("Expected string literal after let token!")
-                );
+                return Err(context.error(String::from("Expected a variable name after 'let'")));
             }
         }
     }
 }
+
+fn check_annotation(name: &str, ann: ValueTypes, ann_span: Span, typ: ValueTypes, expr_span: Span) -> ParseResult<ValueTypes> {
+    // An empty list doesn't know its element type yet, the annotation gives it one
+    if ann.accepts(&typ) {
+        return Ok(ann);
+    }
+    return Err(Diagnostic::error(format!("Mismatched types, '{}' is annotated as {} but its value is {}", name, ann, typ), expr_span)
+        .with_label(format!("expected {}, found {}", ann, typ))
+        .with_secondary(ann_span, String::from("expected because of this annotation")));
+}
@@ -0,0 +1,83 @@
+use crate::eight::common::diagnostics::ParseResult;
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::parsing::ParsableExpression;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::primary::block::Block;
+use crate::eight::expressions::primary::ifexpr::{parse_condition, skip_to_block_start};
+use crate::eight::expressions::primary::{Expression, ExpressionRelations, RunnableExpression};
+use crate::eight::expressions::secondary::{SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::literals::basic::bool::BoolVal;
+use log::{debug, trace};
+
+
+#[derive(Debug)]
+pub struct WhileExpr {
+    pub(crate) condition: SecondaryExpression,
+    pub(crate) body: Block,
+}
+
+impl RunnableExpression for WhileExpr {
+    fn run_expr(&self, interpreter: &mut EightInterpreter) {
+        loop {
+            let m = interpreter.step(&self.condition);
+            let cond = interpreter.get_val(&m).get_val::<BoolVal>().value;
+            interpreter.pop_stack_vals(m);
+            if !cond {
+                break;
+            }
+
+            self.body.run(interpreter);
+            if interpreter.take_loop_control().exits_loop() {
+                break;
+            }
+        }
+    }
+}
+
+impl ExpressionRelations for Box<WhileExpr> {
+    fn get_expr_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        for r in self.condition.get_references() {
+            rr.push(Box::from(r));
+        }
+        rr.append(&mut self.body.get_references());
+
+        vec![RelationEntry::new(vec![], vec![], rr)]
+    }
+}
+
+impl ParsableExpression for WhileExpr {
+    fn parse<'a, 'c>(context: &'c mut Context) -> ParseResult<Option<Expression>> {
+        debug!("Attempting While parsing");
+        // 'while <cond> { ... }'
+        match context.get()? {
+            Symbols::While => (),
+            _ => {
+                trace!("No while keyword found");
+                return Ok(None);
+            }
+        }
+        context.increment(); // past 'while'
+
+        // On a bad condition still skip the body, so parsing resumes after the loop
+        let condition = parse_condition(context, "while");
+        if condition.is_err() {
+            skip_to_block_start(context);
+        }
+
+        context.enter_loop();
+        let body = Block::parse(context);
+        context.exit_loop();
+
+        let condition = condition?;
+        return match body? {
+            Some(body) => Ok(Some(Expression::WhileExpr(Box::new(WhileExpr {
+                condition,
+                body,
+            })))),
+            None => Err(context.error(String::from("Expected '{' after 'while' condition"))),
+        };
+    }
+}
@@ -1,19 +1,30 @@
 pub mod callfunc;
+pub mod closure;
+pub mod collections;
+pub mod fieldaccess;
+pub mod funcref;
+pub mod higherorder;
+pub mod index;
+pub mod matchvalue;
+pub mod methodcall;
 pub mod operators;
 pub mod parentheses;
 
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::parsing::ParsableOperator;
 use crate::eight::common::tokenizing::symbols::Symbols;
 use crate::eight::values::namespaces::parse_namespace;
-use crate::eight::literals::basic::functions::parse_function_call;
+use crate::eight::literals::basic::functions::parse_user_func_call;
 use crate::eight::literals::basic::variableref::VariableTokenRef;
 use crate::eight::literals::{Literal, parse_literal};
 use crate::eight::common::running::interpreter::EightInterpreter;
 use crate::eight::common::parsing::ast::RelationEntry;
 use crate::eight::expressions::primary::noop::NoOp;
 use crate::eight::expressions::secondary::callfunc::CallFunc;
-use crate::eight::expressions::secondary::operators::binary::{BinaryOperator, sym_to_binop};
+use crate::eight::expressions::secondary::operators::binary::{binop_to_sym, sym_to_binop, nesting, BinaryOperator, BinaryOperators, MAX_OPERATOR_DEPTH};
+use crate::eight::expressions::secondary::operators::Associativity;
+use crate::eight::expressions::secondary::operators::unary::UnaryOperator;
 use crate::eight::values::{Value, ValueTypes};
 use crate::eight::common::running::memory::memref::MemRef;
 use crate::eight::expressions::primary::format::Format;
@@ -22,20 +33,19 @@ use log::{debug, trace};
 use enum_dispatch::enum_dispatch;
 use crate::eight::expressions::secondary::parentheses::Parentheses;
 use crate::eight::literals::basic::list::ListExpression;
+use crate::eight::literals::basic::map::MapExpression;
+use crate::eight::literals::basic::structs::StructExpression;
+use crate::eight::literals::basic::enums::EnumExpression;
+use crate::eight::expressions::secondary::closure::Closure;
+use crate::eight::expressions::secondary::collections::CollectionCall;
+use crate::eight::expressions::secondary::fieldaccess::FieldAccess;
+use crate::eight::expressions::secondary::funcref::FuncRef;
+use crate::eight::expressions::secondary::higherorder::HigherOrder;
+use crate::eight::expressions::secondary::index::Index;
+use crate::eight::expressions::secondary::matchvalue::MatchValue;
+use crate::eight::expressions::secondary::methodcall::MethodCall;
 
 
-#[derive(Debug)]
-pub enum IntermediateExpression {
-    Operator(Symbols),
-    SecondaryExpr(SecondaryExpression),
-}
-
-#[derive(Debug)]
-pub enum TransitionExpression {
-    InterExpr(IntermediateExpression),
-    TransExpr(SecondaryExpression),
-}
-
 #[enum_dispatch]
 pub trait TypedSecondaryExpression {
     fn get_type(&self) -> Result<ValueTypes, String>;
@@ -44,7 +54,7 @@ pub trait TypedSecondaryExpression {
 }
 
 pub trait RunnableSecondaryExpression {
-    fn run_secondary_expr(self, interpreter: &mut EightInterpreter) -> MemRef;
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef;
 }
 
 #[enum_dispatch(TypedSecondaryExpression)]
@@ -52,34 +62,50 @@ pub trait RunnableSecondaryExpression {
 pub enum SecondaryExpression {
     LiteralExpr(Literal),
     ListExpr(ListExpression),
+    MapExpr(MapExpression),
+    StructExpr(StructExpression),
+    EnumExpr(EnumExpression),
     BinaryOperation(Box<BinaryOperator>),
+    UnaryOperation(Box<UnaryOperator>),
     CallResult(CallFunc),
     Reference(VariableTokenRef),
     NoOpExpr(NoOp),
     FormatExpr(Box<Format>),
-    ParenExpr(Box<Parentheses>)
+    ParenExpr(Box<Parentheses>),
+    ClosureExpr(Box<Closure>),
+    FuncRefExpr(FuncRef),
+    HigherOrderExpr(Box<HigherOrder>),
+    CollectionExpr(Box<CollectionCall>),
+    IndexExpr(Box<Index>),
+    FieldExpr(Box<FieldAccess>),
+    MethodExpr(Box<MethodCall>),
+    MatchValueExpr(Box<MatchValue>)
     // TypecastExpr()
 }
 
 impl RunnableSecondaryExpression for SecondaryExpression {
-    fn run_secondary_expr(mut self, interpreter: &mut EightInterpreter) -> MemRef {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
         match self {
-            SecondaryExpression::BinaryOperation(mut a) => {
+            SecondaryExpression::BinaryOperation(a) => {
                 a.run_secondary_expr(interpreter)
             }
-            SecondaryExpression::Reference(mut r) => {
+            SecondaryExpression::UnaryOperation(u) => {
+                u.run_secondary_expr(interpreter)
+            }
+            SecondaryExpression::Reference(r) => {
                 r.run_secondary_expr(interpreter)
             }
-            SecondaryExpression::CallResult(mut c) => {
+            SecondaryExpression::CallResult(c) => {
                 c.run_secondary_expr(interpreter)
             }
             SecondaryExpression::NoOpExpr(_) => {
                 MemRef::empty()
             },
             SecondaryExpression::LiteralExpr(l) => {
-                interpreter.add_val_to_stack(Value::Literal(l))
+                // Expressions can be run more than once, so the literal is copied onto the stack
+                interpreter.add_val_to_stack(Value::Literal(l.copy()))
             },
-            SecondaryExpression::FormatExpr(mut f) => {
+            SecondaryExpression::FormatExpr(f) => {
                 f.run_secondary_expr(interpreter)
             },
             SecondaryExpression::ParenExpr(p) => {
@@ -87,6 +113,39 @@ impl RunnableSecondaryExpression for SecondaryExpression {
             },
             SecondaryExpression::ListExpr(l) => {
                 l.run_secondary_expr(interpreter)
+            },
+            SecondaryExpression::MapExpr(m) => {
+                m.run_secondary_expr(interpreter)
+            },
+            SecondaryExpression::StructExpr(s) => {
+                s.run_secondary_expr(interpreter)
+            },
+            SecondaryExpression::EnumExpr(e) => {
+                e.run_secondary_expr(interpreter)
+            },
+            SecondaryExpression::ClosureExpr(c) => {
+                c.run_secondary_expr(interpreter)
+            },
+            SecondaryExpression::FuncRefExpr(f) => {
+                f.run_secondary_expr(interpreter)
+            },
+            SecondaryExpression::HigherOrderExpr(h) => {
+                h.run_secondary_expr(interpreter)
+            },
+            SecondaryExpression::CollectionExpr(c) => {
+                c.run_secondary_expr(interpreter)
+            },
+            SecondaryExpression::IndexExpr(i) => {
+                i.run_secondary_expr(interpreter)
+            },
+            SecondaryExpression::FieldExpr(f) => {
+                f.run_secondary_expr(interpreter)
+            },
+            SecondaryExpression::MethodExpr(m) => {
+                m.run_secondary_expr(interpreter)
+            },
+            SecondaryExpression::MatchValueExpr(m) => {
+                m.run_secondary_expr(interpreter)
             }
         }
     }
@@ -105,183 +164,104 @@ impl SecondaryExpression {
     }
 }
 
-pub fn get_operator_order() -> Vec<Symbols> {
-    let mut op_order: Vec<Symbols> = Vec::new();
-    op_order.push(Symbols::Exponent);
-    op_order.push(Symbols::Multiply);
-    op_order.push(Symbols::Divide);
-    op_order.push(Symbols::Divide);
-    op_order.push(Symbols::Modulus);
-    op_order.push(Symbols::Add);
-    op_order.push(Symbols::Sub);
-    op_order.push(Symbols::EqualityCheck);
-    return op_order;
+pub fn match_operator_symbol(context: &mut Context) -> ParseResult<Option<BinaryOperators>> {
+    return Ok(sym_to_binop(&context.get()?));
 }
 
-pub fn match_operator_symbol(context: &mut Context) -> Option<Symbols> {
-    let sym = context.get();
-    let ops = get_operator_order();
-    for op in ops {
-        if sym == op {
-            return Some(sym);
+pub fn parse_operand<'a>(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+    // Parse a single value, anything that can sit on either side of a binary operator
+    let start_idx = context.get_index();
+    let mut secexpr: Option<SecondaryExpression> = None;
+    let mut found = false;
+
+    // Literal here
+    debug!("Attempting Literal parse");
+    match parse_literal(context)? {
+        Some(x) => {
+            secexpr = Some(SecondaryExpression::LiteralExpr(x));
+            found = true;
+        }
+        None => {
+            debug!("Value parse fail");
         }
     }
-    return None;
-}
 
-pub fn organize_secondary_expression_list<'a, 'b>(context: &mut Context<'a>, mut expr_list: Vec<IntermediateExpression>) -> SecondaryExpression {
-    /*
-     Take a list of Expressions (Value Expression) and Operators,
-     apply Operation order rules and return a unified expression
-    */
-    let mut expression_list = Vec::new();
-    let op_order = get_operator_order();
-
-    // Transition to expression
-    for expr in expr_list {
-        match expr {
-            IntermediateExpression::SecondaryExpr(sec) => {
-                expression_list.push(TransitionExpression::TransExpr(sec));
-            }
-            o => {
-                expression_list.push(TransitionExpression::InterExpr(o));
+    // Prefix operators '!' and '-', tried after literals so '-5' stays a single negative literal
+    if !found {
+        debug!("Attempting unary operator parse");
+        match UnaryOperator::parse(context)? {
+            Some(u) => {
+                secexpr = Some(u);
+                found = true;
+            },
+            None => {
+                debug!("Unary operator parse fail");
             }
         }
     }
 
-    let expression_limit = 1000;
-    let mut expr_count = 0;
-
-    for op in op_order.iter() {
-        loop {
-            let mut found = false;
-            let mut found_idx = 0;
-            let mut found_op = None;
-            let mut idx = 0;
-
-            for expr in expression_list.iter() {
-                match expr {
-                    TransitionExpression::InterExpr(interexpr) => {
-                        if expr_count >= expression_limit {
-                            panic!("{}", context.get_panic_smessage(format!("Expression parsing limit reached, can't simplify expression, too large")));
-                        }
-
-                        match interexpr {
-                            IntermediateExpression::Operator(interop) => {
-                                if op == interop {
-                                    found_op = Some(op);
-                                    found_idx = idx;
-                                    found = true;
-                                    break;
-                                } else {
-                                    idx += 1;
-                                    continue;
-                                }
-                            }
-                            _ => {
-                                expr_count += 1;
-                                idx += 1;
-                                continue;
-                            }
-                        }
-                    }
-                    oo => {
-                        idx += 1;
-                        continue;
-                    }
-                }
-            }
+    //Secondary expressions here
 
-            if found {
-                BinaryOperator::parse(context, &mut expression_list, found_idx, sym_to_binop(&found_op.unwrap(), &context));
-                continue;
-            } else {
-                break; // Break out of loop
+    // Parse list expression
+    if !found {
+        debug!("Attempting list expression parse");
+        match ListExpression::parse(context)? {
+            Some(s) => {
+                secexpr = Some(s);
+                found = true;
+            },
+            None => {
+                debug!("list expression parse fail");
             }
         }
     }
 
-    if expression_list.len() != 1 {
-        panic!(
-            "{}",
-            context.get_panic_smessage(format!(
-                "Expressionlist not simplified! '{:?}'",
-                expression_list
-            ))
-        );
+    // Parse map expression
+    if !found {
+        debug!("Attempting map expression parse");
+        match MapExpression::parse(context)? {
+            Some(s) => {
+                secexpr = Some(s);
+                found = true;
+            },
+            None => {
+                debug!("map expression parse fail");
+            }
+        }
     }
 
-    let val = expression_list.pop();
-    match val {
-        Some(v) => {
-            return match v {
-                TransitionExpression::TransExpr(ex) => ex,
-                TransitionExpression::InterExpr(ex) => {
-                    return match ex {
-                        IntermediateExpression::SecondaryExpr(s) => s,
-                        _ => {
-                            panic!(
-                                "{}",
-                                context.get_panic_smessage(format!(
-                                    "Non-value intermediate expression found"
-                                ))
-                            );
-                        }
-                    };
-                }
-            };
-        }
-        None => {
-            panic!(
-                "{}",
-                context.get_panic_smessage(format!("No expression left after simplifying!"))
-            );
+    // Parse struct construction
+    if !found {
+        debug!("Attempting struct construction parse");
+        match StructExpression::parse(context)? {
+            Some(s) => {
+                secexpr = Some(s);
+                found = true;
+            },
+            None => {
+                debug!("struct construction parse fail");
+            }
         }
     }
-}
-
-fn secondary_expr_to_intermediate(s: SecondaryExpression) -> IntermediateExpression {
-    IntermediateExpression::SecondaryExpr(s)
-}
 
-pub fn partial_parse_secondary_expression<'a>(context: &mut Context) -> Option<Vec<IntermediateExpression>> {
-    let mut secexpr: Option<SecondaryExpression> = None;
-    let mut found = false;
-
-    // TODO check pre-operator '!' here
-
-    // Literal here
-    debug!("Attempting Literal parse");
-    match parse_literal(context) {
-        Some(x) => {
-            secexpr = Some(SecondaryExpression::LiteralExpr(x));
-            found = true;
-        }
-        None => {
-            debug!("Value parse fail");
+    // Parse enum variant construction
+    if !found {
+        debug!("Attempting enum variant parse");
+        match EnumExpression::parse(context)? {
+            Some(e) => {
+                secexpr = Some(e);
+                found = true;
+            },
+            None => {
+                debug!("enum variant parse fail");
+            }
         }
     }
 
-    //Secondary expressions here
-
-    // // Parse list expression TODO List parsing
-    // if !found {
-    //     debug!("Attempting list expression parse");
-    //     match ListExpression::parse(context) {
-    //         Some(s) => {
-    //             secexpr = Some(s);
-    //             found = true;
-    //         },
-    //         None => {
-    //             debug!("list expression parse fail");
-    //         }
-    //     }
-    // }
-
     // Parse fmt function call
     if !found {
         debug!("Attempting fmt call parse");
-        match Format::parse(context) {
+        match Format::parse(context)? {
             Some(s) => {
                 secexpr = Some(s);
                 found = true;
@@ -292,12 +272,69 @@ pub fn partial_parse_secondary_expression<'a>(context: &mut Context) -> Option<V
         }
     }
 
+    // Parse closure
+    if !found {
+        debug!("Attempting closure parse");
+        match Closure::parse(context)? {
+            Some(c) => {
+                secexpr = Some(c);
+                found = true;
+            },
+            None => {
+                debug!("Closure parse fail");
+            }
+        }
+    }
+
+    // Parse map/reduce, before user funcs since they can't be redeclared
+    if !found {
+        debug!("Attempting map/reduce parse");
+        match HigherOrder::parse(context)? {
+            Some(h) => {
+                secexpr = Some(h);
+                found = true;
+            },
+            None => {
+                debug!("map/reduce parse fail");
+            }
+        }
+    }
+
+    // Parse keys/values/set/contains/insert
+    if !found {
+        debug!("Attempting collection func parse");
+        match CollectionCall::parse(context)? {
+            Some(c) => {
+                secexpr = Some(c);
+                found = true;
+            },
+            None => {
+                debug!("Collection func parse fail");
+            }
+        }
+    }
+
+    // Parse match used as a value
+    if !found {
+        debug!("Attempting match value parse");
+        match MatchValue::parse(context)? {
+            Some(m) => {
+                secexpr = Some(m);
+                found = true;
+            },
+            None => {
+                debug!("Match value parse fail");
+            }
+        }
+    }
+
     // Parse parentheses
     if !found {
         debug!("Attempting paren parse");
-        match Parentheses::parse(context) {
+        match Parentheses::parse(context)? {
             Some(p) => {
                 secexpr = Some(p);
+                found = true;
             },
             None => {
                 debug!("Paren parse fail");
@@ -305,25 +342,24 @@ pub fn partial_parse_secondary_expression<'a>(context: &mut Context) -> Option<V
         }
     }
 
-    // Parse function call
-    // if !found {
-    //     debug!("Attempting Function call parse");
-    //     match parse_function_call(context) {
-    //         Some((name, args)) => {
-    //             // TODO figure out if the literal 'name' exists in the current scope, and if the
-    //             // args match the signature, then return a CallResult(FuncReference, args)
-    //             todo!();
-    //         }
-    //         None => {
-    //             debug!("Function call parse fail");
-    //         }
-    //     }
-    // }
+    // Parse user function call
+    if !found {
+        debug!("Attempting Function call parse");
+        match parse_user_func_call(context)? {
+            Some(c) => {
+                secexpr = Some(c);
+                found = true;
+            }
+            None => {
+                debug!("Function call parse fail");
+            }
+        }
+    }
 
     // Parse namespaced var
     if !found {
         debug!("Attempting namespace parse");
-        match parse_namespace(context) {
+        match parse_namespace(context)? {
             Some(x) => {
                 secexpr = Some(x);
                 found = true;
@@ -336,10 +372,24 @@ pub fn partial_parse_secondary_expression<'a>(context: &mut Context) -> Option<V
 
     // TODO Pointer parsing here
 
+    // Parse a declared func used as a value
+    if !found {
+        debug!("Attempting func reference parse");
+        match FuncRef::parse(context)? {
+            Some(f) => {
+                secexpr = Some(f);
+                found = true;
+            }
+            None => {
+                debug!("Func reference parse fail");
+            }
+        }
+    }
+
     // Parse VariableTokenRef
     if !found {
         debug!("Attempting VarTokenRef parse");
-        match VariableTokenRef::parse(context) {
+        match VariableTokenRef::parse(context)? {
             Some(x) => {
                 secexpr = Some(x);
                 found = true;
@@ -351,51 +401,83 @@ pub fn partial_parse_secondary_expression<'a>(context: &mut Context) -> Option<V
     }
 
     return match secexpr {
-        Some(x) => match match_operator_symbol(context) {
-            Some(op) => {
-                trace!("Found operator {:?}", op);
-                let mut v = Vec::new();
-                v.push(secondary_expr_to_intermediate(x));
-                v.push(IntermediateExpression::Operator(op.clone()));
-                context.increment();
-
-                match partial_parse_secondary_expression(context) {
-                    Some(n) => {
-                        for i in n.into_iter() {
-                            let g = i;
-                            v.push(g);
-                        }
-                        Some(v)
-                    }
-                    None => {
-                        panic!(
-                            "{}",
-                            context.get_panic_smessage(format!(
-                                "Expected expression after operator '{:?}'",
-                                op.clone()
-                            ))
-                        );
-                    }
-                }
-            }
-            None => {
-                trace!("Didn't find operator, just expr {:?}", &x);
-                let mut v = Vec::new();
-                v.push(secondary_expr_to_intermediate(x));
-                Some(v)
+        Some(x) => Ok(Some(parse_postfix(context, x, start_idx)?)),
+        None => Ok(None),
+    };
+}
+
+fn parse_postfix(context: &mut Context, operand: SecondaryExpression, start_idx: usize) -> ParseResult<SecondaryExpression> {
+    // Any number of 'xs[i]', 'xs[a..b]', 'p.x' and 'xs.len()' after a value, they bind tighter than any operator
+    let mut operand = operand;
+    loop {
+        operand = match context.get_safe_multiple(3).as_slice() {
+            [Ok(Symbols::BracketOpen), ..] => Index::parse(context, operand, start_idx)?,
+            [Ok(Symbols::ValueAccessor), Ok(Symbols::LiteralSymb(_)), Ok(Symbols::ParenOpen)] => MethodCall::parse(context, operand, start_idx)?,
+            [Ok(Symbols::ValueAccessor), ..] => FieldAccess::parse(context, operand, start_idx)?,
+            _ => return Ok(operand),
+        };
+    }
+}
+
+const MAX_NESTING_DEPTH: usize = 128;
+
+pub fn parse_binary_expression(context: &mut Context, min_precedence: u8, after_op: Option<&BinaryOperators>) -> ParseResult<SecondaryExpression> {
+    // Precedence climbing, keep folding operators into lhs while they bind at least as tight as min_precedence
+    // Left associative operators parse their rhs one level tighter so 1 - 2 + 3 groups as (1 - 2) + 3
+    let start_idx = context.get_index();
+    let mut lhs = match parse_operand(context)? {
+        Some(x) => x,
+        None => {
+            if let Symbols::LiteralSymb(name) = context.get()? {
+                return Err(context.error(format!("Cannot find variable '{}' in this scope", name)));
             }
-        },
-        None => None,
+            return Err(context.error(match after_op {
+                Some(op) => format!("Expected expression after operator '{}'", binop_to_sym(op).to_str()),
+                None => String::from("Expected an expression"),
+            }));
+        }
     };
+
+    loop {
+        let op = match match_operator_symbol(context)? {
+            Some(op) => op,
+            None => break,
+        };
+        let precedence = op.precedence();
+        if precedence < min_precedence {
+            break;
+        }
+        trace!("Found operator {:?}", op);
+        let op_span = context.get_span();
+        context.increment();
+
+        let next_min = match op.associativity() {
+            Associativity::Left => precedence + 1,
+            Associativity::Right => precedence,
+        };
+        let rhs = parse_nested(context, |context| parse_binary_expression(context, next_min, Some(&op)))?;
+        if nesting(&lhs).max(nesting(&rhs)) >= MAX_OPERATOR_DEPTH {
+            return Err(Diagnostic::error(format!("Expression chains too many operators, at most {} deep are supported", MAX_OPERATOR_DEPTH), op_span)
+                .with_note(String::from("split it up with 'let' bindings")));
+        }
+        lhs = BinaryOperator::parse(lhs, rhs, op, context.span_since(start_idx))?;
+    }
+    return Ok(lhs);
+}
+
+pub fn parse_secondary_expression<'a, 'b>(context: &'a mut Context) -> ParseResult<SecondaryExpression> {
+    parse_nested(context, |context| parse_binary_expression(context, 0, None))
 }
 
-pub fn parse_secondary_expression<'a, 'b>(context: &'a mut Context) -> SecondaryExpression {
-    let val = partial_parse_secondary_expression(context);
-    match val {
-        Some(mut x) => organize_secondary_expression_list(context, x),
-        None => panic!(
-            "{}",
-            context.get_panic_message("Unknown secondary expression statement")
-        ),
+fn parse_nested<F>(context: &mut Context, parse: F) -> ParseResult<SecondaryExpression>
+    where F: FnOnce(&mut Context) -> ParseResult<SecondaryExpression> {
+    // Parsing recurses for every '(', call argument and right associative operand, each level takes a lot of stack
+    if context.expression_depth() >= MAX_NESTING_DEPTH {
+        return Err(context.error(format!("Expression is nested too deeply, at most {} levels are supported", MAX_NESTING_DEPTH))
+            .with_note(String::from("split it up with 'let' bindings")));
     }
+    context.enter_expression();
+    let res = parse(context);
+    context.exit_expression();
+    return res;
 }
@@ -33,6 +33,9 @@ impl TypedSecondaryExpression for CallFunc {
 
     fn get_references(&self) -> Vec<RelationEntry> {
         let mut r = RelationEntry::new(vec![], vec![], vec![]);
+        if let FuncContent::VarRef = self.func.content {
+            r.reference.push(self.func.name.clone());
+        }
         for arg in self.args.get_arglist() {
             for refr in arg.get_references() {
                 r.entries.push(Box::from(refr));
@@ -45,36 +48,32 @@ impl TypedSecondaryExpression for CallFunc {
 }
 
 impl RunnableSecondaryExpression for CallFunc {
-    fn run_secondary_expr(mut self, interpreter: &mut EightInterpreter) -> MemRef {
-        let mut f: Func = std::mem::replace(
-            &mut self.func,
-            Func::empty(),
-        );
-
-        let mut args: FuncArgs = std::mem::replace(
-            &mut self.args,
-            FuncArgs::empty(),
-        );
-
-        return match f.content {
-            FuncContent::Func(exprs) => {
-                // TODO impl user funcs
-                panic!("TODO user defined functions not implemented yet");
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        return match &self.func.content {
+            content @ (FuncContent::Func(_) | FuncContent::Declared | FuncContent::Closure(_) | FuncContent::VarRef) => {
+                // User funcs are looked up by name so recursive calls don't need to own the body
+                let mut args: Vec<Value> = Vec::new();
+                for arg in self.args.get_arglist() {
+                    let d = interpreter.step(arg);
+                    args.push(interpreter.copy_val(d));
+                }
+                trace!("Calling user func '{}'", self.func.name);
+                match content {
+                    FuncContent::VarRef => interpreter.call_func_var(self.func.name.as_str(), args),
+                    FuncContent::Closure(_) => interpreter.call_func_value(&self.func, args),
+                    _ => interpreter.call_user_func(self.func.name.as_str(), args),
+                }
             },
             FuncContent::BuiltInFunc(func) => {
                 trace!("Running built in func");
-                let mut fn_vars: Vec<(String, MemRef)> = Vec::new();
+                // Args are passed by value, so the callers variables are left untouched
                 let mut fn_args: Vec<(String, Value)> = Vec::new();
 
-                for idx in 0..args.arglist.len() {
-                    let mut s = args.grab_arg(idx);
-                    let d = interpreter.step( s);
+                for (idx, arg) in self.args.get_arglist().iter().enumerate() {
+                    let d = interpreter.step(arg);
                     trace!("Parsing arg #{} -> '{:?}'", idx, d);
-                    let name = format!("${}", idx);
-                    fn_vars.push((name.clone(), d.copy()));
-
-                    let val = interpreter.take_val(d);
-                    fn_args.push((name, val));
+                    let val = interpreter.copy_val(d);
+                    fn_args.push((format!("${}", idx), val));
                 }
                 trace!("Creating new frame for func, adding args");
                 interpreter.new_frame();
@@ -86,22 +85,10 @@ impl RunnableSecondaryExpression for CallFunc {
                 }
                 trace!("Running func");
                 let o = func(interpreter);
-                trace!("Grabbing args passed by value");
-                let mut mod_args: Vec<(MemRef, Value)> = Vec::new();
 
-                for (nam, mem) in fn_vars {
-                    let m = interpreter.get_val_from_frame(nam.as_str());
-                    let v = interpreter.take_val(m);
-                    mod_args.push((mem, v));
-                }
                 trace!("Popping func frame");
                 interpreter.pop_or_clear_frame();
 
-                trace!("Adding back arg values");
-                for (mem, val) in mod_args {
-                    interpreter.add_mem_to_frame(mem, val);
-                }
-
                 return o;
             }
         };
@@ -0,0 +1,102 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::primary::funcdecl::parse_params;
+use crate::eight::expressions::secondary::{parse_secondary_expression, RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::literals::basic::functions::signature::FuncSignature;
+use crate::eight::literals::basic::functions::Func;
+use crate::eight::values::{Value, ValueTypes};
+use log::{debug, trace};
+use std::rc::Rc;
+
+
+#[derive(Debug)]
+pub struct Closure {
+    pub(crate) params: Vec<String>,
+    pub(crate) sig: FuncSignature,
+    pub(crate) body: Rc<SecondaryExpression>,
+    pub(crate) captures: Vec<String>, // Outer variables used by the body, copied when the closure is created
+}
+
+impl TypedSecondaryExpression for Box<Closure> {
+    fn get_type(&self) -> Result<ValueTypes, String> {
+        Ok(ValueTypes::FuncType(Box::new(self.sig.clone())))
+    }
+
+    fn get_references(&self) -> Vec<RelationEntry> {
+        // Params are local to the body, only the captures are read from the defining frame
+        vec![RelationEntry::new(vec![], self.captures.clone(), vec![])]
+    }
+}
+
+impl RunnableSecondaryExpression for Closure {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        let mut captures = Vec::new();
+        for name in &self.captures {
+            let m = interpreter.get_val_from_frame(name.as_str());
+            captures.push((name.clone(), interpreter.copy_val(m)));
+        }
+        trace!("Creating closure with captures {:?}", captures);
+
+        let func = Func::new_closure(self.params.clone(), self.sig.clone(), Rc::clone(&self.body), captures);
+        interpreter.add_val_to_stack(Value::Function(func))
+    }
+}
+
+impl Closure {
+    pub fn parse(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+        // '|<arg>: <type>, ...| <expr>', '||' is a closure without params
+        let start_idx = context.get_index();
+        let params = match context.get()? {
+            Symbols::Pipe => {
+                context.increment();
+                parse_params(context, Symbols::Pipe, "the closure")?
+            }
+            Symbols::Or => {
+                context.increment();
+                vec![]
+            }
+            _ => return Ok(None),
+        };
+        debug!("Found closure with params {:?}", params);
+
+        // The body sees the params on top of every variable in scope where the closure is written
+        let outer = context.vartable.clone();
+        context.push_scope();
+        for (name, typ) in &params {
+            context.put_var(name.clone(), typ.clone());
+        }
+        let body = parse_secondary_expression(context);
+        context.pop_scope();
+        let body = body?;
+
+        let output = match body.get_type() {
+            Ok(t) => t,
+            Err(s) => {
+                return Err(Diagnostic::error(
+                    format!("Error getting type of closure body, '{}'", s),
+                    context.span_since(start_idx),
+                ));
+            }
+        };
+
+        let mut referenced = Vec::new();
+        for r in body.get_references() {
+            r.collect_references(&mut referenced);
+        }
+        let captures = referenced.into_iter()
+            .filter(|name| outer.contains_key(name) && !params.iter().any(|(p, _)| p == name))
+            .collect();
+
+        let sig = FuncSignature::new(params.iter().map(|(_, t)| t.clone()).collect(), output);
+        return Ok(Some(SecondaryExpression::ClosureExpr(Box::new(Closure {
+            params: params.into_iter().map(|(n, _)| n).collect(),
+            sig,
+            body: Rc::new(body),
+            captures,
+        }))));
+    }
+}
@@ -0,0 +1,240 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::running::error::RuntimeError;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::secondary::{RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::literals::basic::bool::BoolVal;
+use crate::eight::literals::basic::functions::parse_function_call;
+use crate::eight::literals::basic::list::List;
+use crate::eight::literals::basic::set::Set;
+use crate::eight::literals::Literal;
+use crate::eight::values::{BasicValue, Value, ValueTypes};
+use log::{debug, trace};
+
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CollectionFunc {
+    Keys,     // 'keys(m)', the keys of a Map as a List
+    Values,   // 'values(m)', the values of a Map as a List
+    ToSet,    // 'set(xs)', a Set of the elements of a List
+    Contains, // 'contains(c, x)', whether a List or Set holds x, or a Map has the key x
+    Insert,   // 'insert(m, k, v)' or 'insert(s, x)', changes the Map or Set held by the variable in place
+}
+
+#[derive(Debug)]
+pub struct CollectionCall {
+    pub(crate) kind: CollectionFunc,
+    pub(crate) args: Vec<SecondaryExpression>,
+    pub(crate) typ: ValueTypes,
+}
+
+impl TypedSecondaryExpression for Box<CollectionCall> {
+    fn get_type(&self) -> Result<ValueTypes, String> {
+        Ok(self.typ.clone())
+    }
+
+    fn get_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        for arg in &self.args {
+            for r in arg.get_references() {
+                rr.push(Box::from(r));
+            }
+        }
+        vec![RelationEntry::new(vec![], vec![], rr)]
+    }
+}
+
+impl RunnableSecondaryExpression for CollectionCall {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        if self.kind == CollectionFunc::Insert {
+            return self.run_insert(interpreter);
+        }
+
+        let m = interpreter.step(&self.args[0]);
+        let result = match self.kind {
+            CollectionFunc::Keys | CollectionFunc::Values => match interpreter.get_val(&m) {
+                Value::MapVal(map) => {
+                    let keys = self.kind == CollectionFunc::Keys;
+                    Value::ListVal(List {
+                        data: map.entries.iter().map(|(k, v)| if keys { k.copy() } else { v.copy() }).collect(),
+                        typ: if keys { map.key_typ.clone() } else { map.val_typ.clone() },
+                    })
+                }
+                o => {
+                    RuntimeError::new(format!("Expected a Map, found {:?}", o)).raise();
+                }
+            },
+            CollectionFunc::ToSet => match interpreter.get_val(&m) {
+                Value::ListVal(l) => {
+                    let mut set = Set::new(l.typ.clone());
+                    for v in &l.data {
+                        set.insert(v.copy());
+                    }
+                    Value::SetVal(set)
+                }
+                o => {
+                    RuntimeError::new(format!("Expected a List, found {:?}", o)).raise();
+                }
+            },
+            _ => {
+                let m2 = interpreter.step(&self.args[1]);
+                let needle = interpreter.get_val(&m2);
+                let found = match interpreter.get_val(&m) {
+                    Value::MapVal(map) => map.contains(needle),
+                    Value::SetVal(set) => set.contains(needle),
+                    Value::ListVal(l) => l.data.iter().any(|v| v.eq(needle)),
+                    o => {
+                        RuntimeError::new(format!("Cannot search {:?}", o)).raise();
+                    }
+                };
+                interpreter.pop_stack_vals(m2);
+                Value::Literal(Literal::Boolean(BoolVal { value: found }))
+            }
+        };
+        trace!("{:?} result {:?}", self.kind, result);
+        interpreter.pop_stack_vals(m);
+        interpreter.add_val_to_stack(result)
+    }
+}
+
+impl CollectionCall {
+    fn run_insert(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        let name = match &self.args[0] {
+            SecondaryExpression::Reference(r) => r.varname.as_str(),
+            o => {
+                RuntimeError::new(format!("Can only insert into a variable, found {:?}", o)).raise();
+            }
+        };
+        let mut vals = Vec::new();
+        for arg in &self.args[1..] {
+            let m = interpreter.step(arg);
+            vals.push(interpreter.copy_val(m));
+        }
+
+        match (interpreter.get_mut_val_from_frame(name), vals.len()) {
+            (Value::MapVal(map), 2) => {
+                let val = vals.pop().unwrap();
+                map.insert(vals.pop().unwrap(), val);
+            }
+            (Value::SetVal(set), 1) => set.insert(vals.pop().unwrap()),
+            (o, _) => {
+                RuntimeError::new(format!("Cannot insert {:?} into {:?}", vals, o)).raise();
+            }
+        }
+        return MemRef::empty();
+    }
+
+    pub fn parse(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+        let start_idx = context.get_index();
+        let kind = match context.get_safe_multiple(2).as_slice() {
+            [Ok(Symbols::LiteralSymb(name)), Ok(Symbols::ParenOpen)] => match name.as_str() {
+                "keys" => CollectionFunc::Keys,
+                "values" => CollectionFunc::Values,
+                "set" => CollectionFunc::ToSet,
+                "contains" => CollectionFunc::Contains,
+                "insert" => CollectionFunc::Insert,
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        debug!("Found {:?} call", kind);
+
+        let (name, args) = match parse_function_call(context)? {
+            Some(call) => call,
+            None => return Ok(None),
+        };
+        let args = args.to_vec();
+        let arg_types: Result<Vec<ValueTypes>, String> = args.iter().map(|a| a.get_type()).collect();
+        let arg_types = match arg_types {
+            Ok(t) => t,
+            Err(s) => {
+                return Err(Diagnostic::error(format!("Invalid type in arg! Err: '{}'", s), context.span_since(start_idx)));
+            }
+        };
+
+        let (typ, refined) = match check_types(kind, &arg_types) {
+            Ok(t) => t,
+            Err(e) => {
+                return Err(Diagnostic::error(format!("Invalid call to '{}', {}", name, e), context.span_since(start_idx)));
+            }
+        };
+
+        if kind == CollectionFunc::Insert {
+            match &args[0] {
+                SecondaryExpression::Reference(r) => {
+                    if let Some(t) = refined {
+                        // 'let m = {};' gets its types from the first entry inserted
+                        context.refine_var(r.varname.as_str(), t);
+                    }
+                }
+                _ => {
+                    return Err(Diagnostic::error(format!("Invalid call to '{}', the first argument must be a variable", name), context.span_since(start_idx))
+                        .with_note(String::from("the Map or Set is changed in place, so it has to be stored somewhere")));
+                }
+            }
+        }
+
+        return Ok(Some(SecondaryExpression::CollectionExpr(Box::new(CollectionCall {
+            kind,
+            args,
+            typ,
+        }))));
+    }
+}
+
+fn check_types(kind: CollectionFunc, arg_types: &[ValueTypes]) -> Result<(ValueTypes, Option<ValueTypes>), String> {
+    // Returns the type of the whole call, and for 'insert' the refined type of an empty collection
+    let (expected_args, usage) = match kind {
+        CollectionFunc::Keys => (1..=1, "keys(map)"),
+        CollectionFunc::Values => (1..=1, "values(map)"),
+        CollectionFunc::ToSet => (1..=1, "set(list)"),
+        CollectionFunc::Contains => (2..=2, "contains(collection, value)"),
+        CollectionFunc::Insert => (2..=3, "insert(map, key, value) or insert(set, value)"),
+    };
+    if !expected_args.contains(&arg_types.len()) {
+        return Err(format!("expected {} but {} argument(s) were given", usage, arg_types.len()));
+    }
+
+    let fits = |expected: &ValueTypes, found: &ValueTypes| *expected == ValueTypes::NoneType || expected.accepts(found);
+    return match (kind, &arg_types[0]) {
+        (CollectionFunc::Keys, ValueTypes::MapType(k, _)) => Ok((ValueTypes::ListType(k.clone()), None)),
+        (CollectionFunc::Values, ValueTypes::MapType(_, v)) => Ok((ValueTypes::ListType(v.clone()), None)),
+        (CollectionFunc::Keys, o) | (CollectionFunc::Values, o) => Err(format!("expected a Map, found {}", o)),
+        (CollectionFunc::ToSet, ValueTypes::ListType(t)) if t.is_hashable() => Ok((ValueTypes::SetType(t.clone()), None)),
+        (CollectionFunc::ToSet, ValueTypes::ListType(t)) => Err(format!("{} can't be stored in a Set, elements have to be String, Number or Boolean", t)),
+        (CollectionFunc::ToSet, o) => Err(format!("expected a List, found {}", o)),
+        (CollectionFunc::Contains, ValueTypes::MapType(t, _))
+        | (CollectionFunc::Contains, ValueTypes::SetType(t))
+        | (CollectionFunc::Contains, ValueTypes::ListType(t)) => {
+            if !fits(t, &arg_types[1]) {
+                return Err(format!("expected to search for a {}, found {}", t, arg_types[1]));
+            }
+            Ok((ValueTypes::BooleanType, None))
+        }
+        (CollectionFunc::Contains, o) => Err(format!("expected a Map, Set or List, found {}", o)),
+        (CollectionFunc::Insert, ValueTypes::MapType(k, v)) if arg_types.len() == 3 => {
+            if !fits(k, &arg_types[1]) || !fits(v, &arg_types[2]) {
+                return Err(format!("expected a {} key and {} value, found {} and {}", k, v, arg_types[1], arg_types[2]));
+            }
+            if !arg_types[1].is_hashable() {
+                return Err(format!("{} can't be used as a Map key", arg_types[1]));
+            }
+            let refined = ValueTypes::MapType(Box::new(arg_types[1].clone()), Box::new(arg_types[2].clone()));
+            Ok((ValueTypes::NoneType, if arg_types[0].is_unknown_collection() { Some(refined) } else { None }))
+        }
+        (CollectionFunc::Insert, ValueTypes::SetType(t)) if arg_types.len() == 2 => {
+            if !fits(t, &arg_types[1]) {
+                return Err(format!("expected a {} value, found {}", t, arg_types[1]));
+            }
+            if !arg_types[1].is_hashable() {
+                return Err(format!("{} can't be stored in a Set", arg_types[1]));
+            }
+            let refined = ValueTypes::SetType(Box::new(arg_types[1].clone()));
+            Ok((ValueTypes::NoneType, if arg_types[0].is_unknown_collection() { Some(refined) } else { None }))
+        }
+        (CollectionFunc::Insert, o) => Err(format!("expected {}, found a {} with {} argument(s)", usage, o, arg_types.len())),
+    };
+}
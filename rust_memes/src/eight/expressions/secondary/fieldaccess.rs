@@ -0,0 +1,77 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::running::error::RuntimeError;
+use crate::eight::expressions::primary::funcdecl::parse_name;
+use crate::eight::expressions::secondary::{RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::values::{Value, ValueTypes};
+use log::{debug, trace};
+
+
+#[derive(Debug)]
+pub struct FieldAccess {
+    pub(crate) target: SecondaryExpression,
+    pub(crate) field: String,
+    pub(crate) typ: ValueTypes,
+}
+
+impl TypedSecondaryExpression for Box<FieldAccess> {
+    fn get_type(&self) -> Result<ValueTypes, String> {
+        Ok(self.typ.clone())
+    }
+
+    fn get_references(&self) -> Vec<RelationEntry> {
+        self.target.get_references()
+    }
+}
+
+impl RunnableSecondaryExpression for FieldAccess {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        let m = interpreter.step(&self.target);
+        let result = match interpreter.get_val(&m) {
+            Value::StructVal(s) => s.get(self.field.as_str()).copy(),
+            o => {
+                RuntimeError::new(format!("Cannot read field '{}' of {:?}", self.field, o)).raise();
+            }
+        };
+        trace!("Field '{}' is {:?}", self.field, result);
+        interpreter.pop_stack_vals(m);
+        interpreter.add_val_to_stack(result)
+    }
+}
+
+impl FieldAccess {
+    pub fn parse(context: &mut Context, target: SecondaryExpression, start_idx: usize) -> ParseResult<SecondaryExpression> {
+        // '.<field>' after a struct value, the '.' is the current token
+        let target_typ = match target.get_type() {
+            Ok(t) => t,
+            Err(s) => {
+                return Err(Diagnostic::error(format!("Error getting type of expression, '{}'", s), context.span_since(start_idx)));
+            }
+        };
+        context.increment(); // past '.'
+
+        let field = parse_name(context, "field name")?;
+        let typ = match field_type(context, &target_typ, field.as_str()) {
+            Ok(t) => t,
+            Err(e) => return Err(Diagnostic::error(e, context.span_since(start_idx))),
+        };
+        debug!("Found access of field '{}' in {:?}", field, target_typ);
+        return Ok(SecondaryExpression::FieldExpr(Box::new(FieldAccess { target, field, typ })));
+    }
+}
+
+pub fn field_type(context: &Context, typ: &ValueTypes, field: &str) -> Result<ValueTypes, String> {
+    return match typ {
+        ValueTypes::StructType(name) => {
+            let def = context.structs.get(name).unwrap();
+            match def.field_type(field) {
+                Some(t) => Ok(t.clone()),
+                None => Err(format!("'{}' has no field '{}', the fields are {}", name, field, def.field_names())),
+            }
+        }
+        o => Err(format!("Cannot read field '{}' of a value of type {}, only structs have fields", field, o)),
+    };
+}
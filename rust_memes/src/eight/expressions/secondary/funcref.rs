@@ -0,0 +1,57 @@
+use crate::eight::common::diagnostics::ParseResult;
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::secondary::{RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::literals::basic::functions::content::FuncContent;
+use crate::eight::literals::basic::functions::signature::FuncSignature;
+use crate::eight::literals::basic::functions::Func;
+use crate::eight::values::{Value, ValueTypes};
+use log::debug;
+
+
+#[derive(Debug)]
+pub struct FuncRef {
+    pub(crate) name: String,
+    pub(crate) sig: FuncSignature,
+}
+
+impl TypedSecondaryExpression for FuncRef {
+    fn get_type(&self) -> Result<ValueTypes, String> {
+        Ok(ValueTypes::FuncType(Box::new(self.sig.clone())))
+    }
+
+    fn get_references(&self) -> Vec<RelationEntry> {
+        vec![]
+    }
+}
+
+impl RunnableSecondaryExpression for FuncRef {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        // The value only holds the name, the body is looked up when it's called
+        let func = Func::new(self.name.clone(), self.sig.clone(), FuncContent::Declared);
+        interpreter.add_val_to_stack(Value::Function(func))
+    }
+}
+
+impl FuncRef {
+    pub fn parse(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+        // A declared func named without being called, 'map(double, xs)'
+        return Ok(match context.get()? {
+            Symbols::LiteralSymb(name) if !context.vartable.contains_key(&name) => {
+                match context.functions.get(&name) {
+                    Some(sig) => {
+                        debug!("Found reference to func '{}'", name);
+                        let sig = sig.clone();
+                        context.increment();
+                        Some(SecondaryExpression::FuncRefExpr(FuncRef { name, sig }))
+                    }
+                    None => None,
+                }
+            }
+            _ => None,
+        });
+    }
+}
@@ -0,0 +1,197 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::running::error::RuntimeError;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::secondary::{RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::literals::basic::functions::parse_function_call;
+use crate::eight::literals::basic::list::List;
+use crate::eight::values::{Value, ValueTypes};
+use log::{debug, trace};
+
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum HigherOrderKind {
+    Map,    // 'map(f, xs)', a List of f applied to every element
+    Reduce, // 'reduce(f, xs)' or 'reduce(f, xs, init)', folds the elements left to right
+}
+
+#[derive(Debug)]
+pub struct HigherOrder {
+    pub(crate) kind: HigherOrderKind,
+    pub(crate) func: SecondaryExpression,
+    pub(crate) list: SecondaryExpression,
+    pub(crate) init: Option<SecondaryExpression>,
+    pub(crate) typ: ValueTypes,
+}
+
+impl TypedSecondaryExpression for Box<HigherOrder> {
+    fn get_type(&self) -> Result<ValueTypes, String> {
+        Ok(self.typ.clone())
+    }
+
+    fn get_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        let mut args = vec![&self.func, &self.list];
+        if let Some(init) = &self.init {
+            args.push(init);
+        }
+        for arg in args {
+            for r in arg.get_references() {
+                rr.push(Box::from(r));
+            }
+        }
+        vec![RelationEntry::new(vec![], vec![], rr)]
+    }
+}
+
+impl RunnableSecondaryExpression for HigherOrder {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        let m = interpreter.step(&self.func);
+        let func = match interpreter.copy_val(m) {
+            Value::Function(f) => f,
+            o => {
+                RuntimeError::new(format!("Expected a function, found {:?}", o)).raise();
+            }
+        };
+        let m = interpreter.step(&self.list);
+        let list = match interpreter.copy_val(m) {
+            Value::ListVal(l) => l,
+            o => {
+                RuntimeError::new(format!("Expected a List, found {:?}", o)).raise();
+            }
+        };
+        trace!("Running {:?} over {} element(s)", self.kind, list.data.len());
+
+        return match self.kind {
+            HigherOrderKind::Map => {
+                let mut data = Vec::new();
+                for val in list.data {
+                    let r = interpreter.call_func_value(&func, vec![val]);
+                    data.push(interpreter.copy_val(r));
+                }
+                interpreter.add_val_to_stack(Value::ListVal(List {
+                    data,
+                    typ: func.sig.get_output_type(),
+                }))
+            }
+            HigherOrderKind::Reduce => {
+                let mut items = list.data.into_iter();
+                let mut acc = match &self.init {
+                    Some(init) => {
+                        let m = interpreter.step(init);
+                        interpreter.copy_val(m)
+                    }
+                    None => match items.next() {
+                        Some(first) => first,
+                        None => RuntimeError::new(String::from("Cannot reduce an empty List without an initial value")).raise(),
+                    },
+                };
+                for val in items {
+                    let r = interpreter.call_func_value(&func, vec![acc, val]);
+                    acc = interpreter.copy_val(r);
+                }
+                interpreter.add_val_to_stack(acc)
+            }
+        };
+    }
+}
+
+impl HigherOrder {
+    pub fn parse(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+        let start_idx = context.get_index();
+        let kind = match context.get_safe_multiple(2).as_slice() {
+            [Ok(Symbols::LiteralSymb(name)), Ok(Symbols::ParenOpen)] => match name.as_str() {
+                "map" => HigherOrderKind::Map,
+                "reduce" => HigherOrderKind::Reduce,
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        debug!("Found {:?} call", kind);
+
+        let (name, args) = match parse_function_call(context)? {
+            Some(call) => call,
+            None => return Ok(None),
+        };
+        let mut args = args.to_vec();
+        let arg_types: Result<Vec<ValueTypes>, String> = args.iter().map(|a| a.get_type()).collect();
+        let arg_types = match arg_types {
+            Ok(t) => t,
+            Err(s) => {
+                return Err(Diagnostic::error(format!("Invalid type in arg! Err: '{}'", s), context.span_since(start_idx)));
+            }
+        };
+
+        let typ = match check_types(kind, &arg_types) {
+            Ok(t) => t,
+            Err(e) => {
+                return Err(Diagnostic::error(format!("Invalid call to '{}', {}", name, e), context.span_since(start_idx)));
+            }
+        };
+
+        let init = if args.len() == 3 { args.pop() } else { None };
+        let list = args.pop().unwrap();
+        let func = args.pop().unwrap();
+        return Ok(Some(SecondaryExpression::HigherOrderExpr(Box::new(HigherOrder {
+            kind,
+            func,
+            list,
+            init,
+            typ,
+        }))));
+    }
+}
+
+fn check_types(kind: HigherOrderKind, arg_types: &[ValueTypes]) -> Result<ValueTypes, String> {
+    // Returns the type of the whole call
+    let (expected_args, usage) = match kind {
+        HigherOrderKind::Map => (2..=2, "map(func, list)"),
+        HigherOrderKind::Reduce => (2..=3, "reduce(func, list) or reduce(func, list, init)"),
+    };
+    if !expected_args.contains(&arg_types.len()) {
+        return Err(format!("expected {} but {} argument(s) were given", usage, arg_types.len()));
+    }
+
+    let sig = match &arg_types[0] {
+        ValueTypes::FuncType(sig) => sig,
+        o => return Err(format!("the first argument must be a function, found {}", o)),
+    };
+    let elem = match &arg_types[1] {
+        ValueTypes::ListType(elem) => elem.as_ref(),
+        o => return Err(format!("the second argument must be a List, found {}", o)),
+    };
+
+    // The elements of an empty list literal have no type yet, any function fits them
+    let elem_fits = |t: &ValueTypes| *elem == ValueTypes::NoneType || t.accepts(elem);
+
+    return match kind {
+        HigherOrderKind::Map => {
+            if sig.get_arglist().len() != 1 || !elem_fits(&sig.get_arglist()[0]) {
+                return Err(format!("the function must take a single {}, found {}", elem, arg_types[0]));
+            }
+            Ok(ValueTypes::ListType(Box::new(sig.get_output_type())))
+        }
+        HigherOrderKind::Reduce => {
+            let output = sig.get_output_type();
+            let acc = match arg_types.get(2) {
+                Some(init) => init,
+                None if *elem == ValueTypes::NoneType => &output,
+                None => elem,
+            };
+            let args = sig.get_arglist();
+            if args.len() != 2 || !args[0].accepts(acc) || !elem_fits(&args[1]) || !acc.accepts(&output) {
+                return Err(format!(
+                    "the function must take ({}, {}) and return {}, found {}",
+                    acc,
+                    elem,
+                    acc,
+                    arg_types[0]
+                ));
+            }
+            Ok(acc.clone())
+        }
+    };
+}
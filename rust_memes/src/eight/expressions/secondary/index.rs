@@ -0,0 +1,209 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::running::error::RuntimeError;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::secondary::{parse_secondary_expression, RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::literals::basic::list::List;
+use crate::eight::literals::basic::number::NumberVal;
+use crate::eight::literals::basic::string::{display_elem, StringVal};
+use crate::eight::literals::Literal;
+use crate::eight::values::{unknown_elem_error, Value, ValueTypes};
+use log::{debug, trace};
+
+
+#[derive(Debug)]
+pub enum IndexKind {
+    Single(SecondaryExpression),                                     // 'xs[i]'
+    Slice(Option<SecondaryExpression>, Option<SecondaryExpression>), // 'xs[a..b]', either end can be left out
+}
+
+#[derive(Debug)]
+pub struct Index {
+    pub(crate) target: SecondaryExpression,
+    pub(crate) kind: IndexKind,
+    pub(crate) typ: ValueTypes,
+}
+
+impl TypedSecondaryExpression for Box<Index> {
+    fn get_type(&self) -> Result<ValueTypes, String> {
+        Ok(self.typ.clone())
+    }
+
+    fn get_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        let mut exprs = vec![&self.target];
+        match &self.kind {
+            IndexKind::Single(idx) => exprs.push(idx),
+            IndexKind::Slice(start, end) => {
+                exprs.extend(start.iter());
+                exprs.extend(end.iter());
+            }
+        }
+        for ex in exprs {
+            for r in ex.get_references() {
+                rr.push(Box::from(r));
+            }
+        }
+        vec![RelationEntry::new(vec![], vec![], rr)]
+    }
+}
+
+impl RunnableSecondaryExpression for Index {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        let m = interpreter.step(&self.target);
+        let result = match &self.kind {
+            IndexKind::Single(idx) => {
+                let m2 = interpreter.step(idx);
+                let i = interpreter.copy_val(m2);
+                match interpreter.get_val(&m) {
+                    Value::ListVal(l) => l.data[check_index(get_number(&i), l.data.len(), "List")].copy(),
+                    Value::MapVal(map) => match map.get(&i) {
+                        Some(v) => v.copy(),
+                        None => missing_key(&i),
+                    },
+                    Value::Literal(Literal::String(s)) => {
+                        let idx = check_index(get_number(&i), s.value.chars().count(), "String");
+                        let ch = s.value.chars().nth(idx).unwrap();
+                        Value::Literal(Literal::String(StringVal { value: ch.to_string() }))
+                    }
+                    o => {
+                        RuntimeError::new(format!("Cannot index into {:?}", o)).raise();
+                    }
+                }
+            }
+            IndexKind::Slice(start, end) => {
+                let start = start.as_ref().map(|ex| run_number(interpreter, ex));
+                let end = end.as_ref().map(|ex| run_number(interpreter, ex));
+                match interpreter.get_val(&m) {
+                    Value::ListVal(l) => {
+                        let (a, b) = check_slice(start, end, l.data.len(), "List");
+                        Value::ListVal(List {
+                            data: l.data[a..b].iter().map(|v| v.copy()).collect(),
+                            typ: l.typ.clone(),
+                        })
+                    }
+                    Value::Literal(Literal::String(s)) => {
+                        let (a, b) = check_slice(start, end, s.value.chars().count(), "String");
+                        let value = s.value.chars().skip(a).take(b - a).collect();
+                        Value::Literal(Literal::String(StringVal { value }))
+                    }
+                    o => {
+                        RuntimeError::new(format!("Cannot slice {:?}", o)).raise();
+                    }
+                }
+            }
+        };
+        trace!("Index result {:?}", result);
+        interpreter.pop_stack_vals(m);
+        interpreter.add_val_to_stack(result)
+    }
+}
+
+impl Index {
+    pub fn parse(context: &mut Context, target: SecondaryExpression, start_idx: usize) -> ParseResult<SecondaryExpression> {
+        // '[<index>]' or '[<start>..<end>]' after a value, the '[' is the current token
+        let open_span = context.get_span();
+        let target_typ = match target.get_type() {
+            Ok(t) => t,
+            Err(s) => {
+                return Err(Diagnostic::error(format!("Error getting type of expression, '{}'", s), context.span_since(start_idx)));
+            }
+        };
+        if target_typ.is_unknown_collection() {
+            return Err(unknown_elem_error(&target_typ, context.span_since(start_idx)));
+        }
+        let (key_typ, elem_typ) = match &target_typ {
+            ValueTypes::ListType(inner) => (ValueTypes::NumberType, inner.as_ref().clone()),
+            ValueTypes::StringType => (ValueTypes::NumberType, ValueTypes::StringType),
+            ValueTypes::MapType(k, v) => (k.as_ref().clone(), v.as_ref().clone()),
+            o => {
+                return Err(Diagnostic::error(format!("Cannot index into a value of type {}", o), context.span_since(start_idx))
+                    .with_note(String::from("only Lists, Strings and Maps can be indexed")));
+            }
+        };
+        let is_map = matches!(target_typ, ValueTypes::MapType(_, _));
+        context.increment(); // past '['
+        debug!("Found index into {:?}", target_typ);
+
+        let first = if context.get()? == Symbols::Range {
+            None
+        } else {
+            Some(parse_index_expr(context, &key_typ)?)
+        };
+        let (kind, typ) = if context.get()? == Symbols::Range {
+            if is_map {
+                return Err(context.error(String::from("Maps can't be sliced"))
+                    .with_note(String::from("look up a single key with 'm[key]'")));
+            }
+            context.increment(); // past '..'
+            let end = if context.get()? == Symbols::BracketClose {
+                None
+            } else {
+                Some(parse_index_expr(context, &key_typ)?)
+            };
+            (IndexKind::Slice(first, end), target_typ)
+        } else {
+            match first {
+                Some(idx) => (IndexKind::Single(idx), elem_typ),
+                None => return Err(context.error(String::from("Expected an index"))),
+            }
+        };
+
+        match context.get()? {
+            Symbols::BracketClose => context.increment(),
+            o => {
+                return Err(context.error(format!("Expected ']' to close the index, found '{}'", o.to_str()))
+                    .with_note(format!("'[' opened on line {} column {}", open_span.line, open_span.col)));
+            }
+        }
+        return Ok(SecondaryExpression::IndexExpr(Box::new(Index { target, kind, typ })));
+    }
+}
+
+fn parse_index_expr(context: &mut Context, key_typ: &ValueTypes) -> ParseResult<SecondaryExpression> {
+    // Lists and Strings are indexed by Number, Maps by their key type
+    let idx = context.get_index();
+    let expr = parse_secondary_expression(context)?;
+    return match expr.get_type() {
+        Ok(t) if *key_typ == ValueTypes::NoneType || key_typ.accepts(&t) => Ok(expr),
+        Ok(t) if *key_typ == ValueTypes::NumberType => {
+            Err(Diagnostic::error(format!("Indices must be Number, found {}", t), context.span_since(idx)))
+        }
+        Ok(t) => Err(Diagnostic::error(format!("Mismatched types, the Map has {} keys but the index is {}", key_typ, t), context.span_since(idx))),
+        Err(s) => Err(Diagnostic::error(format!("Error getting type of index, '{}'", s), context.span_since(idx))),
+    };
+}
+
+fn run_number(interpreter: &mut EightInterpreter, expr: &SecondaryExpression) -> i64 {
+    let m = interpreter.step(expr);
+    let n = interpreter.get_val(&m).get_val::<NumberVal>().value;
+    interpreter.pop_stack_vals(m);
+    return n;
+}
+
+fn get_number(val: &Value) -> i64 {
+    return val.get_val::<NumberVal>().value;
+}
+
+pub fn missing_key(key: &Value) -> ! {
+    RuntimeError::new(format!("Key {} is not in the Map", display_elem(key))).raise();
+}
+
+pub fn check_index(idx: i64, len: usize, what: &str) -> usize {
+    if idx < 0 || idx as usize >= len {
+        RuntimeError::new(format!("Index {} is out of range for a {} of length {}", idx, what, len)).raise();
+    }
+    return idx as usize;
+}
+
+fn check_slice(start: Option<i64>, end: Option<i64>, len: usize, what: &str) -> (usize, usize) {
+    let a = start.unwrap_or(0);
+    let b = end.unwrap_or(len as i64);
+    if a < 0 || b < a || b as usize > len {
+        RuntimeError::new(format!("Slice {}..{} is out of range for a {} of length {}", a, b, what, len)).raise();
+    }
+    return (a as usize, b as usize);
+}
@@ -0,0 +1,68 @@
+use crate::eight::common::diagnostics::ParseResult;
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::primary::matchexpr::{parse_match, select_arm, ArmBody, MatchArm, MatchPattern};
+use crate::eight::expressions::secondary::{RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::values::ValueTypes;
+use log::{debug, trace};
+
+
+#[derive(Debug)]
+pub struct MatchValue {
+    pub(crate) target: SecondaryExpression,
+    pub(crate) arms: Vec<MatchArm>, // Every arm body is an ArmBody::Value
+    pub(crate) typ: ValueTypes,
+}
+
+impl TypedSecondaryExpression for Box<MatchValue> {
+    fn get_type(&self) -> Result<ValueTypes, String> {
+        Ok(self.typ.clone())
+    }
+
+    fn get_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        for r in self.target.get_references() {
+            rr.push(Box::from(r));
+        }
+        let mut bound = Vec::new();
+        for arm in &self.arms {
+            if let MatchPattern::Variant(_, bindings) = &arm.pattern {
+                bound.extend(bindings.iter().flatten().cloned());
+            }
+            rr.append(&mut arm.body.get_references());
+        }
+        vec![RelationEntry::new(bound, vec![], rr)]
+    }
+}
+
+impl RunnableSecondaryExpression for MatchValue {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        let m = interpreter.step(&self.target);
+        let val = interpreter.copy_val(m);
+        let result = match select_arm(&self.arms, val) {
+            (ArmBody::Value(ex), vars) => interpreter.step_with_vars(ex, vars),
+            (o, _) => {
+                panic!("Shouldn't be here! Match used as a value has an arm without one, found {:?}", o);
+            }
+        };
+        trace!("Match result {:?}", result);
+        interpreter.add_val_to_stack(result)
+    }
+}
+
+impl MatchValue {
+    pub fn parse(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+        // 'match <enum value> { <Enum>::<Variant>(<name>, ...) => <expr>, _ => <expr> }' where a value is expected
+        match context.get()? {
+            Symbols::Match => (),
+            _ => return Ok(None),
+        }
+        debug!("Found match used as a value");
+
+        let (target, arms, typ) = parse_match(context, true)?;
+        return Ok(Some(SecondaryExpression::MatchValueExpr(Box::new(MatchValue { target, arms, typ }))));
+    }
+}
@@ -0,0 +1,105 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::secondary::{RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::literals::basic::functions::parse_function_call;
+use crate::eight::values::methods::{MethodFn, METHODS};
+use crate::eight::values::ValueTypes;
+use log::{debug, trace};
+
+
+#[derive(Debug)]
+pub struct MethodCall {
+    pub(crate) target: SecondaryExpression,
+    pub(crate) name: String,
+    pub(crate) args: Vec<SecondaryExpression>,
+    pub(crate) builtin: MethodFn,
+    pub(crate) typ: ValueTypes,
+}
+
+impl TypedSecondaryExpression for Box<MethodCall> {
+    fn get_type(&self) -> Result<ValueTypes, String> {
+        Ok(self.typ.clone())
+    }
+
+    fn get_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        for ex in std::iter::once(&self.target).chain(self.args.iter()) {
+            for r in ex.get_references() {
+                rr.push(Box::from(r));
+            }
+        }
+        vec![RelationEntry::new(vec![], vec![], rr)]
+    }
+}
+
+impl RunnableSecondaryExpression for MethodCall {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        // The receiver is read where it is, only the args are copied
+        let m = interpreter.step(&self.target);
+        let mut args = Vec::new();
+        for arg in &self.args {
+            let m2 = interpreter.step(arg);
+            args.push(interpreter.copy_val(m2));
+        }
+        let result = (self.builtin)(interpreter.get_val(&m), args);
+        trace!("Method '{}' result {:?}", self.name, result);
+        interpreter.pop_stack_vals(m);
+        interpreter.add_val_to_stack(result)
+    }
+}
+
+impl MethodCall {
+    pub fn parse(context: &mut Context, target: SecondaryExpression, start_idx: usize) -> ParseResult<SecondaryExpression> {
+        // '.<method>(<args>)' after a value, the '.' is the current token
+        let target_typ = match target.get_type() {
+            Ok(t) => t,
+            Err(s) => {
+                return Err(Diagnostic::error(format!("Error getting type of expression, '{}'", s), context.span_since(start_idx)));
+            }
+        };
+        context.increment(); // past '.'
+
+        let name = match context.get()? {
+            Symbols::LiteralSymb(name) => name,
+            o => {
+                return Err(context.error(format!("Expected a method name after '.', found '{}'", o.to_str())));
+            }
+        };
+        let method = match METHODS.lookup(&target_typ, name.as_str()) {
+            Some(m) => m,
+            None => {
+                let names = METHODS.method_names(&target_typ);
+                let note = if names.is_empty() {
+                    format!("{} has no methods", target_typ)
+                } else {
+                    format!("the methods of {} are {}", target_typ, names.join(", "))
+                };
+                return Err(context.error(format!("No method '{}' on a value of type {}", name, target_typ))
+                    .with_note(note));
+            }
+        };
+        debug!("Found call of method '{}' on {:?}", name, target_typ);
+
+        let (name, args) = match parse_function_call(context)? {
+            Some(call) => call,
+            None => return Err(context.error(format!("Expected '(' after method '{}'", name))),
+        };
+        let sig = (method.sig)(&target_typ);
+        if let Err(e) = sig.match_signature(&args) {
+            return Err(Diagnostic::error(e, context.span_since(start_idx))
+                .with_note(format!("calling method '{}' of {}", name, target_typ)));
+        }
+
+        return Ok(SecondaryExpression::MethodExpr(Box::new(MethodCall {
+            target,
+            name,
+            args: args.arglist,
+            builtin: method.builtin,
+            typ: sig.get_output_type(),
+        })));
+    }
+}
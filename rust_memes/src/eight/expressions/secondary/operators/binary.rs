@@ -1,20 +1,25 @@
 use crate::eight::common::parsing::ast::RelationEntry;
-use crate::eight::common::parsing::context::Context;
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
 use crate::eight::common::parsing::ParsableOperator;
-use crate::eight::expressions::secondary::operators::{compare_lhs_rhs_type, split_lhs_rhs};
-use crate::eight::expressions::secondary::{RunnableSecondaryExpression, SecondaryExpression, TransitionExpression, TypedSecondaryExpression};
+use crate::eight::expressions::secondary::operators::Associativity;
+use crate::eight::expressions::secondary::{RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::common::running::error::RuntimeError;
 use crate::eight::common::running::interpreter::EightInterpreter;
 use crate::eight::common::running::memory::memref::MemRef;
 use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::common::tokenizing::token::Span;
 use crate::eight::literals::basic::bool::BoolVal;
+use crate::eight::literals::basic::float::FloatVal;
 use crate::eight::literals::basic::number::NumberVal;
 use crate::eight::literals::basic::string::StringVal;
 use crate::eight::literals::Literal;
 use crate::eight::values::{Value, ValueTypes};
 use crate::eight::values::BasicValue;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BinaryOperators {
     Add,
     Exponent,
@@ -23,70 +28,110 @@ pub enum BinaryOperators {
     Modulo,
     Sub,
     EqualityCheck,
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    And,
+    Or,
 }
 
+impl BinaryOperators {
+    pub fn precedence(&self) -> u8 {
+        // Higher binds tighter
+        match self {
+            BinaryOperators::Or => 1,
+            BinaryOperators::And => 2,
+            BinaryOperators::EqualityCheck | BinaryOperators::NotEqual => 3,
+            BinaryOperators::LessThan | BinaryOperators::LessThanOrEqual
+            | BinaryOperators::GreaterThan | BinaryOperators::GreaterThanOrEqual => 4,
+            BinaryOperators::Add | BinaryOperators::Sub => 5,
+            BinaryOperators::Multiply | BinaryOperators::Divide | BinaryOperators::Modulo => 6,
+            BinaryOperators::Exponent => 7,
+        }
+    }
+
+    pub fn is_logical(&self) -> bool {
+        matches!(self, BinaryOperators::And | BinaryOperators::Or)
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        matches!(self, BinaryOperators::LessThan | BinaryOperators::LessThanOrEqual
+            | BinaryOperators::GreaterThan | BinaryOperators::GreaterThanOrEqual)
+    }
+
+    pub fn is_equality(&self) -> bool {
+        matches!(self, BinaryOperators::EqualityCheck | BinaryOperators::NotEqual)
+    }
+
+    pub fn associativity(&self) -> Associativity {
+        match self {
+            BinaryOperators::Exponent => Associativity::Right,
+            _ => Associativity::Left,
+        }
+    }
+}
+
+// Operators nested deeper than this are a parse error, running or dropping the tree recurses once per level
+pub const MAX_OPERATOR_DEPTH: usize = 2000;
+
 #[derive(Debug)]
 pub struct BinaryOperator {
     pub(crate) rhs: SecondaryExpression,
     pub(crate) lhs: SecondaryExpression,
     pub(crate) op: BinaryOperators,
+    pub(crate) typ: ValueTypes, // Checked once when parsed, so typing a long chain doesn't walk it again
+    pub(crate) depth: usize, // Operators on the longest path down through the operands, including this one
 }
 
 impl BinaryOperator {
-    pub fn new(lhs: SecondaryExpression, rhs: SecondaryExpression, op: BinaryOperators) -> Self {
+    pub fn new(lhs: SecondaryExpression, rhs: SecondaryExpression, op: BinaryOperators, typ: ValueTypes) -> Self {
+        let depth = 1 + nesting(&lhs).max(nesting(&rhs));
         BinaryOperator {
             rhs,
             lhs,
-            op
+            op,
+            typ,
+            depth,
         }
     }
 }
 
+pub fn nesting(expr: &SecondaryExpression) -> usize {
+    // How many operators deep the expression is, a flat chain like 1 + 2 + 3 is as deep as it is long
+    return match expr {
+        SecondaryExpression::BinaryOperation(b) => b.depth,
+        SecondaryExpression::ParenExpr(p) => nesting(&p.expr),
+        _ => 0,
+    };
+}
+
 impl RunnableSecondaryExpression for Box<BinaryOperator> {
-    fn run_secondary_expr(mut self, interpreter: &mut EightInterpreter) -> MemRef {
-        let m1 = interpreter.step(self.lhs);
-        let m2 = interpreter.step(self.rhs);
-
-        let mut l1 = interpreter.get_val(&m1);
-        let mut r1 = interpreter.get_val(&m2);
-
-        let val = match self.op {
-            BinaryOperators::EqualityCheck => {
-                Value::Literal(Literal::Boolean(BoolVal{value: l1.eq(r1)}))
-            },
-            o=> {
-                let mut l = l1.get_val::<NumberVal>();
-                let mut r = r1.get_val::<NumberVal>();
-
-
-                match o {
-                    BinaryOperators::Add => {
-                        Value::Literal(Literal::Number(NumberVal { value: l.value + r.value }))
-                    }
-                    BinaryOperators::Exponent => {
-                        if r.value < 0 {
-                            panic!("Runtime Exception: Cannot raise {} to a value less than zero! val: {}", l.value, r.value);
-                        }
-                        Value::Literal(Literal::Number(NumberVal { value: l.value.pow(r.value as u32) }))
-                    }
-                    BinaryOperators::Multiply => {
-                        Value::Literal(Literal::Number(NumberVal { value: l.value * r.value }))
-                    }
-                    BinaryOperators::Divide => {
-                        Value::Literal(Literal::Number(NumberVal { value: l.value / r.value }))
-                    }
-                    BinaryOperators::Modulo => {
-                        Value::Literal(Literal::Number(NumberVal { value: l.value % r.value }))
-                    }
-                    BinaryOperators::Sub => {
-                        Value::Literal(Literal::Number(NumberVal { value: l.value - r.value }))
-                    },
-                    _ => {
-                        panic!("Shouldn't be here, unknown operator found?");
-                    }
-                }
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        let BinaryOperator { lhs, rhs, op, .. } = self.as_ref();
+        let op = *op;
+        let m1 = interpreter.step(lhs);
+
+        if op.is_logical() {
+            // Short-circuit, the rhs is only stepped when the lhs doesn't decide the result
+            let lhs_val = interpreter.get_val(&m1).get_val::<BoolVal>().value;
+            interpreter.pop_stack_vals(m1);
+            let decided = match op {
+                BinaryOperators::And => !lhs_val,
+                _ => lhs_val,
+            };
+            if decided {
+                return interpreter.add_val_to_stack(Value::Literal(Literal::Boolean(BoolVal { value: lhs_val })));
             }
-        };
+            let m2 = interpreter.step(rhs);
+            let rhs_val = interpreter.get_val(&m2).get_val::<BoolVal>().value;
+            interpreter.pop_stack_vals(m2);
+            return interpreter.add_val_to_stack(Value::Literal(Literal::Boolean(BoolVal { value: rhs_val })));
+        }
+
+        let m2 = interpreter.step(rhs);
+        let val = run_binop(op, interpreter.get_val(&m1), interpreter.get_val(&m2));
 
         interpreter.pop_stack_vals(m1);
         interpreter.pop_stack_vals(m2);
@@ -94,9 +139,107 @@ impl RunnableSecondaryExpression for Box<BinaryOperator> {
     }
 }
 
+pub fn run_binop(op: BinaryOperators, l1: &Value, r1: &Value) -> Value {
+    // Every operator except the short-circuiting '&&' and '||', also used by compound assignments like 'xs[i] += 1'
+    return match op {
+        BinaryOperators::EqualityCheck => {
+            Value::Literal(Literal::Boolean(BoolVal{value: l1.eq(r1)}))
+        },
+        BinaryOperators::NotEqual => {
+            Value::Literal(Literal::Boolean(BoolVal{value: !l1.eq(r1)}))
+        },
+        o if o.is_comparison() => {
+            run_comparison(o, l1, r1)
+        },
+        o => {
+            match (l1, r1) {
+                (Value::Literal(Literal::Float(l)), Value::Literal(Literal::Float(r))) => {
+                    run_float_op(o, l.value, r.value)
+                },
+                _ => {
+                    let l = l1.get_val::<NumberVal>();
+                    let r = r1.get_val::<NumberVal>();
+                    run_number_op(o, l.value, r.value)
+                }
+            }
+        }
+    };
+}
+
+fn run_number_op(op: BinaryOperators, l: i64, r: i64) -> Value {
+    // Number arithmetic is checked, overflowing an i64 is a runtime error rather than a wrap
+    let result = match op {
+        BinaryOperators::Add => l.checked_add(r),
+        BinaryOperators::Exponent => {
+            if r < 0 {
+                RuntimeError::new(format!("Cannot raise {} to a value less than zero! val: {}", l, r)).raise();
+            }
+            u32::try_from(r).ok().and_then(|exp| l.checked_pow(exp))
+        }
+        BinaryOperators::Multiply => l.checked_mul(r),
+        BinaryOperators::Divide | BinaryOperators::Modulo if r == 0 => {
+            RuntimeError::new(format!("Cannot divide {} by zero!", l)).raise();
+        }
+        BinaryOperators::Divide => l.checked_div(r),
+        BinaryOperators::Modulo => l.checked_rem(r),
+        BinaryOperators::Sub => l.checked_sub(r),
+        _ => {
+            panic!("Shouldn't be here, unknown operator found?");
+        }
+    };
+
+    match result {
+        Some(value) => Value::Literal(Literal::Number(NumberVal { value })),
+        None => {
+            RuntimeError::new(format!("Operation '{}' on {} and {} overflowed, Numbers are 64-bit signed integers", binop_to_sym(&op).to_str(), l, r)).raise();
+        }
+    }
+}
+
+fn run_comparison(op: BinaryOperators, l: &Value, r: &Value) -> Value {
+    // Numbers, Floats and Strings are orderable, a NaN float compares false with everything
+    let ordering = match (l, r) {
+        (Value::Literal(Literal::Number(l)), Value::Literal(Literal::Number(r))) => l.value.partial_cmp(&r.value),
+        (Value::Literal(Literal::Float(l)), Value::Literal(Literal::Float(r))) => l.value.partial_cmp(&r.value),
+        (Value::Literal(Literal::String(l)), Value::Literal(Literal::String(r))) => l.value.partial_cmp(&r.value),
+        (l, r) => {
+            RuntimeError::new(format!("Cannot compare {:?} and {:?}", l, r)).raise();
+        }
+    };
+
+    let value = match ordering {
+        Some(ord) => match op {
+            BinaryOperators::LessThan => ord == Ordering::Less,
+            BinaryOperators::LessThanOrEqual => ord != Ordering::Greater,
+            BinaryOperators::GreaterThan => ord == Ordering::Greater,
+            BinaryOperators::GreaterThanOrEqual => ord != Ordering::Less,
+            _ => {
+                panic!("Shouldn't be here, unknown comparison operator found?");
+            }
+        },
+        None => false,
+    };
+    Value::Literal(Literal::Boolean(BoolVal { value }))
+}
+
+fn run_float_op(op: BinaryOperators, l: f64, r: f64) -> Value {
+    let value = match op {
+        BinaryOperators::Add => l + r,
+        BinaryOperators::Exponent => l.powf(r),
+        BinaryOperators::Multiply => l * r,
+        BinaryOperators::Divide => l / r,
+        BinaryOperators::Modulo => l % r,
+        BinaryOperators::Sub => l - r,
+        _ => {
+            panic!("Shouldn't be here, unknown operator found?");
+        }
+    };
+    Value::Literal(Literal::Float(FloatVal { value }))
+}
+
 impl TypedSecondaryExpression for Box<BinaryOperator> {
     fn get_type(&self) -> Result<ValueTypes, String> {
-        compare_lhs_rhs_type(self.lhs.get_type(), self.rhs.get_type(), &self.op)
+        Ok(self.typ.clone())
     }
 
     fn get_references(&self) -> Vec<RelationEntry> {
@@ -105,77 +248,80 @@ impl TypedSecondaryExpression for Box<BinaryOperator> {
 }
 
 impl ParsableOperator for BinaryOperator {
-    fn parse(context: &mut Context, expr_list: &mut Vec<TransitionExpression>, idx: usize, op: BinaryOperators) {
-        let (lhs, rhs, iidx) = split_lhs_rhs(context, expr_list, idx);
-
-        let lhst_res: Result<ValueTypes, String> = lhs.get_type();
-        let rhst_res: Result<ValueTypes, String> = rhs.get_type();
-
-        let lhs_typ: ValueTypes;
-        let rhs_typ: ValueTypes;
-
-        match lhst_res {
-            Ok(t) => lhs_typ = t,
+    fn parse(lhs: SecondaryExpression, rhs: SecondaryExpression, op: BinaryOperators, span: Span) -> ParseResult<SecondaryExpression> {
+        let lhs_typ = match lhs.get_type() {
+            Ok(t) => t,
             Err(e) => {
-                panic!("{}", context.get_panic_smessage(format!("Error in typecheck parsing '{}'", e)));
+                return Err(Diagnostic::error(format!("Error in typecheck parsing '{}'", e), span));
             }
-        }
+        };
 
-        match rhst_res {
-            Ok(t) => rhs_typ = t,
+        let rhs_typ = match rhs.get_type() {
+            Ok(t) => t,
             Err(e) => {
-                panic!("{}", context.get_panic_smessage(format!("Error in typecheck parsing '{}'", e)));
+                return Err(Diagnostic::error(format!("Error in typecheck parsing '{}'", e), span));
             }
-        }
+        };
 
-        match &op {
-            BinaryOperators::EqualityCheck => {
-                if rhs_typ != lhs_typ {
-                    context.back_to_linestart(); // Go back to line start for snippet
-                    panic!("{}", context.get_panic_smessage(format!("Operator '==' only supports Number typed operands! Found {:?} and {:?}", lhs_typ, rhs_typ)));
-                }
-            },
-            o => {
-                match lhs_typ {
-                    ValueTypes::NumberType => {
-                        match rhs_typ {
-                            ValueTypes::NumberType => {},
-                            oo => {
-                                context.back_to_linestart(); // Go back to line start for snippet
-                                panic!("{}", context.get_panic_smessage(format!("Operator '{:?}' only supports Number typed operands! Found {:?} and {:?}", op, o, oo)));
-                            }
-                        }
-                    },
-                    oo => {
-                        context.back_to_linestart(); // Go back to line start for snippet
-                        panic!("{}", context.get_panic_smessage(format!("Operator {:?} only supports Number typed operands! Found {:?} and {:?}", op, o, oo)));
-                    }
-                }
+        check_operand_types(op, &lhs_typ, &rhs_typ, span)?;
+        let typ = if op.is_equality() || op.is_comparison() || op.is_logical() {
+            ValueTypes::BooleanType // Operands match, the result is a bool
+        } else {
+            lhs_typ
+        };
+        return Ok(SecondaryExpression::BinaryOperation(Box::from(BinaryOperator::new(lhs, rhs, op, typ))));
+    }
+}
 
+pub fn check_operand_types(op: BinaryOperators, lhs_typ: &ValueTypes, rhs_typ: &ValueTypes, span: Span) -> ParseResult<()> {
+    let op_str = binop_to_sym(&op).to_str();
+    match &op {
+        o if o.is_equality() => {
+            if !lhs_typ.accepts(rhs_typ) && !rhs_typ.accepts(lhs_typ) {
+                return Err(Diagnostic::error(format!("Operator '{}' needs operands of the same type! Found {} and {}", op_str, lhs_typ, rhs_typ), span));
+            }
+            if !lhs_typ.is_equatable() || !rhs_typ.is_equatable() {
+                return Err(Diagnostic::error(format!("Operator '{}' can't compare functions or namespace values! Found {} and {}", op_str, lhs_typ, rhs_typ), span));
+            }
+        },
+        o if o.is_logical() => {
+            if *lhs_typ != ValueTypes::BooleanType || *rhs_typ != ValueTypes::BooleanType {
+                return Err(Diagnostic::error(format!("Operator '{}' needs two Boolean typed operands! Found {} and {}", op_str, lhs_typ, rhs_typ), span));
+            }
+        },
+        o if o.is_comparison() => {
+            let is_orderable = *lhs_typ == ValueTypes::NumberType || *lhs_typ == ValueTypes::FloatType || *lhs_typ == ValueTypes::StringType;
+            if !is_orderable || rhs_typ != lhs_typ {
+                return Err(Diagnostic::error(format!("Operator '{}' needs two Number, Float or String typed operands of the same type! Found {} and {}", op_str, lhs_typ, rhs_typ), span));
+            }
+        },
+        _ => {
+            let is_numeric = *lhs_typ == ValueTypes::NumberType || *lhs_typ == ValueTypes::FloatType;
+            if !is_numeric || rhs_typ != lhs_typ {
+                return Err(Diagnostic::error(format!("Operator '{}' needs two Number or two Float typed operands! Found {} and {}", op_str, lhs_typ, rhs_typ), span));
             }
         }
-
-        expr_list.insert(
-            iidx,
-            TransitionExpression::TransExpr(
-                SecondaryExpression::BinaryOperation(Box::from(BinaryOperator::new(lhs, rhs, op)))
-            ),
-        );
     }
+    return Ok(());
 }
 
-pub fn sym_to_binop(sym: &Symbols, context: &Context) -> BinaryOperators {
+pub fn sym_to_binop(sym: &Symbols) -> Option<BinaryOperators> {
     return match sym {
-        Symbols::Exponent => { BinaryOperators::Exponent }
-        Symbols::Multiply => { BinaryOperators::Multiply }
-        Symbols::Divide => { BinaryOperators::Divide }
-        Symbols::Modulus => { BinaryOperators::Modulo }
-        Symbols::Add => { BinaryOperators::Add }
-        Symbols::Sub => { BinaryOperators::Sub }
-        Symbols::EqualityCheck => { BinaryOperators::EqualityCheck }
-        other => {
-            panic!("{}", context.get_panic_smessage(format!("Invalid symbol '{:?}' expected operator", other)));
-        }
+        Symbols::Exponent => Some(BinaryOperators::Exponent),
+        Symbols::Multiply => Some(BinaryOperators::Multiply),
+        Symbols::Divide => Some(BinaryOperators::Divide),
+        Symbols::Modulus => Some(BinaryOperators::Modulo),
+        Symbols::Add => Some(BinaryOperators::Add),
+        Symbols::Sub => Some(BinaryOperators::Sub),
+        Symbols::EqualityCheck => Some(BinaryOperators::EqualityCheck),
+        Symbols::NotEqual => Some(BinaryOperators::NotEqual),
+        Symbols::LessThan => Some(BinaryOperators::LessThan),
+        Symbols::LessThanOrEqual => Some(BinaryOperators::LessThanOrEqual),
+        Symbols::GreaterThan => Some(BinaryOperators::GreaterThan),
+        Symbols::GreaterThanOrEqual => Some(BinaryOperators::GreaterThanOrEqual),
+        Symbols::And => Some(BinaryOperators::And),
+        Symbols::Or => Some(BinaryOperators::Or),
+        _ => None,
     };
 }
 
@@ -188,5 +334,12 @@ pub fn binop_to_sym(binop: &BinaryOperators) -> Symbols {
         BinaryOperators::Modulo => { Symbols::Modulus }
         BinaryOperators::Sub => { Symbols::Sub }
         BinaryOperators::EqualityCheck => { Symbols::EqualityCheck }
+        BinaryOperators::NotEqual => { Symbols::NotEqual }
+        BinaryOperators::LessThan => { Symbols::LessThan }
+        BinaryOperators::LessThanOrEqual => { Symbols::LessThanOrEqual }
+        BinaryOperators::GreaterThan => { Symbols::GreaterThan }
+        BinaryOperators::GreaterThanOrEqual => { Symbols::GreaterThanOrEqual }
+        BinaryOperators::And => { Symbols::And }
+        BinaryOperators::Or => { Symbols::Or }
     };
 }
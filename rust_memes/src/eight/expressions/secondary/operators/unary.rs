@@ -0,0 +1,115 @@
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::running::error::RuntimeError;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::secondary::operators::binary::BinaryOperators;
+use crate::eight::expressions::secondary::{parse_binary_expression, RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::literals::basic::bool::BoolVal;
+use crate::eight::literals::basic::float::FloatVal;
+use crate::eight::literals::basic::number::NumberVal;
+use crate::eight::literals::Literal;
+use crate::eight::values::{Value, ValueTypes};
+
+
+#[derive(Debug)]
+pub enum UnaryOperators {
+    Not,    // !expr
+    Negate, // -expr
+}
+
+impl UnaryOperators {
+    pub fn operand_precedence() -> u8 {
+        // Prefix operators bind tighter than every binary operator except '**', so -2 ** 2 == -(2 ** 2)
+        BinaryOperators::Exponent.precedence()
+    }
+}
+
+#[derive(Debug)]
+pub struct UnaryOperator {
+    pub(crate) operand: SecondaryExpression,
+    pub(crate) op: UnaryOperators,
+}
+
+impl UnaryOperator {
+    pub fn new(operand: SecondaryExpression, op: UnaryOperators) -> Self {
+        UnaryOperator {
+            operand,
+            op
+        }
+    }
+
+    pub fn parse(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+        let start_idx = context.get_index();
+        let op = match sym_to_unop(&context.get()?) {
+            Some(op) => op,
+            None => return Ok(None),
+        };
+        context.increment(); // past the operator
+
+        let operand = parse_binary_expression(context, UnaryOperators::operand_precedence(), None)?;
+        let unop = Box::from(UnaryOperator::new(operand, op));
+
+        // Type check now so misuse is a compile time error
+        match unop.get_type() {
+            Ok(_) => Ok(Some(SecondaryExpression::UnaryOperation(unop))),
+            Err(e) => Err(Diagnostic::error(e, context.span_since(start_idx))),
+        }
+    }
+}
+
+impl RunnableSecondaryExpression for Box<UnaryOperator> {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        let m = interpreter.step(&self.operand);
+        let v = interpreter.get_val(&m);
+
+        let val = match (&self.op, v) {
+            (UnaryOperators::Not, Value::Literal(Literal::Boolean(b))) => {
+                Value::Literal(Literal::Boolean(BoolVal { value: !b.value }))
+            },
+            (UnaryOperators::Negate, Value::Literal(Literal::Float(f))) => {
+                Value::Literal(Literal::Float(FloatVal { value: -f.value }))
+            },
+            (UnaryOperators::Negate, Value::Literal(Literal::Number(n))) => {
+                match n.value.checked_neg() {
+                    Some(value) => Value::Literal(Literal::Number(NumberVal { value })),
+                    None => {
+                        RuntimeError::new(format!("Negating {} overflowed, Numbers are 64-bit signed integers", n.value)).raise();
+                    }
+                }
+            },
+            (op, o) => {
+                RuntimeError::new(format!("Cannot apply {:?} to {:?}", op, o)).raise();
+            }
+        };
+
+        interpreter.pop_stack_vals(m);
+        return interpreter.add_val_to_stack(val);
+    }
+}
+
+impl TypedSecondaryExpression for Box<UnaryOperator> {
+    fn get_type(&self) -> Result<ValueTypes, String> {
+        let typ = self.operand.get_type()?;
+        match (&self.op, &typ) {
+            (UnaryOperators::Not, ValueTypes::BooleanType) => Ok(typ),
+            (UnaryOperators::Negate, ValueTypes::NumberType) | (UnaryOperators::Negate, ValueTypes::FloatType) => Ok(typ),
+            (UnaryOperators::Not, o) => Err(format!("Operator '!' needs a Boolean typed operand! Found {}", o)),
+            (UnaryOperators::Negate, o) => Err(format!("Operator '-' needs a Number or Float typed operand! Found {}", o)),
+        }
+    }
+
+    fn get_references(&self) -> Vec<RelationEntry> {
+        self.operand.get_references()
+    }
+}
+
+pub fn sym_to_unop(sym: &Symbols) -> Option<UnaryOperators> {
+    return match sym {
+        Symbols::Not => Some(UnaryOperators::Not),
+        Symbols::Sub => Some(UnaryOperators::Negate),
+        _ => None,
+    };
+}
@@ -1,4 +1,5 @@
 use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::running::interpreter::EightInterpreter;
 use crate::eight::common::running::memory::memref::MemRef;
@@ -9,27 +10,28 @@ use crate::eight::values::ValueTypes;
 
 #[derive(Debug)]
 pub struct Parentheses {
-    expr: SecondaryExpression
+    pub(crate) expr: SecondaryExpression
 }
 
 impl Parentheses {
-    pub fn parse(context: &mut Context) -> Option<SecondaryExpression> {
-        match context.get() {
+    pub fn parse(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+        match context.get()? {
             Symbols::ParenOpen => {
-                let ln = context.get_line_no();
+                let open_span = context.get_span();
                 context.increment();
-                let s = parse_secondary_expression(context);
-                match context.get() {
+                let s = parse_secondary_expression(context)?;
+                match context.get()? {
                     Symbols::ParenClose => {
                         context.increment();
-                        return Some(SecondaryExpression::ParenExpr(Box::from(Parentheses {
+                        return Ok(Some(SecondaryExpression::ParenExpr(Box::from(Parentheses {
                             expr: s
-                        })));
+                        }))));
                     },
-                    _ => panic!("{}", context.get_panic_smessage(format!("Expected closing paren from '(' started on line {}", ln)))
+                    _ => Err(context.error(String::from("Expected closing paren ')'"))
+                        .with_note(format!("'(' opened on line {} column {}", open_span.line, open_span.col)))
                 }
             },
-            _ => None
+            _ => Ok(None)
         }
     }
 }
@@ -45,7 +47,7 @@ impl TypedSecondaryExpression for Box<Parentheses> {
 }
 
 impl RunnableSecondaryExpression for Parentheses {
-    fn run_secondary_expr(self, interpreter: &mut EightInterpreter) -> MemRef {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
         self.expr.run_secondary_expr(interpreter)
     }
 }
@@ -1,15 +1,18 @@
 use log::{debug, trace};
 
 use basic::bool::BoolVal;
+use basic::float::FloatVal;
 use basic::number::NumberVal;
 use basic::string::StringVal;
 
 use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::parsing::ParsableLiteral;
 use enum_dispatch::enum_dispatch;
 use crate::eight::expressions::secondary::TypedSecondaryExpression;
 use crate::eight::values::{BasicValue, Value, ValueTypes};
+use std::hash::Hasher;
 
 pub mod basic;
 
@@ -19,6 +22,7 @@ pub enum Literal {
     Boolean(BoolVal),
     String(StringVal),
     Number(NumberVal),
+    Float(FloatVal),
 }
 impl BasicValue for Literal {
     fn eq(&self, other: &Value) -> bool {
@@ -31,9 +35,32 @@ impl BasicValue for Literal {
             },
             Literal::Number(n) => {
                 n.eq(other)
+            },
+            Literal::Float(f) => {
+                f.eq(other)
             }
         }
     }
+
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Literal::Boolean(b) => b.hash(state),
+            Literal::String(s) => s.hash(state),
+            Literal::Number(n) => n.hash(state),
+            Literal::Float(f) => f.hash(state),
+        }
+    }
+}
+
+impl Literal {
+    pub fn copy(&self) -> Self {
+        match self {
+            Literal::Boolean(b) => Literal::Boolean(b.copy()),
+            Literal::String(s) => Literal::String(s.copy()),
+            Literal::Number(n) => Literal::Number(n.copy()),
+            Literal::Float(f) => Literal::Float(f.copy()),
+        }
+    }
 }
 
 impl TypedSecondaryExpression for Literal {
@@ -42,6 +69,7 @@ impl TypedSecondaryExpression for Literal {
             Literal::Boolean(_) => ValueTypes::BooleanType,
             Literal::String(_) => ValueTypes::StringType,
             Literal::Number(_) => ValueTypes::NumberType,
+            Literal::Float(_) => ValueTypes::FloatType,
         });
     }
 
@@ -50,12 +78,12 @@ impl TypedSecondaryExpression for Literal {
     }
 }
 
-pub fn parse_literal<'a>(context: &mut Context) -> Option<Literal> {
+pub fn parse_literal<'a>(context: &mut Context) -> ParseResult<Option<Literal>> {
     // Parse bool
     debug!("Attempting Bool Parse");
-    match BoolVal::parse(context) {
+    match BoolVal::parse(context)? {
         Some(x) => {
-            return Some(x);
+            return Ok(Some(x));
         }
         None => {
             debug!("Bool parse fail");
@@ -64,25 +92,25 @@ pub fn parse_literal<'a>(context: &mut Context) -> Option<Literal> {
 
     // Parse string
     debug!("Attempting String parse");
-    match StringVal::parse(context) {
+    match StringVal::parse(context)? {
         Some(x) => {
-            return Some(x);
+            return Ok(Some(x));
         }
         None => {
             debug!("String parse fail");
         }
     }
 
-    // Parse number
+    // Parse number, this also handles floats
     debug!("Attempting Number parse");
-    match NumberVal::parse(context) {
+    match NumberVal::parse(context)? {
         Some(x) => {
-            return Some(x);
+            return Ok(Some(x));
         }
         None => {
             debug!("Number parse fail");
         }
     }
 
-    None
+    Ok(None)
 }
@@ -1,6 +1,11 @@
 pub mod bool;
+pub mod float;
 pub mod functions;
 pub mod number;
 pub mod string;
 pub mod variableref;
 pub mod list;
+pub mod map;
+pub mod set;
+pub mod structs;
+pub mod enums;
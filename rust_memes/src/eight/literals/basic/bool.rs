@@ -1,18 +1,29 @@
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::parsing::ParsableLiteral;
 use crate::eight::common::tokenizing::symbols::Symbols;
 use crate::eight::common::AccessibleValue;
+use crate::eight::common::running::error::RuntimeError;
 use crate::eight::expressions::secondary::callfunc::CallFunc;
 use crate::eight::literals::basic::functions::args::FuncArgs;
 use crate::eight::literals::{Literal};
 use log::debug;
 use crate::eight::values::{BasicValue, Value};
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug)]
 pub struct BoolVal {
     pub(crate) value: bool,
 }
 
+impl BoolVal {
+    pub fn copy(&self) -> Self {
+        BoolVal {
+            value: self.value
+        }
+    }
+}
+
 impl BasicValue for BoolVal {
     fn eq(&self, other: &Value) -> bool {
         match other {
@@ -22,26 +33,39 @@ impl BasicValue for BoolVal {
                         self.value == b.value
                     },
                     o => {
-                        panic!("Runtime exception cannot equate {:?} and {:?}", self, o);
+                        RuntimeError::new(format!("Cannot equate {:?} and {:?}", self, o)).raise();
                     }
                 }
             },
             o => {
-                panic!("Runtime exception cannot equate {:?} and {:?}", self, o);
+                RuntimeError::new(format!("Cannot equate {:?} and {:?}", self, o)).raise();
             }
         }
     }
+
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
 }
 
 impl AccessibleValue for BoolVal {
-    fn implicit_cast_to<'a>(val: &'a Value) -> &'a Self { todo!() }
+    fn implicit_cast_to<'a>(val: &'a Value) -> &'a Self {
+        match val {
+            Value::Literal(Literal::Boolean(b)) => {
+                b
+            },
+            o => {
+                RuntimeError::new(format!("Unable to implicitly cast {:?} to a bool value", o)).raise();
+            }
+        }
+    }
 
     fn explicit_cast_to<'a>(val: &'a Value) -> Self { todo!() }
 }
 
 impl ParsableLiteral for BoolVal {
-    fn parse<'a>(context: &mut Context) -> Option<Literal> {
-        return match context.get() {
+    fn parse<'a>(context: &mut Context) -> ParseResult<Option<Literal>> {
+        return Ok(match context.get()? {
             Symbols::LiteralSymb(s) => {
                 if s == "true" {
                     context.increment();
@@ -60,7 +84,7 @@ impl ParsableLiteral for BoolVal {
                 debug!("Invalid symbol for bool '{:?}'", o);
                 None
             }
-        };
+        });
     }
 
     fn create_class_func_call<'a>(name: &str, args: FuncArgs) -> Result<CallFunc, String> {
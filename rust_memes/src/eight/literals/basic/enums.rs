@@ -0,0 +1,149 @@
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::primary::funcdecl::parse_name;
+use crate::eight::expressions::secondary::{RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::literals::basic::functions::parse_function_call_args;
+use crate::eight::values::{BasicValue, Value, ValueTypes};
+use log::debug;
+
+#[derive(Debug, Clone)]
+pub struct EnumDef {
+    pub(crate) name: String,
+    pub(crate) variants: Vec<(String, Vec<ValueTypes>)>, // Each variant with the types of its payload, empty for 'Linear'
+}
+
+#[derive(Debug)]
+pub struct EnumVal {
+    pub(crate) name: String,
+    pub(crate) variant: String,
+    pub(crate) payload: Vec<Value>,
+}
+
+#[derive(Debug)]
+pub struct EnumExpression {
+    pub(crate) name: String,
+    pub(crate) variant: String,
+    pub(crate) payload: Vec<SecondaryExpression>,
+}
+
+
+impl EnumDef {
+    pub fn variant(&self, variant: &str) -> Option<&Vec<ValueTypes>> {
+        return self.variants.iter().find(|(n, _)| n == variant).map(|(_, t)| t);
+    }
+
+    pub fn variant_names(&self) -> String {
+        let names: Vec<String> = self.variants.iter().map(|(n, _)| format!("'{}'", n)).collect();
+        return names.join(", ");
+    }
+}
+
+impl EnumVal {
+    pub fn eq(&self, other: &EnumVal) -> bool {
+        return self.name == other.name
+            && self.variant == other.variant
+            && self.payload.iter().zip(other.payload.iter()).all(|(a, b)| a.eq(b));
+    }
+
+    pub fn copy(&self) -> Self {
+        EnumVal {
+            name: self.name.clone(),
+            variant: self.variant.clone(),
+            payload: self.payload.iter().map(|v| v.copy()).collect(),
+        }
+    }
+}
+
+impl EnumExpression {
+    pub fn parse(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+        // '<Enum>::<Variant>' or '<Enum>::<Variant>(<expr>, ...)' when the variant carries a payload
+        let def = match context.get_safe_multiple(2).as_slice() {
+            [Ok(Symbols::LiteralSymb(name)), Ok(Symbols::ClassAccessor)] => match context.enums.get(name) {
+                Some(def) => def.clone(),
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        let start_idx = context.get_index();
+        context.increment(); // past the name
+        context.increment(); // past '::'
+
+        let variant_idx = context.get_index();
+        let variant = parse_name(context, "variant name")?;
+        let expected = match def.variant(variant.as_str()) {
+            Some(t) => t.clone(),
+            None => {
+                context.jump(variant_idx);
+                return Err(context.error(format!("'{}' has no variant '{}'", def.name, variant))
+                    .with_note(format!("the variants are {}", def.variant_names())));
+            }
+        };
+        debug!("Found construction of '{}::{}'", def.name, variant);
+
+        let payload = if context.get_safe() == Ok(Symbols::ParenOpen) {
+            context.increment(); // past '('
+            parse_function_call_args(context)?.arglist
+        } else {
+            Vec::new()
+        };
+        if payload.len() != expected.len() {
+            return Err(Diagnostic::error(
+                format!("'{}::{}' takes {} value(s) but {} were given", def.name, variant, expected.len(), payload.len()),
+                context.span_since(start_idx),
+            ));
+        }
+        for (i, (val, typ)) in payload.iter().zip(expected.iter()).enumerate() {
+            match val.get_type() {
+                Ok(t) if typ.accepts(&t) => (),
+                Ok(t) => {
+                    return Err(Diagnostic::error(
+                        format!("Mismatched types, value {} of '{}::{}' is {} but found {}", i + 1, def.name, variant, typ, t),
+                        context.span_since(start_idx),
+                    ));
+                }
+                Err(s) => {
+                    return Err(Diagnostic::error(format!("Error getting type of value {} of '{}::{}', '{}'", i + 1, def.name, variant, s), context.span_since(start_idx)));
+                }
+            }
+        }
+
+        return Ok(Some(SecondaryExpression::EnumExpr(EnumExpression {
+            name: def.name,
+            variant,
+            payload,
+        })));
+    }
+}
+
+impl TypedSecondaryExpression for EnumExpression {
+    fn get_type(&self) -> Result<ValueTypes, String> {
+        Ok(ValueTypes::EnumType(self.name.clone()))
+    }
+
+    fn get_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        for ex in &self.payload {
+            rr.append(&mut ex.get_references());
+        }
+        return rr;
+    }
+}
+
+impl RunnableSecondaryExpression for EnumExpression {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        let mut payload = Vec::new();
+        for ex in &self.payload {
+            let m = interpreter.step(ex);
+            payload.push(interpreter.copy_val(m));
+        }
+        interpreter.add_val_to_stack(Value::EnumVal(EnumVal {
+            name: self.name.clone(),
+            variant: self.variant.clone(),
+            payload,
+        }))
+    }
+}
@@ -0,0 +1,65 @@
+use crate::eight::common::AccessibleValue;
+use crate::eight::common::running::error::RuntimeError;
+use crate::eight::literals::Literal;
+use crate::eight::values::{BasicValue, Value};
+use std::hash::{Hash, Hasher};
+
+#[derive(Debug)]
+pub struct FloatVal {
+    pub(crate) value: f64,
+}
+
+impl FloatVal {
+    pub fn copy(&self) -> Self {
+        FloatVal {
+            value: self.value
+        }
+    }
+
+    pub fn to_display(&self) -> String {
+        // Debug formatting keeps the '.0' on whole floats, so 2.0 doesn't print like the Number 2
+        format!("{:?}", self.value)
+    }
+}
+
+impl BasicValue for FloatVal {
+    fn eq(&self, other: &Value) -> bool {
+        match other {
+            Value::Literal(Literal::Float(f)) => {
+                self.value == f.value
+            },
+            o => {
+                RuntimeError::new(format!("Cannot equate {:?} and {:?}", self, o)).raise();
+            }
+        }
+    }
+
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.to_bits().hash(state);
+    }
+}
+
+impl AccessibleValue for FloatVal {
+    fn implicit_cast_to<'a>(val: &'a Value) -> &'a Self {
+        match val {
+            Value::Literal(Literal::Float(f)) => {
+                f
+            },
+            o => {
+                RuntimeError::new(format!("Unable to implicitly cast {:?} to a float value", o)).raise();
+            }
+        }
+    }
+
+    fn explicit_cast_to<'a>(val: &'a Value) -> Self {
+        return match val {
+            Value::Literal(Literal::Float(f)) => f.copy(),
+            Value::Literal(Literal::Number(n)) => FloatVal { value: n.value as f64 },
+            Value::Literal(Literal::String(s)) => match s.value.trim().parse::<f64>() {
+                Ok(value) => FloatVal { value },
+                Err(_) => RuntimeError::new(format!("Cannot cast '{}' to a Float", s.value)).raise(),
+            },
+            o => RuntimeError::new(format!("Cannot cast {:?} to a Float", o)).raise(),
+        };
+    }
+}
@@ -1,17 +1,21 @@
 use crate::eight::common::{AccessibleValue, is_varname_valid};
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::tokenizing::symbols::Symbols;
 use crate::eight::expressions::primary::noop::NoOp;
+use crate::eight::expressions::primary::Expression;
 use crate::eight::expressions::secondary::callfunc::CallFunc;
 use crate::eight::literals::basic::functions::args::FuncArgs;
 use crate::eight::literals::basic::functions::content::FuncContent;
 use crate::eight::literals::basic::functions::signature::FuncSignature;
 use crate::eight::common::running::interpreter::EightInterpreter;
 use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::running::error::RuntimeError;
 use crate::eight::expressions::secondary::{parse_secondary_expression, SecondaryExpression};
-use crate::eight::values::Value;
+use crate::eight::values::{Value, ValueTypes};
 
 use std::fmt::Debug;
+use std::rc::Rc;
 use log::{debug, trace};
 
 pub mod args;
@@ -23,7 +27,9 @@ pub mod signature;
 pub struct Func {
     pub(crate) name: String,
     pub(crate) sig: FuncSignature,
+    pub(crate) params: Vec<String>, // Argument names for user funcs, built in funcs read '$0', '$1', ...
     pub(crate) content: FuncContent,
+    pub(crate) captures: Vec<(String, Value)>, // Closure values copied from the defining frame
 }
 
 impl Func {
@@ -31,7 +37,29 @@ impl Func {
         Func {
             name,
             sig,
+            params: vec![],
             content: cont,
+            captures: vec![],
+        }
+    }
+
+    pub fn new_user(name: String, params: Vec<String>, sig: FuncSignature, body: Vec<Expression>) -> Self {
+        Func {
+            name,
+            sig,
+            params,
+            content: FuncContent::Func(body),
+            captures: vec![],
+        }
+    }
+
+    pub fn new_closure(params: Vec<String>, sig: FuncSignature, body: Rc<SecondaryExpression>, captures: Vec<(String, Value)>) -> Self {
+        Func {
+            name: String::from("<closure>"),
+            sig,
+            params,
+            content: FuncContent::Closure(body),
+            captures,
         }
     }
 
@@ -39,7 +67,37 @@ impl Func {
         Func {
             name: String::new(),
             sig: FuncSignature::empty(),
+            params: vec![],
             content: FuncContent::BuiltInFunc(|i: &mut EightInterpreter| MemRef::empty()),
+            captures: vec![],
+        }
+    }
+
+    pub fn get_body(&self) -> Option<&Vec<Expression>> {
+        return match &self.content {
+            FuncContent::Func(body) => Some(body),
+            _ => None,
+        };
+    }
+
+    pub fn copy(&self) -> Self {
+        // Function values are copied like any other value, closure bodies are shared
+        let content = match &self.content {
+            FuncContent::BuiltInFunc(f) => FuncContent::BuiltInFunc(*f),
+            FuncContent::Closure(body) => FuncContent::Closure(Rc::clone(body)),
+            FuncContent::Declared => FuncContent::Declared,
+            FuncContent::VarRef => FuncContent::VarRef,
+            FuncContent::Func(_) => {
+                RuntimeError::new(format!("Cannot copy the body of function '{}'", self.name)).raise();
+            }
+        };
+
+        Func {
+            name: self.name.clone(),
+            sig: self.sig.clone(),
+            params: self.params.clone(),
+            content,
+            captures: self.captures.iter().map(|(n, v)| (n.clone(), v.copy())).collect(),
         }
     }
 
@@ -58,15 +116,20 @@ impl AccessibleValue for Func {
     }
 }
 
-pub fn parse_function_call_args<'a>(context: &mut Context) -> FuncArgs {
+pub fn parse_function_call_args<'a>(context: &mut Context) -> ParseResult<FuncArgs> {
     let mut args = Vec::new();
+    if context.get()? == Symbols::ParenClose {
+        // No args, 'f()'
+        context.increment();
+        return Ok(FuncArgs { arglist: args });
+    }
 
     loop {
-        let secexpr: SecondaryExpression = parse_secondary_expression(context);
+        let secexpr: SecondaryExpression = parse_secondary_expression(context)?;
         args.push(secexpr);
         context.print_symbols_current();
 
-        match context.get() {
+        match context.get()? {
             Symbols::Comma => {
                 context.increment();
                 continue;
@@ -76,33 +139,27 @@ pub fn parse_function_call_args<'a>(context: &mut Context) -> FuncArgs {
                 break;
             }
             s => {
-                panic!(
-                    "{}",
-                    context.get_panic_smessage(format!(
-                        "Unknown identifier '{:?}' when parsing func args",
-                        s.to_str()
-                    ))
-                );
+                return Err(context.error(format!(
+                    "Unknown identifier '{}' when parsing func args",
+                    s.to_str()
+                )));
             }
         }
     }
-    return FuncArgs { arglist: args };
+    return Ok(FuncArgs { arglist: args });
 }
 
-pub fn parse_function_call<'a>(context: &mut Context) -> Option<(String, FuncArgs)> {
-    return match context.get() {
+pub fn parse_function_call<'a>(context: &mut Context) -> ParseResult<Option<(String, FuncArgs)>> {
+    return match context.get()? {
         Symbols::LiteralSymb(func_name) => {
 
             match context.get_safe_multiple(2).remove(1) {
                 Ok(Symbols::ParenOpen) => {
                     if !is_varname_valid(func_name.as_str()) {
-                        panic!(
-                            "{}",
-                            context.get_panic_smessage(format!(
-                                "Invalid function name '{}' in parsing function call",
-                                func_name.as_str()
-                            ))
-                        );
+                        return Err(context.error(format!(
+                            "Invalid function name '{}' in parsing function call",
+                            func_name.as_str()
+                        )));
                     }
                     context.increment();
                     // Past func name literal
@@ -112,15 +169,15 @@ pub fn parse_function_call<'a>(context: &mut Context) -> Option<(String, FuncArg
                     );
 
                     context.increment(); // Increment past paren
-                    Some((func_name, parse_function_call_args(context)))
+                    Ok(Some((func_name, parse_function_call_args(context)?)))
                 }
                 _ => {
                     trace!("No '(' found for func");
-                    None
+                    Ok(None)
                 }
             }
         }
-        _ => None,
+        _ => Ok(None),
     };
 }
 
@@ -138,3 +195,34 @@ pub fn generate_func<'a>(name: String, mut args: FuncArgs, sig: FuncSignature,
 
     return Ok(CallFunc::new(ff, args));
 }
+
+pub fn parse_user_func_call(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+    // '<name>(<args>)' where name is a func declared with 'func' or a variable holding a function value
+    let start_idx = context.get_index();
+    let name = match context.get_safe_multiple(2).as_slice() {
+        [Ok(Symbols::LiteralSymb(name)), Ok(Symbols::ParenOpen)] => name.clone(),
+        _ => return Ok(None),
+    };
+    // Variables shadow declared functions of the same name
+    let (sig, content) = match (context.vartable.get(&name), context.functions.get(&name)) {
+        (Some(ValueTypes::FuncType(sig)), _) => (sig.as_ref().clone(), FuncContent::VarRef),
+        (Some(typ), _) => {
+            return Err(context.error(format!("'{}' is a {} and can't be called like a function", name, typ)));
+        }
+        (None, Some(sig)) => (sig.clone(), FuncContent::Declared),
+        (None, None) => {
+            return Err(context.error(format!("Cannot find function '{}' in this scope", name)));
+        }
+    };
+
+    let (name, args) = match parse_function_call(context)? {
+        Some(call) => call,
+        None => return Ok(None),
+    };
+    if let Err(e) = sig.match_signature(&args) {
+        return Err(Diagnostic::error(e, context.span_since(start_idx)));
+    }
+
+    let func = Func::new(name, sig, content);
+    return Ok(Some(SecondaryExpression::CallResult(CallFunc::new(func, args))));
+}
@@ -1,13 +1,18 @@
 use crate::eight::common::running::interpreter::EightInterpreter;
 use crate::eight::common::running::memory::memref::MemRef;
 use crate::eight::expressions::primary::Expression;
+use crate::eight::expressions::secondary::SecondaryExpression;
 use crate::eight::values::Value;
 use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
 
 
 pub enum FuncContent {
     BuiltInFunc(fn(&mut EightInterpreter) -> MemRef),
     Func(Vec<Expression>),
+    Declared, // User func called by name, the body is registered with the interpreter when its declaration runs
+    Closure(Rc<SecondaryExpression>), // Body of a '|x: Number| x * 2' expression, shared between copies of the value
+    VarRef, // Function value stored in the variable with the func's name, looked up when called
 }
 
 impl Debug for FuncContent {
@@ -20,6 +25,15 @@ impl Debug for FuncContent {
             FuncContent::BuiltInFunc(_) => {
                 write!(f, "{}BuiltInFunc)", s)
             }
+            FuncContent::Declared => {
+                write!(f, "{}Declared)", s)
+            }
+            FuncContent::Closure(body) => {
+                write!(f, "{}Closure {:?})", s, body)
+            }
+            FuncContent::VarRef => {
+                write!(f, "{}VarRef)", s)
+            }
         };
     }
 }
@@ -1,7 +1,9 @@
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
 use crate::eight::literals::basic::functions::args::FuncArgs;
 use crate::eight::values::ValueTypes;
 use crate::eight::expressions::secondary::TypedSecondaryExpression;
+use log::trace;
 
 
 #[derive(Eq, PartialEq, Debug, Clone)]
@@ -49,18 +51,20 @@ impl FuncSignature {
         FuncSignature { arglist: v, output }
     }
 
-    pub fn match_output(&self, context: &mut Context, output: ValueTypes) {
+    pub fn get_arglist(&self) -> &Vec<ValueTypes> {
+        &self.arglist
+    }
+
+    pub fn match_output(&self, context: &mut Context, output: ValueTypes) -> ParseResult<()> {
         // Match output
-        if output != self.output {
-            panic!(
-                "{}",
-                context.get_panic_smessage(format!(
-                    "Function call output type '{:?}' doesn't match expected '{:?}'",
-                    output, self.output
-                ))
-            );
+        if !self.output.accepts(&output) {
+            return Err(context.error(format!(
+                "Function output type '{}' doesn't match expected '{}'",
+                output, self.output
+            )));
         }
-        println!("Matched output '{:?}' == '{:?}'", output, self.output);
+        trace!("Matched output '{:?}' == '{:?}'", output, self.output);
+        return Ok(());
     }
 
     pub fn match_signature(&self, args: &FuncArgs) -> Result<(), String> {
@@ -70,7 +74,9 @@ impl FuncSignature {
         // Match arg length
         if arglist.len() != self.arglist.len() {
             return Err(format!(
-                "Invalid function call, does not match function signature"
+                "Invalid function call, expected {} argument(s) but {} were given",
+                self.arglist.len(),
+                arglist.len()
             ));
         }
 
@@ -81,13 +87,13 @@ impl FuncSignature {
             let argtyp = arg.get_type();
             match argtyp {
                 Ok(m) => {
-                    let mm = &m == sig_arg;
-                    println!("matched '{:?}' == '{:?}' -> {:?}", &m, sig_arg, mm);
+                    let mm = sig_arg.accepts(&m);
+                    trace!("matched '{:?}' == '{:?}' -> {:?}", &m, sig_arg, mm);
                     if mm {
                         i += 1;
                         continue;
                     } else {
-                        return Err(format!("Invalid function call, argument {} does not match signature. Given '{:?}' Expected '{:?}'",
+                        return Err(format!("Invalid function call, argument {} does not match signature. Given '{}' Expected '{}'",
                                            i,
                                            m,
                                            sig_arg
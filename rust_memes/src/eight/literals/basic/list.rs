@@ -1,10 +1,21 @@
 use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::running::interpreter::EightInterpreter;
 use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::running::error::RuntimeError;
 use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::common::tokenizing::token::Span;
+use crate::eight::expressions::primary::format::get_fmt_relations;
 use crate::eight::expressions::secondary::{parse_secondary_expression, RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
-use crate::eight::values::{Value, ValueTypes};
+use crate::eight::literals::basic::bool::BoolVal;
+use crate::eight::literals::basic::functions::signature::FuncSignature;
+use crate::eight::literals::basic::number::NumberVal;
+use crate::eight::literals::basic::string::StringVal;
+use crate::eight::literals::Literal;
+use crate::eight::common::AccessibleValue;
+use crate::eight::values::methods::{MethodReceiver, MethodTable};
+use crate::eight::values::{BasicValue, Value, ValueTypes};
 
 #[derive(Debug)]
 pub struct List {
@@ -19,78 +30,95 @@ pub struct ListExpression {
 }
 
 
-impl ListExpression {
-    fn parse_element(ln: i32, context: &mut Context) -> (Option<SecondaryExpression>, Option<ValueTypes>, bool) {
-        // Returns (SecondaryExpression, ValueType, is_next_expr: bool)
-        context.increment();
-        match context.get() {
-            Symbols::BracketClose => {
-                // Empty list
-                return (None, None, false);
-            },
-            _ => ()
-        }
-        let s = parse_secondary_expression(context);
-        let typ: Result<ValueTypes, String> = s.get_type();
-        let v_typ: ValueTypes;
-
-        match typ {
-            Ok(v) => {
-                v_typ = v;
-            },
-            Err(s) => {
-                panic!("{}", context.get_panic_smessage(format!("Type error during list starting with '[' on line {} Error: {}", ln, s)))
-            }
+impl List {
+    pub fn copy(&self) -> Self {
+        List {
+            data: self.data.iter().map(|v| v.copy()).collect(),
+            typ: self.typ.clone(),
         }
+    }
+}
 
-        match context.get() {
-            Symbols::BracketClose => {
-                return (Some(s), Some(v_typ), false);
-            },
-            Symbols::Comma => {
-                return (Some(s), Some(v_typ), true);
-            },
-            _ => panic!("{}", context.get_panic_smessage(format!("Expected closing bracket or comma from '[' started on line {}", ln)))
+impl AccessibleValue for List {
+    fn implicit_cast_to<'a>(val: &'a Value) -> &'a Self {
+        match val {
+            Value::ListVal(v) => v,
+            o => RuntimeError::new(format!("Unable to implicitly cast {:?} to a List value", o)).raise(),
         }
     }
 
-    pub fn parse(context: &mut Context) -> Option<SecondaryExpression> {
-        match context.get() {
-            Symbols::BracketOpen => {
-                let ln = context.get_line_no();
+    fn explicit_cast_to<'a>(val: &'a Value) -> Self {
+        // A Set gives its elements and a Map its keys, in insertion order like 'for' sees them
+        return match val {
+            Value::ListVal(l) => l.copy(),
+            Value::SetVal(s) => List { data: s.entries.iter().map(|v| v.copy()).collect(), typ: s.typ.clone() },
+            Value::MapVal(m) => List { data: m.entries.iter().map(|(k, _)| k.copy()).collect(), typ: m.key_typ.clone() },
+            o => RuntimeError::new(format!("Cannot cast {:?} to a List", o)).raise(),
+        };
+    }
+}
 
-                let expr_and_type = ListExpression::parse_element(ln, context);
-                let expr = expr_and_type.0;
-                if !expr_and_type.2 {
-                    return Some(SecondaryExpression::ListExpr(ListExpression::empty()));
+impl ListExpression {
+    pub fn parse(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+        // '[<expr>, ...]', the first element sets the type every other element has to match
+        let open_span = match context.get()? {
+            Symbols::BracketOpen => context.get_span(),
+            _ => return Ok(None),
+        };
+        context.increment(); // past '['
+
+        let mut exprs: Vec<SecondaryExpression> = Vec::new();
+        let mut typ = ValueTypes::NoneType; // Stays NoneType for an empty list until it's given a type
+        let mut first_span: Option<Span> = None;
+        loop {
+            if context.get()? == Symbols::BracketClose {
+                // Empty list or a trailing comma
+                context.increment();
+                break;
+            }
+
+            let elem_idx = context.get_index();
+            let expr = parse_secondary_expression(context)?;
+            let elem_span = context.span_since(elem_idx);
+            let elem_typ = match expr.get_type() {
+                Ok(t) => t,
+                Err(s) => {
+                    return Err(Diagnostic::error(format!("Error getting type of list element, '{}'", s), elem_span));
                 }
+            };
 
-                let mut exprs: Vec<SecondaryExpression> = Vec::new();
-
-                exprs.push(expr.unwrap_or_else(|| {
-                    panic!("{}", context.get_panic_smessage(format!("Expected value in list, none found from '[' starting on line {}", ln)));
-                }));
-                let orig_typ = expr_and_type.1.unwrap();
-
-                loop {
-                    match ListExpression::parse_element(ln, context) {
-                        (Some(sexpr), Some(sexpr_typ), cont) => {
-                            if orig_typ != sexpr_typ {
-                                panic!("Currently only homogeneous-typed lists are supported"); // TODO mutli-typed list?
-                            }
-                            exprs.push(sexpr);
-
-                            if !cont {
-                                context.increment(); // Increment past closing ']'
-                                return Some(SecondaryExpression::ListExpr(ListExpression::new(exprs, orig_typ)));
-                            }
-                        },
-                        p => panic!("{}", context.get_panic_smessage(format!("Error parsing list: unexpected pattern returned from List::parse_element! {:?}", p)))
+            match &first_span {
+                None => {
+                    typ = elem_typ;
+                    first_span = Some(elem_span);
+                }
+                Some(first) => {
+                    if !typ.accepts(&elem_typ) {
+                        return Err(Diagnostic::error(format!("Mismatched types in list, expected {} but found {}", typ, elem_typ), elem_span)
+                            .with_label(format!("expected {}, found {}", typ, elem_typ))
+                            .with_secondary(*first, String::from("expected because of the first element")));
                     }
+                    if typ.is_unknown_collection() {
+                        typ = elem_typ; // '[[], [1]]' is a List<List<Number>>
+                    }
+                }
+            }
+            exprs.push(expr);
+
+            match context.get()? {
+                Symbols::Comma => context.increment(),
+                Symbols::BracketClose => {
+                    context.increment();
+                    break;
+                }
+                o => {
+                    return Err(context.error(format!("Expected ',' or ']' in list, found '{}'", o.to_str()))
+                        .with_note(format!("'[' opened on line {} column {}", open_span.line, open_span.col)));
                 }
-            },
-            _ => None
+            }
         }
+
+        return Ok(Some(SecondaryExpression::ListExpr(ListExpression::new(exprs, typ))));
     }
 
     pub fn new(exprs: Vec<SecondaryExpression>, typ: ValueTypes) -> Self {
@@ -111,12 +139,41 @@ impl TypedSecondaryExpression for ListExpression {
     }
 
     fn get_references(&self) -> Vec<RelationEntry> {
-        todo!()
+        return get_fmt_relations(&self.exprs);
     }
 }
 
 impl RunnableSecondaryExpression for ListExpression {
-    fn run_secondary_expr(self, interpreter: &mut EightInterpreter) -> MemRef {
-        todo!()
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        let mut data = Vec::new();
+        for ex in &self.exprs {
+            let m = interpreter.step(ex);
+            data.push(interpreter.copy_val(m));
+        }
+        interpreter.add_val_to_stack(Value::ListVal(List {
+            data,
+            typ: self.typ.clone(),
+        }))
     }
-}
\ No newline at end of file
+}
+
+pub fn register_methods(table: &mut MethodTable) {
+    table.register(MethodReceiver::List, "len", |_| FuncSignature::new(vec![], ValueTypes::NumberType), |l, _| {
+        Value::Literal(Literal::Number(NumberVal { value: l.get_val::<List>().data.len() as i64 }))
+    });
+    table.register(MethodReceiver::List, "is_empty", |_| FuncSignature::new(vec![], ValueTypes::BooleanType), |l, _| {
+        Value::Literal(Literal::Boolean(BoolVal { value: l.get_val::<List>().data.is_empty() }))
+    });
+    table.register(MethodReceiver::List, "contains", |t| FuncSignature::new(vec![t.elem_type()], ValueTypes::BooleanType), |l, args| {
+        Value::Literal(Literal::Boolean(BoolVal { value: l.get_val::<List>().data.iter().any(|v| v.eq(&args[0])) }))
+    });
+    table.register(MethodReceiver::List, "reverse", |t| FuncSignature::new(vec![], t.clone()), |l, _| {
+        let l = l.get_val::<List>();
+        Value::ListVal(List { data: l.data.iter().rev().map(|v| v.copy()).collect(), typ: l.typ.clone() })
+    });
+    // Elements are shown the same way 'fmt' shows them
+    table.register(MethodReceiver::List, "join", |_| FuncSignature::new(vec![ValueTypes::StringType], ValueTypes::StringType), |l, args| {
+        let parts: Vec<String> = l.get_val::<List>().data.iter().map(|v| StringVal::explicit_cast_to(v).value).collect();
+        Value::Literal(Literal::String(StringVal { value: parts.join(args[0].get_val::<StringVal>().value.as_str()) }))
+    });
+}
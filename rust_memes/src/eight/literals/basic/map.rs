@@ -0,0 +1,257 @@
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::running::error::RuntimeError;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::common::tokenizing::token::Span;
+use crate::eight::expressions::secondary::{parse_secondary_expression, RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::literals::basic::bool::BoolVal;
+use crate::eight::literals::basic::functions::signature::FuncSignature;
+use crate::eight::literals::basic::list::List;
+use crate::eight::literals::basic::number::NumberVal;
+use crate::eight::literals::Literal;
+use crate::eight::common::AccessibleValue;
+use crate::eight::values::methods::{MethodReceiver, MethodTable};
+use crate::eight::values::{BasicValue, Value, ValueTypes};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+#[derive(Debug)]
+pub struct HashKey(pub(crate) Value); // Hashes and compares the value through BasicValue
+
+impl Hash for HashKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        BasicValue::hash(&self.0, state);
+    }
+}
+
+impl PartialEq for HashKey {
+    fn eq(&self, other: &Self) -> bool {
+        BasicValue::eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for HashKey {}
+
+#[derive(Debug)]
+pub struct Map {
+    pub(crate) entries: Vec<(Value, Value)>, // Kept in insertion order, so iterating and printing is stable
+    pub(crate) index: HashMap<HashKey, usize>,
+    pub(crate) key_typ: ValueTypes,
+    pub(crate) val_typ: ValueTypes,
+}
+
+#[derive(Debug)]
+pub struct MapExpression {
+    pub(crate) exprs: Vec<(SecondaryExpression, SecondaryExpression)>,
+    pub(crate) key_typ: ValueTypes,
+    pub(crate) val_typ: ValueTypes,
+}
+
+
+impl Map {
+    pub fn new(key_typ: ValueTypes, val_typ: ValueTypes) -> Self {
+        Map {
+            entries: Vec::new(),
+            index: HashMap::new(),
+            key_typ,
+            val_typ,
+        }
+    }
+
+    pub fn insert(&mut self, key: Value, val: Value) {
+        if self.entries.is_empty() && self.key_typ == ValueTypes::NoneType {
+            // An empty '{}' takes its types from the first entry
+            self.key_typ = ValueTypes::convert_to_type(&key);
+            self.val_typ = ValueTypes::convert_to_type(&val);
+        }
+        match self.index.get(&HashKey(key.copy())) {
+            Some(idx) => self.entries[*idx].1 = val,
+            None => {
+                self.index.insert(HashKey(key.copy()), self.entries.len());
+                self.entries.push((key, val));
+            }
+        }
+    }
+
+    pub fn get(&self, key: &Value) -> Option<&Value> {
+        let idx = *self.index.get(&HashKey(key.copy()))?;
+        return Some(&self.entries[idx].1);
+    }
+
+    pub fn get_mut(&mut self, key: &Value) -> Option<&mut Value> {
+        let idx = *self.index.get(&HashKey(key.copy()))?;
+        return Some(&mut self.entries[idx].1);
+    }
+
+    pub fn contains(&self, key: &Value) -> bool {
+        return self.index.contains_key(&HashKey(key.copy()));
+    }
+
+    pub fn eq(&self, other: &Map) -> bool {
+        // Same keys holding equal values, the insertion order doesn't matter
+        return self.entries.len() == other.entries.len()
+            && self.entries.iter().all(|(k, v)| other.get(k).map_or(false, |o| v.eq(o)));
+    }
+
+    pub fn copy(&self) -> Self {
+        let mut m = Map::new(self.key_typ.clone(), self.val_typ.clone());
+        for (k, v) in &self.entries {
+            m.insert(k.copy(), v.copy());
+        }
+        return m;
+    }
+}
+
+impl AccessibleValue for Map {
+    fn implicit_cast_to<'a>(val: &'a Value) -> &'a Self {
+        match val {
+            Value::MapVal(v) => v,
+            o => RuntimeError::new(format!("Unable to implicitly cast {:?} to a Map value", o)).raise(),
+        }
+    }
+
+    fn explicit_cast_to<'a>(val: &'a Value) -> Self {
+        return match val {
+            Value::MapVal(m) => m.copy(),
+            o => RuntimeError::new(format!("Cannot cast {:?} to a Map", o)).raise(),
+        };
+    }
+}
+
+impl MapExpression {
+    pub fn parse(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+        // '{<key>: <value>, ...}', the first entry sets the types every other entry has to match
+        let open_span = match context.get()? {
+            Symbols::CurlyOpen => context.get_span(),
+            _ => return Ok(None),
+        };
+        context.increment(); // past '{'
+
+        let mut exprs = Vec::new();
+        let mut key_typ = ValueTypes::NoneType; // Both stay NoneType for an empty map until it's given a type
+        let mut val_typ = ValueTypes::NoneType;
+        let mut first_spans: Option<(Span, Span)> = None;
+        loop {
+            if context.get()? == Symbols::CurlyClose {
+                // Empty map or a trailing comma
+                context.increment();
+                break;
+            }
+
+            let (key, k_typ, k_span) = parse_entry_part(context, "key")?;
+            if !k_typ.is_hashable() {
+                return Err(Diagnostic::error(format!("{} can't be used as a Map key", k_typ), k_span)
+                    .with_note(String::from("keys have to be String, Number or Boolean")));
+            }
+            match context.get()? {
+                Symbols::Colon => context.increment(),
+                o => {
+                    return Err(context.error(format!("Expected ':' between the key and value of a map entry, found '{}'", o.to_str())));
+                }
+            }
+            let (val, v_typ, v_span) = parse_entry_part(context, "value")?;
+
+            match &first_spans {
+                None => {
+                    key_typ = k_typ;
+                    val_typ = v_typ;
+                    first_spans = Some((k_span, v_span));
+                }
+                Some((first_k, first_v)) => {
+                    if !key_typ.accepts(&k_typ) {
+                        return Err(mismatch("key", &key_typ, &k_typ, k_span, *first_k));
+                    }
+                    if !val_typ.accepts(&v_typ) {
+                        return Err(mismatch("value", &val_typ, &v_typ, v_span, *first_v));
+                    }
+                    if val_typ.is_unknown_collection() {
+                        val_typ = v_typ; // '{"a": [], "b": [1]}' is a Map<String, List<Number>>
+                    }
+                }
+            }
+            exprs.push((key, val));
+
+            match context.get()? {
+                Symbols::Comma => context.increment(),
+                Symbols::CurlyClose => {
+                    context.increment();
+                    break;
+                }
+                o => {
+                    return Err(context.error(format!("Expected ',' or '}}' in map, found '{}'", o.to_str()))
+                        .with_note(format!("'{{' opened on line {} column {}", open_span.line, open_span.col)));
+                }
+            }
+        }
+
+        return Ok(Some(SecondaryExpression::MapExpr(MapExpression {
+            exprs,
+            key_typ,
+            val_typ,
+        })));
+    }
+}
+
+fn parse_entry_part(context: &mut Context, part: &str) -> ParseResult<(SecondaryExpression, ValueTypes, Span)> {
+    let idx = context.get_index();
+    let expr = parse_secondary_expression(context)?;
+    let span = context.span_since(idx);
+    return match expr.get_type() {
+        Ok(t) => Ok((expr, t, span)),
+        Err(s) => Err(Diagnostic::error(format!("Error getting type of map {}, '{}'", part, s), span)),
+    };
+}
+
+fn mismatch(part: &str, expected: &ValueTypes, found: &ValueTypes, span: Span, first: Span) -> Diagnostic {
+    return Diagnostic::error(format!("Mismatched {} types in map, expected {} but found {}", part, expected, found), span)
+        .with_label(format!("expected {}, found {}", expected, found))
+        .with_secondary(first, format!("expected because of the first {}", part));
+}
+
+impl TypedSecondaryExpression for MapExpression {
+    fn get_type(&self) -> Result<ValueTypes, String> {
+        Ok(ValueTypes::MapType(Box::from(self.key_typ.clone()), Box::from(self.val_typ.clone())))
+    }
+
+    fn get_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        for (k, v) in &self.exprs {
+            rr.append(&mut SecondaryExpression::combine_references(k, v));
+        }
+        return rr;
+    }
+}
+
+impl RunnableSecondaryExpression for MapExpression {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        let mut map = Map::new(self.key_typ.clone(), self.val_typ.clone());
+        for (k, v) in &self.exprs {
+            let m = interpreter.step(k);
+            let key = interpreter.copy_val(m);
+            let m = interpreter.step(v);
+            let val = interpreter.copy_val(m);
+            map.insert(key, val); // A repeated key keeps the last value
+        }
+        interpreter.add_val_to_stack(Value::MapVal(map))
+    }
+}
+
+pub fn register_methods(table: &mut MethodTable) {
+    table.register(MethodReceiver::Map, "len", |_| FuncSignature::new(vec![], ValueTypes::NumberType), |m, _| {
+        Value::Literal(Literal::Number(NumberVal { value: m.get_val::<Map>().entries.len() as i64 }))
+    });
+    table.register(MethodReceiver::Map, "keys", |t| FuncSignature::new(vec![], ValueTypes::ListType(Box::new(t.elem_type()))), |m, _| {
+        let m = m.get_val::<Map>();
+        Value::ListVal(List { data: m.entries.iter().map(|(k, _)| k.copy()).collect(), typ: m.key_typ.clone() })
+    });
+    table.register(MethodReceiver::Map, "values", |t| FuncSignature::new(vec![], ValueTypes::ListType(Box::new(t.val_type()))), |m, _| {
+        let m = m.get_val::<Map>();
+        Value::ListVal(List { data: m.entries.iter().map(|(_, v)| v.copy()).collect(), typ: m.val_typ.clone() })
+    });
+    table.register(MethodReceiver::Map, "contains", |t| FuncSignature::new(vec![t.elem_type()], ValueTypes::BooleanType), |m, args| {
+        Value::Literal(Literal::Boolean(BoolVal { value: m.get_val::<Map>().contains(&args[0]) }))
+    });
+}
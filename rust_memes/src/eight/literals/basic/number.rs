@@ -1,17 +1,23 @@
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
-use crate::eight::common::parsing::util::slice_str;
 use crate::eight::common::parsing::ParsableLiteral;
 use crate::eight::common::tokenizing::symbols::Symbols;
 use crate::eight::common::AccessibleValue;
 use crate::eight::expressions::secondary::callfunc::CallFunc;
 use crate::eight::literals::basic::functions::args::FuncArgs;
+use crate::eight::common::parsing::util::slice_str;
+use crate::eight::common::running::error::RuntimeError;
+use crate::eight::literals::basic::float::FloatVal;
 use crate::eight::literals::{Literal};
 use log::debug;
-use crate::eight::values::{BasicValue, Value};
+use crate::eight::literals::basic::functions::signature::FuncSignature;
+use crate::eight::values::methods::{MethodReceiver, MethodTable};
+use crate::eight::values::{BasicValue, Value, ValueTypes};
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug)]
 pub struct NumberVal {
-    pub(crate) value: i32,
+    pub(crate) value: i64,
 }
 
 impl NumberVal {
@@ -31,15 +37,19 @@ impl BasicValue for NumberVal {
                         self.value == b.value
                     },
                     o => {
-                        panic!("Runtime exception cannot equate {:?} and {:?}", self, o);
+                        RuntimeError::new(format!("Cannot equate {:?} and {:?}", self, o)).raise();
                     }
                 }
             },
             o => {
-                panic!("Runtime exception cannot equate {:?} and {:?}", self, o);
+                RuntimeError::new(format!("Cannot equate {:?} and {:?}", self, o)).raise();
             }
         }
     }
+
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
 }
 
 impl AccessibleValue for NumberVal {
@@ -49,8 +59,7 @@ impl AccessibleValue for NumberVal {
                 n
             },
             o => {
-                //TODO Runtime exceptions
-                panic!("Runtime exception: unable to implicitly cast {:?} to a number value", o);
+                RuntimeError::new(format!("Unable to implicitly cast {:?} to a number value", o)).raise();
             }
         }
     }
@@ -59,40 +68,36 @@ impl AccessibleValue for NumberVal {
 }
 
 impl ParsableLiteral for NumberVal {
-    fn parse<'a>(context: &mut Context) -> Option<Literal> {
-        return match context.get() {
-            Symbols::LiteralSymb(snippet) => {
-                for idx in 0..snippet.len() {
-                    let one_char = slice_str(&snippet, idx, idx + 1);
-
-                    if one_char == "0"
-                        || one_char == "1"
-                        || one_char == "2"
-                        || one_char == "3"
-                        || one_char == "4"
-                        || one_char == "5"
-                        || one_char == "6"
-                        || one_char == "7"
-                        || one_char == "8"
-                        || one_char == "9"
-                    {
-                    } else {
-                        // println!("Found non-numeric character in literal '{}'!", one_char);
-                        return None;
+    fn parse<'a>(context: &mut Context) -> ParseResult<Option<Literal>> {
+        // Parses both Number and Float literals, since the token alone decides which it is
+        // A '-' directly in front of a number literal makes it negative, '-5'
+        // unless '**' follows, since -2 ** 2 is -(2 ** 2) and is left to the unary operator
+        let (text, negative) = match context.get()? {
+            Symbols::NumberLiteral(text) => (text, false),
+            Symbols::Sub => {
+                let ahead = context.get_safe_multiple(3);
+                match (ahead.get(1), ahead.get(2)) {
+                    (Some(Ok(Symbols::NumberLiteral(_))), Some(Ok(Symbols::Exponent))) => return Ok(None),
+                    (Some(Ok(Symbols::NumberLiteral(text))), _) => {
+                        let text = text.clone();
+                        context.increment(); // past '-'
+                        (text, true)
                     }
+                    _ => return Ok(None),
                 }
+            },
+            _ => return Ok(None),
+        };
 
-                let num = &snippet.parse::<i32>();
-                return match num {
-                    Ok(x) => {
-                        debug!("Found num '{}'", x);
-                        context.increment();
-                        Some(Literal::Number(NumberVal { value: *x }))
-                    }
-                    _ => None,
-                };
+        return match parse_number_literal(&text, negative) {
+            Ok(lit) => {
+                debug!("Found num '{:?}'", lit);
+                context.increment();
+                Ok(Some(lit))
+            }
+            Err(e) => {
+                Err(context.error(e))
             }
-            _ => None,
         };
     }
 
@@ -100,3 +105,88 @@ impl ParsableLiteral for NumberVal {
         todo!()
     }
 }
+
+fn int_out_of_range(literal: &str) -> String {
+    format!(
+        "Number literal '{}' is out of range, Numbers are 64-bit signed integers between {} and {}",
+        literal,
+        i64::MIN,
+        i64::MAX
+    )
+}
+
+pub fn parse_number_literal(text: &str, negative: bool) -> Result<Literal, String> {
+    // Convert the text of a number literal into a Number (i64) or Float (f64)
+    // Supports '_' separators, 0x/0b/0o prefixes, decimals and exponents
+    let literal = if negative { format!("-{}", text) } else { String::from(text) };
+    if text.ends_with('_') {
+        return Err(format!("Number literal '{}' can't end with a '_' separator", literal));
+    }
+    let digits: String = text.chars().filter(|ch| *ch != '_').collect();
+
+    let (radix, radix_name, body) = match slice_str(&digits, 0, 2) {
+        "0x" | "0X" => (16, "hexadecimal", slice_str(&digits, 2, usize::MAX)),
+        "0b" | "0B" => (2, "binary", slice_str(&digits, 2, usize::MAX)),
+        "0o" | "0O" => (8, "octal", slice_str(&digits, 2, usize::MAX)),
+        _ => (10, "decimal", digits.as_str()),
+    };
+
+    let is_float = radix == 10 && body.contains(|ch| ch == '.' || ch == 'e' || ch == 'E');
+    if is_float {
+        if let Some(ch) = body.chars().find(|ch| !ch.is_ascii_digit() && !".eE+-".contains(*ch)) {
+            return Err(format!("Invalid character '{}' in float literal '{}'", ch, literal));
+        }
+        let value = match body.parse::<f64>() {
+            Ok(v) => v,
+            Err(_) => return Err(format!("Malformed float literal '{}'", literal)),
+        };
+        if value.is_infinite() {
+            return Err(format!(
+                "Float literal '{}' is out of range, Floats are 64-bit with a magnitude of at most {:e}",
+                literal,
+                f64::MAX
+            ));
+        }
+        let mantissa = body.split(|ch| ch == 'e' || ch == 'E').next().unwrap_or("");
+        if value == 0.0 && mantissa.contains(|ch: char| ch.is_ascii_digit() && ch != '0') {
+            return Err(format!(
+                "Float literal '{}' is too small and rounds to zero, Floats are 64-bit with a magnitude of at least {:e}",
+                literal,
+                f64::MIN_POSITIVE * f64::EPSILON
+            ));
+        }
+        return Ok(Literal::Float(FloatVal { value: if negative { -value } else { value } }));
+    }
+
+    if body.is_empty() {
+        return Err(format!("Missing digits in {} literal '{}'", radix_name, literal));
+    }
+    if let Some(ch) = body.chars().find(|ch| !ch.is_digit(radix)) {
+        return Err(format!("Invalid digit '{}' in {} literal '{}'", ch, radix_name, literal));
+    }
+
+    // Parse the magnitude wider than i64 so i64::MIN and overflow can both be checked
+    let magnitude = match u128::from_str_radix(body, radix) {
+        Ok(m) => m as i128,
+        Err(_) => return Err(int_out_of_range(&literal)),
+    };
+    let value = if negative { -magnitude } else { magnitude };
+    if value < i64::MIN as i128 || value > i64::MAX as i128 {
+        return Err(int_out_of_range(&literal));
+    }
+    return Ok(Literal::Number(NumberVal { value: value as i64 }));
+}
+
+pub fn register_methods(table: &mut MethodTable) {
+    table.register(MethodReceiver::Number, "abs", |_| FuncSignature::new(vec![], ValueTypes::NumberType), |n, _| {
+        let v = n.get_val::<NumberVal>().value;
+        let value = match v.checked_abs() {
+            Some(a) => a,
+            None => RuntimeError::new(format!("Taking the absolute value of {} overflowed, Numbers are 64-bit signed integers", v)).raise(),
+        };
+        Value::Literal(Literal::Number(NumberVal { value }))
+    });
+    table.register(MethodReceiver::Number, "to_float", |_| FuncSignature::new(vec![], ValueTypes::FloatType), |n, _| {
+        Value::Literal(Literal::Float(FloatVal { value: n.get_val::<NumberVal>().value as f64 }))
+    });
+}
@@ -0,0 +1,88 @@
+use crate::eight::common::AccessibleValue;
+use crate::eight::common::running::error::RuntimeError;
+use crate::eight::literals::basic::map::HashKey;
+use crate::eight::literals::basic::bool::BoolVal;
+use crate::eight::literals::basic::functions::signature::FuncSignature;
+use crate::eight::literals::basic::number::NumberVal;
+use crate::eight::literals::Literal;
+use crate::eight::values::methods::{MethodReceiver, MethodTable};
+use crate::eight::values::{Value, ValueTypes};
+use std::collections::HashMap;
+
+#[derive(Debug)]
+pub struct Set {
+    pub(crate) entries: Vec<Value>, // Kept in insertion order, so iterating and printing is stable
+    pub(crate) index: HashMap<HashKey, usize>,
+    pub(crate) typ: ValueTypes,
+}
+
+
+impl Set {
+    pub fn new(typ: ValueTypes) -> Self {
+        Set {
+            entries: Vec::new(),
+            index: HashMap::new(),
+            typ,
+        }
+    }
+
+    pub fn insert(&mut self, val: Value) {
+        if self.entries.is_empty() && self.typ == ValueTypes::NoneType {
+            // An empty 'set([])' takes its type from the first element
+            self.typ = ValueTypes::convert_to_type(&val);
+        }
+        if !self.contains(&val) {
+            self.index.insert(HashKey(val.copy()), self.entries.len());
+            self.entries.push(val);
+        }
+    }
+
+    pub fn contains(&self, val: &Value) -> bool {
+        return self.index.contains_key(&HashKey(val.copy()));
+    }
+
+    pub fn eq(&self, other: &Set) -> bool {
+        return self.entries.len() == other.entries.len() && self.entries.iter().all(|v| other.contains(v));
+    }
+
+    pub fn copy(&self) -> Self {
+        let mut s = Set::new(self.typ.clone());
+        for v in &self.entries {
+            s.insert(v.copy());
+        }
+        return s;
+    }
+}
+
+impl AccessibleValue for Set {
+    fn implicit_cast_to<'a>(val: &'a Value) -> &'a Self {
+        match val {
+            Value::SetVal(v) => v,
+            o => RuntimeError::new(format!("Unable to implicitly cast {:?} to a Set value", o)).raise(),
+        }
+    }
+
+    fn explicit_cast_to<'a>(val: &'a Value) -> Self {
+        // A List keeps its first copy of every element
+        return match val {
+            Value::SetVal(s) => s.copy(),
+            Value::ListVal(l) => {
+                let mut s = Set::new(l.typ.clone());
+                for v in &l.data {
+                    s.insert(v.copy());
+                }
+                s
+            }
+            o => RuntimeError::new(format!("Cannot cast {:?} to a Set", o)).raise(),
+        };
+    }
+}
+
+pub fn register_methods(table: &mut MethodTable) {
+    table.register(MethodReceiver::Set, "len", |_| FuncSignature::new(vec![], ValueTypes::NumberType), |s, _| {
+        Value::Literal(Literal::Number(NumberVal { value: s.get_val::<Set>().entries.len() as i64 }))
+    });
+    table.register(MethodReceiver::Set, "contains", |t| FuncSignature::new(vec![t.elem_type()], ValueTypes::BooleanType), |s, args| {
+        Value::Literal(Literal::Boolean(BoolVal { value: s.get_val::<Set>().contains(&args[0]) }))
+    });
+}
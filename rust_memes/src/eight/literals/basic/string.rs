@@ -1,11 +1,19 @@
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::parsing::ParsableLiteral;
 use crate::eight::common::tokenizing::symbols::Symbols;
 use crate::eight::common::AccessibleValue;
+use crate::eight::common::running::error::RuntimeError;
 use crate::eight::expressions::secondary::callfunc::CallFunc;
 use crate::eight::literals::basic::functions::args::FuncArgs;
 use crate::eight::literals::{Literal};
-use crate::eight::values::{BasicValue, Value};
+use crate::eight::literals::basic::bool::BoolVal;
+use crate::eight::literals::basic::functions::signature::FuncSignature;
+use crate::eight::literals::basic::list::List;
+use crate::eight::literals::basic::number::NumberVal;
+use crate::eight::values::methods::{MethodReceiver, MethodTable};
+use crate::eight::values::{BasicValue, Value, ValueTypes};
+use std::hash::{Hash, Hasher};
 
 
 #[derive(Debug)]
@@ -31,22 +39,26 @@ impl BasicValue for StringVal {
                         self.value == b.value
                     },
                     o => {
-                        panic!("Runtime exception cannot equate {:?} and {:?}", self, o);
+                        RuntimeError::new(format!("Cannot equate {:?} and {:?}", self, o)).raise();
                     }
                 }
             },
             o => {
-                panic!("Runtime exception cannot equate {:?} and {:?}", self, o);
+                RuntimeError::new(format!("Cannot equate {:?} and {:?}", self, o)).raise();
             }
         }
     }
+
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
 }
 
 impl AccessibleValue for StringVal {
     fn implicit_cast_to(val: &Value) -> &Self {
         return match val {
             Value::Literal(Literal::String(s)) => s,
-            _ => panic!("Runtime Exception: Cannot cast {:?} to String!", val),
+            _ => RuntimeError::new(format!("Cannot cast {:?} to String!", val)).raise(),
         };
     }
 
@@ -60,11 +72,42 @@ impl AccessibleValue for StringVal {
                     Literal::Number(n) => {
                         StringVal{ value: n.value.clone().to_string() }
                     },
+                    Literal::Float(f) => {
+                        StringVal{ value: f.to_display() }
+                    },
                     Literal::Boolean(b) => {
                         StringVal{ value: b.value.clone().to_string() }
                     }
                 }
             },
+            Value::ListVal(l) => {
+                // '[1, 2, 3]', strings are quoted so their commas can't be confused with the separators
+                let elems: Vec<String> = l.data.iter().map(display_elem).collect();
+                StringVal{ value: format!("[{}]", elems.join(", ")) }
+            },
+            Value::MapVal(m) => {
+                let elems: Vec<String> = m.entries.iter()
+                    .map(|(k, v)| format!("{}: {}", display_elem(k), display_elem(v)))
+                    .collect();
+                StringVal{ value: format!("{{{}}}", elems.join(", ")) }
+            },
+            Value::SetVal(s) => {
+                let elems: Vec<String> = s.entries.iter().map(display_elem).collect();
+                StringVal{ value: format!("set([{}])", elems.join(", ")) }
+            },
+            Value::StructVal(s) => {
+                let fields: Vec<String> = s.fields.iter()
+                    .map(|(n, v)| format!("{}: {}", n, display_elem(v)))
+                    .collect();
+                StringVal{ value: format!("{} {{ {} }}", s.name, fields.join(", ")) }
+            },
+            Value::EnumVal(e) if e.payload.is_empty() => {
+                StringVal{ value: format!("{}::{}", e.name, e.variant) }
+            },
+            Value::EnumVal(e) => {
+                let elems: Vec<String> = e.payload.iter().map(display_elem).collect();
+                StringVal{ value: format!("{}::{}({})", e.name, e.variant, elems.join(", ")) }
+            },
             o => {
                 StringVal{ value: format!("{:?}", o)}
             }
@@ -72,18 +115,57 @@ impl AccessibleValue for StringVal {
     }
 }
 
+pub fn display_elem(val: &Value) -> String {
+    return match val {
+        Value::Literal(Literal::String(s)) => format!("{:?}", s.value),
+        o => StringVal::explicit_cast_to(o).value,
+    };
+}
+
 impl ParsableLiteral for StringVal {
-    fn parse<'a>(context: &mut Context) -> Option<Literal> {
-        return match context.get() {
+    fn parse<'a>(context: &mut Context) -> ParseResult<Option<Literal>> {
+        return Ok(match context.get()? {
             Symbols::StringLiteral(s) => {
                 context.increment();
                 Some(Literal::String(StringVal { value: s }))
             }
             _ => None,
-        };
+        });
     }
 
     fn create_class_func_call<'a>(name: &str, args: FuncArgs) -> Result<CallFunc, String> {
         todo!()
     }
 }
+
+pub fn register_methods(table: &mut MethodTable) {
+    // 'len' counts chars, the same way indexing does
+    table.register(MethodReceiver::String, "len", |_| FuncSignature::new(vec![], ValueTypes::NumberType), |s, _| {
+        Value::Literal(Literal::Number(NumberVal { value: s.get_val::<StringVal>().value.chars().count() as i64 }))
+    });
+    table.register(MethodReceiver::String, "upper", |_| FuncSignature::new(vec![], ValueTypes::StringType), |s, _| {
+        Value::Literal(Literal::String(StringVal { value: s.get_val::<StringVal>().value.to_uppercase() }))
+    });
+    table.register(MethodReceiver::String, "lower", |_| FuncSignature::new(vec![], ValueTypes::StringType), |s, _| {
+        Value::Literal(Literal::String(StringVal { value: s.get_val::<StringVal>().value.to_lowercase() }))
+    });
+    table.register(MethodReceiver::String, "trim", |_| FuncSignature::new(vec![], ValueTypes::StringType), |s, _| {
+        Value::Literal(Literal::String(StringVal { value: s.get_val::<StringVal>().value.trim().to_string() }))
+    });
+    table.register(MethodReceiver::String, "contains", |_| FuncSignature::new(vec![ValueTypes::StringType], ValueTypes::BooleanType), |s, args| {
+        let found = s.get_val::<StringVal>().value.contains(args[0].get_val::<StringVal>().value.as_str());
+        Value::Literal(Literal::Boolean(BoolVal { value: found }))
+    });
+    table.register(
+        MethodReceiver::String,
+        "split",
+        |_| FuncSignature::new(vec![ValueTypes::StringType], ValueTypes::ListType(Box::new(ValueTypes::StringType))),
+        |s, args| {
+            let sep = args[0].get_val::<StringVal>().value.as_str();
+            let data = s.get_val::<StringVal>().value.split(sep)
+                .map(|p| Value::Literal(Literal::String(StringVal { value: p.to_string() })))
+                .collect();
+            Value::ListVal(List { data, typ: ValueTypes::StringType })
+        },
+    );
+}
@@ -0,0 +1,193 @@
+use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::running::interpreter::EightInterpreter;
+use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::running::error::RuntimeError;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::expressions::primary::funcdecl::parse_name;
+use crate::eight::expressions::secondary::{parse_secondary_expression, RunnableSecondaryExpression, SecondaryExpression, TypedSecondaryExpression};
+use crate::eight::values::{BasicValue, Value, ValueTypes};
+use log::debug;
+
+#[derive(Debug, Clone)]
+pub struct StructDef {
+    pub(crate) name: String,
+    pub(crate) fields: Vec<(String, ValueTypes)>, // In declaration order, values are stored the same way
+}
+
+#[derive(Debug)]
+pub struct StructVal {
+    pub(crate) name: String,
+    pub(crate) fields: Vec<(String, Value)>,
+}
+
+#[derive(Debug)]
+pub struct StructExpression {
+    pub(crate) name: String,
+    pub(crate) fields: Vec<(String, SecondaryExpression)>, // In declaration order, not the order they're written in
+}
+
+
+impl StructDef {
+    pub fn field_type(&self, field: &str) -> Option<&ValueTypes> {
+        return self.fields.iter().find(|(n, _)| n == field).map(|(_, t)| t);
+    }
+
+    pub fn field_names(&self) -> String {
+        let names: Vec<String> = self.fields.iter().map(|(n, _)| format!("'{}'", n)).collect();
+        return names.join(", ");
+    }
+}
+
+impl StructVal {
+    pub fn get(&self, field: &str) -> &Value {
+        match self.fields.iter().find(|(n, _)| n == field) {
+            Some((_, v)) => v,
+            None => {
+                RuntimeError::new(format!("'{}' has no field '{}'", self.name, field)).raise();
+            }
+        }
+    }
+
+    pub fn get_mut(&mut self, field: &str) -> &mut Value {
+        let name = &self.name;
+        match self.fields.iter_mut().find(|(n, _)| n == field) {
+            Some((_, v)) => v,
+            None => {
+                RuntimeError::new(format!("'{}' has no field '{}'", name, field)).raise();
+            }
+        }
+    }
+
+    pub fn eq(&self, other: &StructVal) -> bool {
+        return self.name == other.name
+            && self.fields.iter().zip(other.fields.iter()).all(|((_, a), (_, b))| a.eq(b));
+    }
+
+    pub fn copy(&self) -> Self {
+        StructVal {
+            name: self.name.clone(),
+            fields: self.fields.iter().map(|(n, v)| (n.clone(), v.copy())).collect(),
+        }
+    }
+}
+
+impl StructExpression {
+    pub fn parse(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+        // '<Name> { <field>: <expr>, ... }', every field has to be given exactly once
+        let def = match context.get_safe_multiple(2).as_slice() {
+            [Ok(Symbols::LiteralSymb(name)), Ok(Symbols::CurlyOpen)] => match context.structs.get(name) {
+                Some(def) => def.clone(),
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        debug!("Found construction of struct '{}'", def.name);
+        let start_idx = context.get_index();
+        context.increment(); // past the name
+        context.increment(); // past '{'
+
+        let mut given: Vec<(String, SecondaryExpression)> = Vec::new();
+        loop {
+            if context.get()? == Symbols::CurlyClose {
+                context.increment();
+                break;
+            }
+
+            let field_idx = context.get_index();
+            let field = parse_name(context, "field name")?;
+            let expected = match def.field_type(field.as_str()) {
+                Some(t) => t.clone(),
+                None => {
+                    context.jump(field_idx);
+                    return Err(context.error(format!("'{}' has no field '{}'", def.name, field))
+                        .with_note(format!("the fields are {}", def.field_names())));
+                }
+            };
+            if given.iter().any(|(n, _)| n == &field) {
+                context.jump(field_idx);
+                return Err(context.error(format!("Field '{}' is given more than once", field)));
+            }
+            match context.get()? {
+                Symbols::Colon => context.increment(),
+                o => {
+                    return Err(context.error(format!("Expected ':' and a value after field '{}', found '{}'", field, o.to_str())));
+                }
+            }
+
+            let val_idx = context.get_index();
+            let val = parse_secondary_expression(context)?;
+            match val.get_type() {
+                Ok(t) if expected.accepts(&t) => (),
+                Ok(t) => {
+                    return Err(Diagnostic::error(
+                        format!("Mismatched types, field '{}' of '{}' is {} but found {}", field, def.name, expected, t),
+                        context.span_since(val_idx),
+                    ));
+                }
+                Err(s) => {
+                    return Err(Diagnostic::error(format!("Error getting type of field '{}', '{}'", field, s), context.span_since(val_idx)));
+                }
+            }
+            given.push((field, val));
+
+            match context.get()? {
+                Symbols::Comma => context.increment(),
+                Symbols::CurlyClose => {
+                    context.increment();
+                    break;
+                }
+                o => {
+                    return Err(context.error(format!("Expected ',' or '}}' in '{}', found '{}'", def.name, o.to_str())));
+                }
+            }
+        }
+
+        let missing: Vec<String> = def.fields.iter()
+            .filter(|(n, _)| !given.iter().any(|(g, _)| g == n))
+            .map(|(n, _)| format!("'{}'", n))
+            .collect();
+        if !missing.is_empty() {
+            return Err(Diagnostic::error(format!("Missing field(s) {} in '{}'", missing.join(", "), def.name), context.span_since(start_idx)));
+        }
+
+        let mut fields = Vec::new();
+        for (name, _) in &def.fields {
+            let idx = given.iter().position(|(g, _)| g == name).unwrap();
+            fields.push(given.remove(idx));
+        }
+        return Ok(Some(SecondaryExpression::StructExpr(StructExpression {
+            name: def.name,
+            fields,
+        })));
+    }
+}
+
+impl TypedSecondaryExpression for StructExpression {
+    fn get_type(&self) -> Result<ValueTypes, String> {
+        Ok(ValueTypes::StructType(self.name.clone()))
+    }
+
+    fn get_references(&self) -> Vec<RelationEntry> {
+        let mut rr = Vec::new();
+        for (_, ex) in &self.fields {
+            rr.append(&mut ex.get_references());
+        }
+        return rr;
+    }
+}
+
+impl RunnableSecondaryExpression for StructExpression {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
+        let mut fields = Vec::new();
+        for (name, ex) in &self.fields {
+            let m = interpreter.step(ex);
+            fields.push((name.clone(), interpreter.copy_val(m)));
+        }
+        interpreter.add_val_to_stack(Value::StructVal(StructVal {
+            name: self.name.clone(),
+            fields,
+        }))
+    }
+}
@@ -1,5 +1,6 @@
 use crate::eight::common::AccessibleValue;
 use crate::eight::common::parsing::ast::RelationEntry;
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::tokenizing::symbols::Symbols;
 use crate::eight::common::running::interpreter::EightInterpreter;
@@ -35,24 +36,24 @@ impl TypedSecondaryExpression for VariableTokenRef {
 }
 
 impl RunnableSecondaryExpression for VariableTokenRef {
-    fn run_secondary_expr(mut self, interpreter: &mut EightInterpreter) -> MemRef {
+    fn run_secondary_expr(&self, interpreter: &mut EightInterpreter) -> MemRef {
         interpreter.get_val_from_frame(self.varname.as_str())
     }
 }
 
 impl VariableTokenRef {
-    pub fn parse<'a>(context: &mut Context) -> Option<SecondaryExpression> {
-        return match context.get() {
+    pub fn parse<'a>(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+        return Ok(match context.get()? {
             Symbols::LiteralSymb(v) => {
                 debug!("found literal '{}'", v);
                 for vv in context.get_varnames() {
                     trace!("Checking '{}' == '{}'", vv, v);
                     if vv == v {
                         context.increment();
-                        return Some(SecondaryExpression::Reference(VariableTokenRef {
+                        return Ok(Some(SecondaryExpression::Reference(VariableTokenRef {
                             varname: v.clone(),
                             typ: context.get_vartype(v.as_str()),
-                        }));
+                        })));
                     }
                 }
                 None
@@ -61,6 +62,6 @@ impl VariableTokenRef {
                 debug!("Found other symb '{:?}'", o);
                 None
             }
-        };
+        });
     }
 }
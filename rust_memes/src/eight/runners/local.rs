@@ -1,16 +1,444 @@
 use crate::eight::Expression;
 // use crate::eight::RunnableExpression;
 
+use crate::eight::common::running::error::RuntimeError;
 use crate::eight::common::running::interpreter::{EightInterpreter};
+use crate::eight::common::running::output::OutputSink;
 use log::debug;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
 
-pub fn run(mut exprs: Vec<Vec<Expression>>) {
+pub fn run(mut exprs: Vec<Vec<Expression>>, out: OutputSink, err: OutputSink) -> Result<(), RuntimeError> {
     debug!("-------RUNNING LOCALLY START--------");
+    silence_runtime_error_panics();
 
-    let mut interpreter = EightInterpreter::new();
+    // A RuntimeError unwinds out of the interpreter, dropping it flushes whatever was printed so far
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut interpreter = EightInterpreter::with_sinks(out, err);
 
-    for expr in exprs {
-        interpreter.run(expr);
+        for expr in exprs {
+            interpreter.run(expr);
+        }
+    }));
+
+    return match res {
+        Ok(()) => {
+            debug!("-----RUNNING LOCALLY COMPLETE------");
+            Ok(())
+        }
+        Err(payload) => match payload.downcast::<RuntimeError>() {
+            Ok(e) => {
+                debug!("-----RUNNING LOCALLY FAILED------");
+                Err(*e)
+            }
+            Err(payload) => panic::resume_unwind(payload),
+        },
+    };
+}
+
+pub fn run_captured(exprs: Vec<Vec<Expression>>) -> (String, String) {
+    // Runs with in-memory sinks, returns what the script wrote to (stdout, stderr)
+    let (out, out_buf) = OutputSink::buffer();
+    let (err, err_buf) = OutputSink::buffer();
+    if let Err(e) = run(exprs, out, err) {
+        err_buf.borrow_mut().push_str(format!("{}\n", e).as_str());
+    }
+
+    let captured = (out_buf.borrow().clone(), err_buf.borrow().clone());
+    return captured;
+}
+
+fn silence_runtime_error_panics() {
+    // RuntimeErrors are reported by whoever called run, every other panic keeps the default message
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let default = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if info.payload().downcast_ref::<RuntimeError>().is_none() {
+                default(info);
+            }
+        }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, run_captured};
+    use crate::eight::common::running::error::RuntimeError;
+    use crate::eight::common::running::output::OutputSink;
+    use crate::eight::common::parsing::ast::EightAST;
+    use crate::eight::{start_parse, Expression};
+    use std::cell::RefCell;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+
+    pub fn run_source(code: &str) -> (String, String) {
+        // Parses and runs the code, returning what it wrote to (stdout, stderr)
+        let parsed = match start_parse(String::from("test.8"), String::from(code)) {
+            Ok(p) => p,
+            Err(diagnostics) => panic!("Expected the code to parse, found {:?}", diagnostics),
+        };
+        return run_captured(EightAST::new(parsed).optimize_ast());
+    }
+
+    #[test]
+    fn corpus_scripts_print_what_they_should() {
+        let (out, err) = run_source(include_str!("../../../testing/basic.8"));
+        assert_eq!(out, "Hello World!");
+        assert_eq!(err, "");
+
+        let (out, err) = run_source(include_str!("../../../testing/generic.8"));
+        assert_eq!(out, "e: 11\nf: -1\nd: 'ab'\nc: '  abcde fg h i   '\n");
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn corpus_sketch_does_not_parse() {
+        // testing/test.8 sketches pipeline syntax the parser doesn't have yet, none of it may run
+        let diagnostics = start_parse(String::from("test.8"), String::from(include_str!("../../../testing/test.8"))).unwrap_err();
+        let messages: Vec<&str> = diagnostics.iter().take(3).map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, vec![
+            "Cannot find variable 'env' in this scope",
+            "Cannot assign to undeclared variable 'requirements'",
+            "Cannot find variable 'keras' in this scope",
+        ]);
+    }
+
+    #[test]
+    fn smoke_test_features_together() {
+        // Only checks the features still work side by side, each has its own focused test
+        // Output up to a runtime error is kept, the error itself goes after anything written to stderr
+        let (out, err) = run_source(concat!(
+            "struct Point { x: Number, y: Number }\n",
+            "enum Shape { Dot, Circle(Float) }\n",
+            "func fib(n: Number) -> Number {\n",
+            "    if n < 2 { return n; }\n",
+            "    return fib(n - 1) + fib(n - 2);\n",
+            "}\n",
+            "let p = Point { x: 1, y: 2 };\n",
+            "p.x += 10;\n",
+            "let total = 0;\n",
+            "for i in [1, 2, 3] {\n",
+            "    if i == 2 { continue; }\n",
+            "    total += i;\n",
+            "}\n",
+            "let n = 0;\n",
+            "while true {\n",
+            "    n += 1;\n",
+            "    if n >= 3 { break; }\n",
+            "}\n",
+            "let add = |a: Number, b: Number| a + b;\n",
+            "let m = {\"k\": [1, 2]};\n",
+            "m[\"k\"][1] = 5;\n",
+            "match Shape::Circle(1.5) {\n",
+            "    Shape::Dot => println(\"dot\"),\n",
+            "    Shape::Circle(r) => println(fmt(\"circle \", r)),\n",
+            "}\n",
+            "print(\"a\");\n",
+            "print(\"b\\n\");\n",
+            "eprintln(fmt(\"warn \", !false && 1 <= 2));\n",
+            "println(fmt(fib(10), \" \", p.x, \" \", total, \" \", n, \" \", add(2, 3), \" \", m, \" \", \"h\u{e9}llo\"[1..3], \" \", [1, 2, 3][2]));\n",
+            "let q = [1][p.y];\n",
+            "println(\"unreached\");\n",
+        ));
+        assert_eq!(out, "circle 1.5\nab\n55 11 4 3 5 {\"k\": [1, 5]} \u{e9}l 3\n");
+        assert_eq!(err, "warn true\nRuntime Exception: Index 2 is out of range for a List of length 1\n");
+    }
+
+    #[test]
+    fn runaway_recursion_is_a_runtime_error() {
+        let (out, err) = run_source("func f(n: Number) -> Number { return f(n + 1); }\nprintln(\"start\");\nprintln(fmt(f(0)));\n");
+        assert_eq!(out, "start\n");
+        assert_eq!(err, "Runtime Exception: Calling 'f' exceeded the maximum call depth of 256\n");
+    }
+
+    #[test]
+    fn arithmetic_errors_are_runtime_errors() {
+        let cases = [
+            ("let a = 5 / 0;", "Cannot divide 5 by zero!"),
+            ("let a = 5 % 0;", "Cannot divide 5 by zero!"),
+            ("let a = 2 ** 100;", "Operation '**' on 2 and 100 overflowed, Numbers are 64-bit signed integers"),
+            ("let a = 2 ** (0 - 1);", "Cannot raise 2 to a value less than zero! val: -1"),
+            ("let a = 9223372036854775807 + 1;", "Operation '+' on 9223372036854775807 and 1 overflowed, Numbers are 64-bit signed integers"),
+            ("let a = -9223372036854775807 - 1;\nlet b = -a;", "Negating -9223372036854775808 overflowed, Numbers are 64-bit signed integers"),
+        ];
+        for (code, message) in cases.iter() {
+            let (out, err) = run_source(code);
+            assert_eq!(out, "", "{}", code);
+            assert_eq!(err, format!("Runtime Exception: {}\n", message), "{}", code);
+        }
+    }
+
+    #[test]
+    fn compound_index_assignment_evaluates_the_index_once() {
+        let (out, err) = run_source(concat!(
+            "func f() -> Number { println(\"called\"); return 0; }\n",
+            "let xs = [1, 2];\n",
+            "xs[f()] += 5;\n",
+            "let m = {\"a\": 1};\n",
+            "m[\"a\"] *= 3;\n",
+            "let ys = [[1.5]];\n",
+            "ys[0][f()] -= 0.5;\n",
+            "println(fmt(xs, \" \", m, \" \", ys));\n",
+        ));
+        assert_eq!(out, "called\ncalled\n[6, 2] {\"a\": 3} [[1.0]]\n");
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn match_as_a_value_and_with_expression_arms() {
+        let (out, err) = run_source(concat!(
+            "enum S { A, B(Number) }\n",
+            "let s = S::B(4);\n",
+            "let a = match s { S::A => 1, S::B(n) => n * 2 };\n",
+            "println(fmt(a));\n",
+            "match s {\n",
+            "    S::A => println(\"a\"),\n",
+            "    S::B(n) => println(fmt(\"b \", n)),\n",
+            "}\n",
+            "match S::A { S::A => println(\"first\"), _ => { println(\"other\"); } }\n",
+            "let names = match s { S::A => [], _ => [\"x\"] };\n",
+            "println(fmt(names));\n",
+        ));
+        assert_eq!(out, "8\nb 4\nfirst\n[\"x\"]\n");
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn methods_give_new_values() {
+        let (out, err) = run_source(concat!(
+            "let xs = [3, 1, 2];\n",
+            "let ys = xs.reverse();\n",
+            "let m = {\"a\": 1, \"b\": 2};\n",
+            "let s = set([1, 2, 2]);\n",
+            "println(fmt(xs, \" \", ys, \" \", ys.contains(3), \" \", m.keys(), \" \", m.values(), \" \", s.len(), \" \", s.contains(4)));\n",
+            "println(fmt((0 - 5).abs()));\n",
+            "let a = -9223372036854775807 - 1;\n",
+            "println(fmt(a.abs()));\n",
+        ));
+        assert_eq!(out, "[3, 1, 2] [2, 1, 3] true [\"a\", \"b\"] [1, 2] 2 false\n5\n");
+        assert_eq!(err, "Runtime Exception: Taking the absolute value of -9223372036854775808 overflowed, Numbers are 64-bit signed integers\n");
+    }
+
+    #[test]
+    fn namespace_values_can_be_bound_passed_and_captured() {
+        let open = "let d = Database::csv(\"testing/iris.csv\");\n";
+        let (out, err) = run_source(format!("{}let e = d;\nprintln(fmt(e.rows().len(), \" \", d.rows().len()));\n", open).as_str());
+        assert_eq!((out.as_str(), err.as_str()), ("100 100\n", ""));
+
+        let (out, err) = run_source(format!("{}func count(db: Database::CSV) -> Number {{ return db.rows().len(); }}\nprintln(fmt(count(d)));\n", open).as_str());
+        assert_eq!((out.as_str(), err.as_str()), ("100\n", ""));
+
+        let (out, err) = run_source(format!("{}let count = || d.rows().len();\nprintln(fmt(count()));\n", open).as_str());
+        assert_eq!((out.as_str(), err.as_str()), ("100\n", ""));
+    }
+
+    #[test]
+    fn operators_group_by_precedence_and_associativity() {
+        let (out, err) = run_source(concat!(
+            "println(fmt(8 / 2 * 2, \" \", 8 / 2 * 2 == 8));\n",
+            "println(fmt(1 - 2 + 3, \" \", 1 - 2 + 3 == 2));\n",
+            "println(fmt(2 ** 3 ** 2, \" \", 2 ** 3 ** 2 == 512));\n",
+            "println(fmt(-2 ** 2));\n",
+        ));
+        assert_eq!(out, "8 true\n2 true\n512 true\n-4\n");
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn loops_iterate_the_collection_as_it_was_when_they_started() {
+        let (out, err) = run_source(concat!(
+            "let m = {\"a\": 1, \"b\": 2};\n",
+            "for k in m { m[fmt(k, \"x\")] = 1; }\n",
+            "let xs = [1, 2, 3];\n",
+            "let seen = 0;\n",
+            "for x in xs { xs = [1]; seen += x; }\n",
+            "let s = set([1, 2]);\n",
+            "for v in s { s = set([7]); seen += v; }\n",
+            "println(fmt(m.len(), \" \", xs, \" \", seen));\n",
+        ));
+        assert_eq!(out, "4 [1] 9\n");
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn reducing_an_empty_list_is_a_runtime_error() {
+        let (out, err) = run_source(concat!(
+            "let add = |a: Number, b: Number| a + b;\n",
+            "let xs = [1, 2, 3];\n",
+            "println(fmt(reduce(add, xs), \" \", reduce(add, [], 10)));\n",
+            "let e = xs[0..0];\n",
+            "println(fmt(reduce(add, e)));\n",
+        ));
+        assert_eq!(out, "6 10\n");
+        assert_eq!(err, "Runtime Exception: Cannot reduce an empty List without an initial value\n");
+    }
+
+    #[test]
+    fn logical_operators_skip_the_rhs_once_decided() {
+        let (out, err) = run_source(concat!(
+            "func t(s: String) -> Boolean { print(s); return true; }\n",
+            "let a = false && t(\"and \");\n",
+            "let b = true || t(\"or \");\n",
+            "let c = true && t(\"both \");\n",
+            "println(fmt(a, \" \", b, \" \", c, \" \", 1 < 2, \" \", 2 <= 2, \" \", \"b\" > \"a\", \" \", 1.5 >= 2.0, \" \", 1 != 1));\n",
+        ));
+        assert_eq!(out, "both false true true true true true false false\n");
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn values_that_cannot_be_compared_are_runtime_errors() {
+        // A struct's field types aren't known to '==', so a function field is only found when running
+        let (out, err) = run_source(concat!(
+            "struct Op { f: Func(Number) -> Number }\n",
+            "let a = Op { f: |x: Number| x };\n",
+            "println(\"start\");\n",
+            "println(fmt(a == a));\n",
+        ));
+        assert_eq!(out, "start\n");
+        assert!(err.starts_with("Runtime Exception: Unable to equate Function("), "{}", err);
+    }
+
+    fn parse_source(code: &str) -> Vec<Vec<Expression>> {
+        return EightAST::new(start_parse(String::from("test.8"), String::from(code)).unwrap()).optimize_ast();
+    }
+
+    #[test]
+    fn callbacks_see_each_stream_as_it_is_printed() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let (out_seen, err_seen) = (Rc::clone(&seen), Rc::clone(&seen));
+        let out = OutputSink::Callback(Box::new(move |s: &str| out_seen.borrow_mut().push(format!("out {}", s))));
+        let err = OutputSink::Callback(Box::new(move |s: &str| err_seen.borrow_mut().push(format!("err {}", s))));
+
+        let res = run(parse_source("print(\"a\");\neprintln(\"b\");\nprintln(fmt(1 + 1));\n"), out, err);
+        assert_eq!(res, Ok(()));
+        assert_eq!(*seen.borrow(), vec!["out a", "err b\n", "out 2\n"]);
+    }
+
+    struct ClosedPipe;
+
+    impl Write for ClosedPipe {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "pipe closed"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn failing_to_write_output_is_a_runtime_error() {
+        let (err, err_buf) = OutputSink::buffer();
+        let res = run(parse_source("eprintln(\"before\");\nprintln(\"lost\");\neprintln(\"after\");\n"), OutputSink::Writer(Box::new(ClosedPipe)), err);
+        assert_eq!(res, Err(RuntimeError::new(String::from("Failed to write output, pipe closed"))));
+        assert_eq!(*err_buf.borrow(), "before\n");
+    }
+
+    #[test]
+    fn maps_and_sets_insert_look_up_and_iterate() {
+        let (out, err) = run_source(concat!(
+            "let m = {\"b\": 2};\n",
+            "insert(m, \"a\", 1);\n",
+            "m[\"b\"] = 20;\n",
+            "let s = set([3, 1, 3]);\n",
+            "insert(s, 2);\n",
+            "insert(s, 1);\n",
+            "let total = 0;\n",
+            "for x in s { total += x; }\n",
+            "println(fmt(m, \" \", keys(m), \" \", values(m), \" \", contains(m, \"a\"), \" \", s, \" \", total, \" \", {\"a\": 1} == {\"a\": 1}));\n",
+            "println(fmt(m[\"c\"]));\n",
+        ));
+        assert_eq!(out, "{\"b\": 20, \"a\": 1} [\"b\", \"a\"] [20, 1] true set([3, 1, 2]) 6 true\n");
+        assert_eq!(err, "Runtime Exception: Key \"c\" is not in the Map\n");
+    }
+
+    #[test]
+    fn unary_operators_negate_and_invert() {
+        let (out, err) = run_source("let n = 3;\nprintln(fmt(!false, \" \", -(2.5), \" \", !(1 < 2) || !!true, \" \", -n, \" \", - -n));\n");
+        assert_eq!(out, "true -2.5 true -3 3\n");
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn loops_break_continue_and_bind_each_iteration_fresh() {
+        // Every closure made in the loop keeps the 'i' of its own iteration
+        let (out, err) = run_source(concat!(
+            "let fs: Map<Number, Func() -> Number> = {};\n",
+            "let out = \"\";\n",
+            "for i in 0..5 {\n",
+            "    if i == 1 { continue; }\n",
+            "    if i == 4 { break; }\n",
+            "    out = fmt(out, i);\n",
+            "    insert(fs, i, || i * 10);\n",
+            "}\n",
+            "let n = 0;\n",
+            "while n < 10 {\n",
+            "    n += 1;\n",
+            "    if n % 2 == 0 { continue; }\n",
+            "    if n > 6 { break; }\n",
+            "    out = fmt(out, \" w\", n);\n",
+            "}\n",
+            "let g = fs[2];\n",
+            "let h = fs[3];\n",
+            "println(fmt(out, \" \", n, \" \", g(), \" \", h()));\n",
+        ));
+        assert_eq!(out, "023 w1 w3 w5 7 20 30\n");
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn closures_capture_when_made_and_can_be_passed() {
+        // 'scale' keeps the k it saw when it was made
+        let (out, err) = run_source(concat!(
+            "let k = 3;\n",
+            "let scale = |x: Number| x * k;\n",
+            "k = 100;\n",
+            "func apply(f: Func(Number) -> Number, v: Number) -> Number { return f(v); }\n",
+            "let doubled = map(|x: Number| x * 2, [1, 2, 3]);\n",
+            "let sum = reduce(|a: Number, b: Number| a + b, doubled, 1);\n",
+            "println(fmt(scale(2), \" \", apply(scale, 5), \" \", doubled, \" \", sum));\n",
+        ));
+        assert_eq!(out, "6 15 [2, 4, 6] 13\n");
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn bare_expression_statements_release_their_value() {
+        // The working stack has 1000 slots, keeping every discarded value would run out of them
+        let (out, err) = run_source(concat!(
+            "func bump(n: Number) -> Number { print(\".\"); return n + 1; }\n",
+            "bump(1);\n",
+            "Database::csv(\"testing/iris.csv\");\n",
+            "for i in 0..2000 { bump(i); [i, i].len(); i + 1; }\n",
+            "println(\"done\");\n",
+        ));
+        assert_eq!(out, format!("{}done\n", ".".repeat(2001)));
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn print_functions_pick_the_stream_and_the_newline() {
+        let (out, err) = run_source("print(\"a\");\nprint(\"b\");\nprintln(\"c\");\neprint(\"d\");\neprintln(\"e\");\nprintln(fmt(1, \" \", 2.0));\n");
+        assert_eq!(out, "abc\n1 2.0\n");
+        assert_eq!(err, "de\n");
+    }
+
+    #[test]
+    fn struct_fields_are_read_and_written_through_copies() {
+        // 'p' is a copy of 'l.from', so changing it leaves 'l' alone
+        let (out, err) = run_source(concat!(
+            "struct Point { x: Number, y: Number }\n",
+            "struct Line { from: Point, to: Point }\n",
+            "let l = Line { from: Point { x: 1, y: 2 }, to: Point { x: 3, y: 4 } };\n",
+            "l.to.y = 40;\n",
+            "l.from.x += 5;\n",
+            "let p = l.from;\n",
+            "p.y = 0;\n",
+            "println(fmt(l.from.x, \" \", l.from.y, \" \", l.to.y, \" \", p.y, \" \", l.to == Point { x: 3, y: 40 }));\n",
+        ));
+        assert_eq!(out, "6 2 40 0 true\n");
+        assert_eq!(err, "");
     }
-    debug!("-----RUNNING LOCALLY COMPLETE------");
 }
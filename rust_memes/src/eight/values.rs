@@ -1,4 +1,9 @@
 use crate::eight::common::AccessibleValue;
+use crate::eight::common::diagnostics::{Diagnostic, ParseResult};
+use crate::eight::common::parsing::context::Context;
+use crate::eight::common::tokenizing::symbols::Symbols;
+use crate::eight::common::tokenizing::token::Span;
+use crate::eight::common::running::error::RuntimeError;
 use crate::eight::expressions::primary::noop::NoOp;
 use crate::eight::literals::basic::functions::Func;
 use crate::eight::literals::basic::functions::signature::FuncSignature;
@@ -6,19 +11,34 @@ use crate::eight::literals::Literal;
 use namespaces::database::{CSV, JSON};
 use namespaces::model::Model;
 use crate::eight::literals::basic::list::List;
+use crate::eight::literals::basic::map::Map;
+use crate::eight::literals::basic::set::Set;
+use crate::eight::literals::basic::structs::StructVal;
+use crate::eight::literals::basic::enums::EnumVal;
+use std::fmt::{Display, Formatter};
+use std::hash::Hasher;
+use std::rc::Rc;
 use crate::eight::values::namespaces::{NamespaceValue, NamespaceValueTypes};
 
+pub mod methods;
 pub mod namespaces;
 
 pub trait BasicValue {
     fn eq(&self, other: &Value) -> bool;
+
+    // Values that are equal have to hash the same, only hashable types can be Map keys and Set elements
+    fn hash<H: Hasher>(&self, state: &mut H);
 }
 
 #[derive(Debug)]
 pub enum Value {
     Literal(Literal),
-    NamespaceVal(NamespaceValue),
+    NamespaceVal(Rc<NamespaceValue>), // Shared by every copy, they stand for an external resource
     ListVal(List),
+    MapVal(Map),
+    SetVal(Set),
+    StructVal(StructVal),
+    EnumVal(EnumVal),
     Function(Func),
     None(NoOp),
 }
@@ -29,8 +49,49 @@ impl BasicValue for Value {
             Value::Literal(l) => {
                 l.eq(other)
             },
+            Value::ListVal(l) => {
+                match other {
+                    Value::ListVal(r) => l.data.len() == r.data.len() && l.data.iter().zip(r.data.iter()).all(|(a, b)| a.eq(b)),
+                    o => RuntimeError::new(format!("Unable to equate {:?} and {:?}", self, o)).raise(),
+                }
+            },
+            Value::MapVal(m) => {
+                match other {
+                    Value::MapVal(r) => m.eq(r),
+                    o => RuntimeError::new(format!("Unable to equate {:?} and {:?}", self, o)).raise(),
+                }
+            },
+            Value::SetVal(s) => {
+                match other {
+                    Value::SetVal(r) => s.eq(r),
+                    o => RuntimeError::new(format!("Unable to equate {:?} and {:?}", self, o)).raise(),
+                }
+            },
+            Value::StructVal(s) => {
+                match other {
+                    Value::StructVal(r) => s.eq(r),
+                    o => RuntimeError::new(format!("Unable to equate {:?} and {:?}", self, o)).raise(),
+                }
+            },
+            Value::EnumVal(e) => {
+                match other {
+                    Value::EnumVal(r) => e.eq(r),
+                    o => RuntimeError::new(format!("Unable to equate {:?} and {:?}", self, o)).raise(),
+                }
+            },
             o => {
-                panic!("Runtime exception: unable to equate {:?} and {:?}", self, o);
+                RuntimeError::new(format!("Unable to equate {:?} and {:?}", o, other)).raise();
+            }
+        }
+    }
+
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Value::Literal(l) => {
+                l.hash(state)
+            },
+            o => {
+                RuntimeError::new(format!("Unable to hash {:?}", o)).raise();
             }
         }
     }
@@ -44,6 +105,21 @@ impl Value {
     pub fn get_val<T: AccessibleValue>(&self) -> &T {
         T::implicit_cast_to(self)
     }
+
+    pub fn copy(&self) -> Self {
+        // Namespace values hold external resources, a copy refers to the same one
+        match self {
+            Value::Literal(l) => Value::Literal(l.copy()),
+            Value::ListVal(l) => Value::ListVal(l.copy()),
+            Value::MapVal(m) => Value::MapVal(m.copy()),
+            Value::SetVal(s) => Value::SetVal(s.copy()),
+            Value::StructVal(s) => Value::StructVal(s.copy()),
+            Value::EnumVal(e) => Value::EnumVal(e.copy()),
+            Value::Function(f) => Value::Function(f.copy()),
+            Value::None(_) => Value::gen_noop(),
+            Value::NamespaceVal(n) => Value::NamespaceVal(Rc::clone(n)),
+        }
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -51,22 +127,270 @@ pub enum ValueTypes {
     BooleanType,
     StringType,
     NumberType,
+    FloatType,
     NamespaceValType(NamespaceValueTypes),
     ListType(Box<ValueTypes>),
+    MapType(Box<ValueTypes>, Box<ValueTypes>), // key, value
+    SetType(Box<ValueTypes>),
+    StructType(String), // Name of a declared struct, its fields are looked up in the parse context
+    EnumType(String), // Name of a declared enum, its variants are looked up in the parse context
     FuncType(Box<FuncSignature>),
     NoneType,
 }
 
 impl ValueTypes {
+    pub fn from_type_name(name: &str) -> Option<ValueTypes> {
+        // Names used in type annotations, 'a: Number'
+        return match name {
+            "Boolean" => Some(ValueTypes::BooleanType),
+            "String" => Some(ValueTypes::StringType),
+            "Number" => Some(ValueTypes::NumberType),
+            "Float" => Some(ValueTypes::FloatType),
+            _ => None,
+        };
+    }
+
+    pub fn accepts(&self, other: &ValueTypes) -> bool {
+        // Whether a value of type other can be used where self is expected
+        // An empty list has no element type yet, so it fits any List and any List fits it
+        return match (self, other) {
+            (ValueTypes::ListType(a), ValueTypes::ListType(b)) | (ValueTypes::SetType(a), ValueTypes::SetType(b)) => {
+                a.accepts_elem(b)
+            }
+            (ValueTypes::MapType(ak, av), ValueTypes::MapType(bk, bv)) => {
+                ak.accepts_elem(bk) && av.accepts_elem(bv)
+            }
+            (a, b) => a == b,
+        };
+    }
+
+    fn accepts_elem(&self, other: &ValueTypes) -> bool {
+        return *self == ValueTypes::NoneType || *other == ValueTypes::NoneType || self.accepts(other);
+    }
+
+    pub fn is_unknown_collection(&self) -> bool {
+        // The type of an empty '[]', '{}' or 'set([])', it's refined by the first value assigned
+        return match self {
+            ValueTypes::ListType(t) | ValueTypes::SetType(t) => **t == ValueTypes::NoneType,
+            ValueTypes::MapType(k, v) => **k == ValueTypes::NoneType && **v == ValueTypes::NoneType,
+            _ => false,
+        };
+    }
+
+    pub fn elem_type(&self) -> ValueTypes {
+        // What a List or Set holds and what a Map is keyed by, the values 'for' and 'contains' see
+        return match self {
+            ValueTypes::ListType(t) | ValueTypes::SetType(t) | ValueTypes::MapType(t, _) => t.as_ref().clone(),
+            _ => ValueTypes::NoneType,
+        };
+    }
+
+    pub fn val_type(&self) -> ValueTypes {
+        // What a Map holds for each key
+        return match self {
+            ValueTypes::MapType(_, v) => v.as_ref().clone(),
+            _ => ValueTypes::NoneType,
+        };
+    }
+
+    pub fn is_hashable(&self) -> bool {
+        // Floats are left out, NaN isn't equal to itself
+        return matches!(self, ValueTypes::StringType | ValueTypes::NumberType | ValueTypes::BooleanType | ValueTypes::NoneType);
+    }
+
+    pub fn is_equatable(&self) -> bool {
+        // Functions and external resources have no value to compare, even inside a collection
+        return match self {
+            ValueTypes::FuncType(_) | ValueTypes::NamespaceValType(_) => false,
+            ValueTypes::ListType(t) | ValueTypes::SetType(t) => t.is_equatable(),
+            ValueTypes::MapType(k, v) => k.is_equatable() && v.is_equatable(),
+            _ => true,
+        };
+    }
+
     pub fn convert_to_type(val: &Value) -> ValueTypes {
         return match val {
             Value::Literal(Literal::String(_)) => ValueTypes::StringType,
             Value::Literal(Literal::Number(_)) => ValueTypes::NumberType,
+            Value::Literal(Literal::Float(_)) => ValueTypes::FloatType,
             Value::Literal(Literal::Boolean(_)) => ValueTypes::BooleanType,
             Value::NamespaceVal(v) => ValueTypes::NamespaceValType(NamespaceValue::convert_to_type(v)),
             Value::Function(f) => ValueTypes::FuncType(Box::from(f.sig.clone())),
             Value::None(_) => ValueTypes::NoneType,
-            Value::ListVal(v) => ValueTypes::ListType(Box::from(v.typ.clone()))
+            Value::ListVal(v) => ValueTypes::ListType(Box::from(v.typ.clone())),
+            Value::MapVal(m) => ValueTypes::MapType(Box::from(m.key_typ.clone()), Box::from(m.val_typ.clone())),
+            Value::SetVal(s) => ValueTypes::SetType(Box::from(s.typ.clone())),
+            Value::StructVal(s) => ValueTypes::StructType(s.name.clone()),
+            Value::EnumVal(e) => ValueTypes::EnumType(e.name.clone()),
+        };
+    }
+}
+
+impl Display for ValueTypes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Spelled like a type annotation, 'List<Number>', so diagnostics read like the source
+        return match self {
+            ValueTypes::BooleanType => write!(f, "Boolean"),
+            ValueTypes::StringType => write!(f, "String"),
+            ValueTypes::NumberType => write!(f, "Number"),
+            ValueTypes::FloatType => write!(f, "Float"),
+            ValueTypes::NamespaceValType(n) => write!(f, "{}", n),
+            ValueTypes::ListType(t) => write!(f, "List<{}>", t),
+            ValueTypes::MapType(k, v) => write!(f, "Map<{}, {}>", k, v),
+            ValueTypes::SetType(t) => write!(f, "Set<{}>", t),
+            ValueTypes::StructType(name) | ValueTypes::EnumType(name) => write!(f, "{}", name),
+            ValueTypes::FuncType(sig) => {
+                let args: Vec<String> = sig.get_arglist().iter().map(|t| t.to_string()).collect();
+                match sig.get_output_type() {
+                    ValueTypes::NoneType => write!(f, "Func({})", args.join(", ")),
+                    output => write!(f, "Func({}) -> {}", args.join(", "), output),
+                }
+            }
+            ValueTypes::NoneType => write!(f, "None"), // No value, or the element type of an empty collection
         };
     }
 }
+
+pub fn unknown_elem_error(typ: &ValueTypes, span: Span) -> Diagnostic {
+    // Reading from an empty '[]', '{}' or 'set([])' before its type was filled in would leak NoneType into later errors
+    let (what, example) = match typ {
+        ValueTypes::SetType(_) => ("Set", "let s: Set<Number> = set([]);"),
+        ValueTypes::MapType(_, _) => ("Map", "let m: Map<String, Number> = {};"),
+        _ => ("List", "let xs: List<Number> = [];"),
+    };
+    return Diagnostic::error(format!("The element type of this empty {} is not known yet", what), span)
+        .with_note(format!("assign a non-empty {} to it first, or declare it with a type like '{}'", what, example));
+}
+
+pub fn parse_type(context: &mut Context) -> ParseResult<ValueTypes> {
+    // Parse a type annotation like 'Number', 'List<String>', 'Map<String, Number>', 'Database::CSV' or 'Func(Number) -> Number'
+    let typ = match context.get()? {
+        Symbols::LiteralSymb(name) if name == "Func" => {
+            context.increment();
+            return parse_func_type(context);
+        }
+        Symbols::LiteralSymb(name) if name == "List" || name == "Set" || name == "Map" => {
+            context.increment();
+            return parse_collection_type(context, name.as_str());
+        }
+        Symbols::LiteralSymb(name) if NamespaceValueTypes::is_namespace(name.as_str()) => {
+            return parse_namespace_type(context, name);
+        }
+        Symbols::LiteralSymb(name) if context.structs.contains_key(&name) => ValueTypes::StructType(name),
+        Symbols::LiteralSymb(name) if context.enums.contains_key(&name) => ValueTypes::EnumType(name),
+        Symbols::LiteralSymb(name) => match ValueTypes::from_type_name(name.as_str()) {
+            Some(t) => t,
+            None => {
+                return Err(context.error(format!("Unknown type '{}'", name))
+                    .with_note(String::from("expected one of Number, Float, String, Boolean, List<...>, Map<..., ...>, Set<...>, Func(...), a struct, an enum or a namespace type like Database::CSV")));
+            }
+        },
+        o => {
+            return Err(context.error(format!("Expected a type, found '{}'", o.to_str())));
+        }
+    };
+    context.increment();
+    return Ok(typ);
+}
+
+fn parse_collection_type(context: &mut Context, name: &str) -> ParseResult<ValueTypes> {
+    // '<<type>>' after 'List' or 'Set', '<<key>, <value>>' after 'Map'
+    match context.get()? {
+        Symbols::LessThan => context.increment(),
+        o => {
+            return Err(context.error(format!("Expected '<' and the element type after '{}', found '{}'", name, o.to_str())));
+        }
+    }
+    let elem = parse_type(context)?;
+    let val = if name == "Map" {
+        match context.get()? {
+            Symbols::Comma => context.increment(),
+            o => {
+                return Err(context.error(format!("Expected ',' and the value type after the key type of 'Map', found '{}'", o.to_str())));
+            }
+        }
+        Some(parse_type(context)?)
+    } else {
+        None
+    };
+    match context.get()? {
+        Symbols::GreaterThan => context.increment(),
+        o => {
+            return Err(context.error(format!("Expected '>' to close '{}<', found '{}'", name, o.to_str())));
+        }
+    }
+    return Ok(match (name, val) {
+        ("Map", Some(val)) => ValueTypes::MapType(Box::new(elem), Box::new(val)),
+        ("Set", _) => ValueTypes::SetType(Box::new(elem)),
+        _ => ValueTypes::ListType(Box::new(elem)),
+    });
+}
+
+fn parse_namespace_type(context: &mut Context, namespace: String) -> ParseResult<ValueTypes> {
+    // 'Database::CSV', or just the namespace name when it only holds one type like 'Model'
+    let start_idx = context.get_index();
+    context.increment(); // past the namespace name
+    let member = if context.get_safe() == Ok(Symbols::ClassAccessor) {
+        context.increment();
+        match context.get()? {
+            Symbols::LiteralSymb(member) => {
+                context.increment();
+                Some(member)
+            }
+            o => {
+                return Err(context.error(format!("Expected a type name after '{}::', found '{}'", namespace, o.to_str())));
+            }
+        }
+    } else {
+        None
+    };
+
+    return match NamespaceValueTypes::from_type_name(namespace.as_str(), member.as_deref()) {
+        Some(t) => Ok(ValueTypes::NamespaceValType(t)),
+        None => {
+            let shown = match &member {
+                Some(m) => format!("{}::{}", namespace, m),
+                None => namespace.clone(),
+            };
+            Err(Diagnostic::error(format!("Unknown type '{}'", shown), context.span_since(start_idx))
+                .with_note(String::from("expected one of Database::CSV, Database::JSON, Model or ThreadPool")))
+        }
+    };
+}
+
+fn parse_func_type(context: &mut Context) -> ParseResult<ValueTypes> {
+    // '(<type>, ...) -> <type>' after 'Func', without '->' the func returns nothing
+    match context.get()? {
+        Symbols::ParenOpen => context.increment(),
+        o => {
+            return Err(context.error(format!("Expected '(' and the argument types after 'Func', found '{}'", o.to_str())));
+        }
+    }
+
+    let mut args = Vec::new();
+    if context.get()? == Symbols::ParenClose {
+        context.increment();
+    } else {
+        loop {
+            args.push(parse_type(context)?);
+            match context.get()? {
+                Symbols::Comma => context.increment(),
+                Symbols::ParenClose => {
+                    context.increment();
+                    break;
+                }
+                o => {
+                    return Err(context.error(format!("Expected ',' or ')' in the argument types of 'Func', found '{}'", o.to_str())));
+                }
+            }
+        }
+    }
+
+    let output = if context.get()? == Symbols::Arrow {
+        context.increment(); // past '->'
+        parse_type(context)?
+    } else {
+        ValueTypes::NoneType
+    };
+    return Ok(ValueTypes::FuncType(Box::new(FuncSignature::new(args, output))));
+}
@@ -0,0 +1,92 @@
+use crate::eight::literals::basic::functions::signature::FuncSignature;
+use crate::eight::literals::basic::{list, map, number, set, string};
+use crate::eight::values::namespaces::database::CSV;
+use crate::eight::values::namespaces::stdlib::threads::ThreadPool;
+use crate::eight::values::namespaces::NamespaceValueTypes;
+use crate::eight::values::{Value, ValueTypes};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+
+// Methods read the receiver in place and get copies of the args
+// The receiver is only borrowed, so a method can't change it and gives back a new value instead, 'xs.reverse()'
+pub type MethodFn = fn(&Value, Vec<Value>) -> Value;
+// Builds the signature of the args and output from the receiver type, so 'xs.contains(x)' can use the element type
+pub type MethodSig = fn(&ValueTypes) -> FuncSignature;
+
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub enum MethodReceiver {
+    String,
+    Number,
+    Float,
+    Boolean,
+    List,
+    Map,
+    Set,
+    Namespace(NamespaceValueTypes),
+}
+
+#[derive(Debug)]
+pub struct Method {
+    pub(crate) sig: MethodSig,
+    pub(crate) builtin: MethodFn,
+}
+
+#[derive(Debug)]
+pub struct MethodTable {
+    methods: HashMap<MethodReceiver, HashMap<&'static str, Method>>,
+}
+
+lazy_static! {
+    pub static ref METHODS: MethodTable = MethodTable::builtin();
+}
+
+impl MethodReceiver {
+    pub fn from_type(typ: &ValueTypes) -> Option<MethodReceiver> {
+        return match typ {
+            ValueTypes::StringType => Some(MethodReceiver::String),
+            ValueTypes::NumberType => Some(MethodReceiver::Number),
+            ValueTypes::FloatType => Some(MethodReceiver::Float),
+            ValueTypes::BooleanType => Some(MethodReceiver::Boolean),
+            ValueTypes::ListType(_) => Some(MethodReceiver::List),
+            ValueTypes::MapType(_, _) => Some(MethodReceiver::Map),
+            ValueTypes::SetType(_) => Some(MethodReceiver::Set),
+            ValueTypes::NamespaceValType(t) => Some(MethodReceiver::Namespace(t.clone())),
+            _ => None,
+        };
+    }
+}
+
+impl MethodTable {
+    fn builtin() -> Self {
+        // Every type with methods adds them here
+        let mut table = MethodTable { methods: HashMap::new() };
+        string::register_methods(&mut table);
+        number::register_methods(&mut table);
+        list::register_methods(&mut table);
+        map::register_methods(&mut table);
+        set::register_methods(&mut table);
+        CSV::register_methods(&mut table);
+        ThreadPool::register_methods(&mut table);
+        // Model has no methods, 'model.train(db)' waits on a way to build a Model value, see model.rs
+        return table;
+    }
+
+    pub fn register(&mut self, receiver: MethodReceiver, name: &'static str, sig: MethodSig, builtin: MethodFn) {
+        self.methods.entry(receiver).or_default().insert(name, Method { sig, builtin });
+    }
+
+    pub fn lookup(&self, typ: &ValueTypes, name: &str) -> Option<&Method> {
+        return MethodReceiver::from_type(typ)
+            .and_then(|r| self.methods.get(&r))
+            .and_then(|m| m.get(name));
+    }
+
+    pub fn method_names(&self, typ: &ValueTypes) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = match MethodReceiver::from_type(typ).and_then(|r| self.methods.get(&r)) {
+            Some(m) => m.keys().copied().collect(),
+            None => Vec::new(),
+        };
+        names.sort_unstable();
+        return names;
+    }
+}
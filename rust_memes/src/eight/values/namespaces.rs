@@ -1,7 +1,9 @@
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::tokenizing::symbols::Symbols;
 use log::debug;
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use crate::eight::expressions::secondary::SecondaryExpression;
 use lazy_static::lazy_static;
 use crate::eight::values::namespaces::database::{CSV, JSON};
@@ -14,7 +16,7 @@ pub mod model;
 pub mod algorithms;
 pub mod stdlib;
 
-type NamespaceFunc = fn(&mut Context) -> Option<SecondaryExpression>;
+type NamespaceFunc = fn(&mut Context) -> ParseResult<Option<SecondaryExpression>>;
 
 #[derive(Debug)]
 pub enum NamespaceValue{
@@ -24,7 +26,7 @@ pub enum NamespaceValue{
     ThreadPool(ThreadPool)
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
 pub enum NamespaceValueTypes {
     DatabaseJSONType,
     DatabaseCSVType,
@@ -32,6 +34,35 @@ pub enum NamespaceValueTypes {
     ThreadPoolType
 }
 
+impl NamespaceValueTypes {
+    pub fn is_namespace(name: &str) -> bool {
+        return matches!(name, "Database" | "Model" | "ThreadPool");
+    }
+
+    pub fn from_type_name(namespace: &str, member: Option<&str>) -> Option<NamespaceValueTypes> {
+        // Names used in type annotations, 'db: Database::CSV'
+        return match (namespace, member) {
+            ("Database", Some("CSV")) => Some(DatabaseCSVType),
+            ("Database", Some("JSON")) => Some(DatabaseJSONType),
+            ("Model", None) | ("Model", Some("Model")) => Some(NamespaceValueTypes::ModelType),
+            ("ThreadPool", None) | ("ThreadPool", Some("ThreadPool")) => Some(NamespaceValueTypes::ThreadPoolType),
+            _ => None,
+        };
+    }
+}
+
+impl Display for NamespaceValueTypes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Spelled like the type annotation, 'Database::CSV'
+        return match self {
+            DatabaseJSONType => write!(f, "Database::JSON"),
+            DatabaseCSVType => write!(f, "Database::CSV"),
+            NamespaceValueTypes::ModelType => write!(f, "Model"),
+            NamespaceValueTypes::ThreadPoolType => write!(f, "ThreadPool"),
+        };
+    }
+}
+
 impl NamespaceValue {
     pub fn convert_to_type(val: &NamespaceValue) -> NamespaceValueTypes {
         return match val {
@@ -43,14 +74,14 @@ impl NamespaceValue {
     }
 }
 
-pub fn parse_namespace<'a>(context: &mut Context) -> Option<SecondaryExpression> {
+pub fn parse_namespace<'a>(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
     let mut namespaces: HashMap<&str, NamespaceFunc> = HashMap::new();
 
     namespaces.insert("Database", database::parse_class_func);
     namespaces.insert("Model", model::parse_class_func);
     namespaces.insert("ThreadPool", stdlib::threads::parse_class_func);
 
-    return match context.get() {
+    return match context.get()? {
         Symbols::LiteralSymb(cchunk) => {
             for (namespace, _) in namespaces.iter() {
                 debug!("Checking namespace {:?}", namespace);
@@ -58,7 +89,7 @@ pub fn parse_namespace<'a>(context: &mut Context) -> Option<SecondaryExpression>
                 if cchunk.eq(namespace) {
                     context.increment(); // increment past literal
 
-                    match context.get() {
+                    match context.get()? {
                         Symbols::ClassAccessor => {
                             context.increment();
 
@@ -66,23 +97,20 @@ pub fn parse_namespace<'a>(context: &mut Context) -> Option<SecondaryExpression>
                             return lookup(context);
                         }
                         Symbols::ValueAccessor => {
-                            panic!("{}", context.get_panic_message("Wrong accessor operator '.' for classtype access, use '::' instead."));
+                            return Err(context.error(String::from("Wrong accessor operator '.' for classtype access, use '::' instead.")));
                         }
                         o => {
-                            panic!(
-                                "{}",
-                                context.get_panic_smessage(format!(
-                                    "Expected '::' found '{:?}'",
-                                    o.to_str()
-                                ))
-                            );
+                            return Err(context.error(format!(
+                                "Expected '::' found '{}'",
+                                o.to_str()
+                            )));
                         }
                     }
                 }
             }
             debug!("Value '{}' didn't match any known namespaced var", cchunk);
-            None
+            Ok(None)
         }
-        _ => None,
+        _ => Ok(None),
     };
 }
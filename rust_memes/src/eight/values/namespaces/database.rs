@@ -1,3 +1,4 @@
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::parsing::ParsableLiteral;
 use crate::eight::common::AccessibleValue;
@@ -10,46 +11,42 @@ use crate::eight::literals::Literal;
 use crate::eight::common::running::interpreter::EightInterpreter;
 use crate::eight::common::running::memory::memref::MemRef;
 use crate::eight::expressions::secondary::SecondaryExpression;
+use crate::eight::common::running::error::RuntimeError;
+use crate::eight::literals::basic::list::List;
+use crate::eight::values::methods::{MethodReceiver, MethodTable};
 use crate::eight::values::{Value, ValueTypes};
 use log::debug;
-use std::process::exit;
+use std::rc::Rc;
 use crate::eight::values::namespaces::{NamespaceValue, NamespaceValueTypes};
 
 
-pub fn parse_class_func(context: &mut Context) -> Option<SecondaryExpression> {
-    let (name, args) = match parse_function_call(context) {
+pub fn parse_class_func(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+    let (name, args) = match parse_function_call(context)? {
         Some(n) => n,
         None => {
-            let sym = context.get().to_str();
-            panic!(
-                "{}",
-                context.get_panic_smessage(format!("Invalid namespace member {:?}", sym))
-            );
+            let sym = context.get()?.to_str();
+            return Err(context.error(format!("Invalid namespace member {:?}", sym)));
         }
     };
 
     match name.as_str() {
         "csv" => match CSV::create_class_func_call(&name, args) {
             Ok(cf) => {
-                return Some(SecondaryExpression::CallResult(cf));
+                return Ok(Some(SecondaryExpression::CallResult(cf)));
             }
             Err(err_str) => {
-                panic!("{}", context.get_panic_smessage(err_str));
+                return Err(context.error(err_str));
             }
         },
         "json" => {
             // TODO Impl this
-            println!("matched json, exiting!");
-            exit(0);
+            return Err(context.error(String::from("'Database::json' is not supported yet, only 'Database::csv' is")));
         }
         o => {
-            panic!(
-                "{}",
-                context.get_panic_smessage(format!(
-                    "Invalid member name '{:?}' for namespace 'Database', member not found",
-                    o
-                ))
-            );
+            return Err(context.error(format!(
+                "Invalid member name '{:?}' for namespace 'Database', member not found",
+                o
+            )));
         }
     }
 
@@ -90,6 +87,43 @@ impl CSV {
         debug!("----TODO Making a CSV here filename:'{:?}'", filename);
         CSV { filename }
     }
+
+    pub fn register_methods(table: &mut MethodTable) {
+        // 'db.rows()', every line of the file split on ','
+        table.register(
+            MethodReceiver::Namespace(NamespaceValueTypes::DatabaseCSVType),
+            "rows",
+            |_| FuncSignature::new(vec![], ValueTypes::ListType(Box::new(ValueTypes::ListType(Box::new(ValueTypes::StringType))))),
+            |db, _| {
+                let csv = match db {
+                    Value::NamespaceVal(n) => match n.as_ref() {
+                        NamespaceValue::DatabaseCSV(csv) => csv,
+                        o => RuntimeError::new(format!("Expected a Database::CSV, found {:?}", o)).raise(),
+                    },
+                    o => RuntimeError::new(format!("Expected a Database::CSV, found {:?}", o)).raise(),
+                };
+                Value::ListVal(csv.read_rows())
+            },
+        );
+    }
+
+    fn read_rows(&self) -> List {
+        let contents = match std::fs::read_to_string(&self.filename) {
+            Ok(c) => c,
+            Err(e) => RuntimeError::new(format!("Couldn't read CSV '{}', {}", self.filename, e)).raise(),
+        };
+        let row_typ = ValueTypes::ListType(Box::new(ValueTypes::StringType));
+        let data = contents.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Value::ListVal(List {
+                data: line.split(',')
+                    .map(|cell| Value::Literal(Literal::String(StringVal { value: cell.trim().to_string() })))
+                    .collect(),
+                typ: ValueTypes::StringType,
+            }))
+            .collect();
+        return List { data, typ: row_typ };
+    }
 }
 
 impl AccessibleValue for CSV {
@@ -99,7 +133,7 @@ impl AccessibleValue for CSV {
 }
 
 impl ParsableLiteral for CSV {
-    fn parse<'a>(context: &mut Context) -> Option<Literal> {
+    fn parse<'a>(context: &mut Context) -> ParseResult<Option<Literal>> {
         panic!("Should never be here! CSV is not parsable");
     }
 
@@ -116,7 +150,7 @@ impl ParsableLiteral for CSV {
 
             fn builtin<'d>(interpreter: &'d mut EightInterpreter) -> MemRef {
                 let filn: &StringVal = interpreter.get_val_typed_from_frame::<StringVal>("$0");
-                let val = Value::NamespaceVal(NamespaceValue::DatabaseCSV(CSV::new(filn.value.to_string())));
+                let val = Value::NamespaceVal(Rc::new(NamespaceValue::DatabaseCSV(CSV::new(filn.value.to_string()))));
 
                 interpreter.add_val_to_stack(val)
             }
@@ -147,7 +181,7 @@ impl AccessibleValue for JSON {
 }
 
 impl ParsableLiteral for JSON {
-    fn parse<'a, 'c>(context: &mut Context) -> Option<Literal> {
+    fn parse<'a, 'c>(context: &mut Context) -> ParseResult<Option<Literal>> {
         todo!()
     }
 
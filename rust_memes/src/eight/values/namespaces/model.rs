@@ -1,3 +1,4 @@
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::parsing::ParsableLiteral;
 use crate::eight::common::AccessibleValue;
@@ -8,8 +9,9 @@ use crate::eight::literals::Literal;
 use crate::eight::expressions::secondary::SecondaryExpression;
 use crate::eight::values::Value;
 
-pub fn parse_class_func(context: &mut Context) -> Option<SecondaryExpression> {
-    return None;
+pub fn parse_class_func(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+    // TODO Building and training models is not supported yet, 'Model::new(...)' and 'model.train(db)' come later
+    return Ok(None);
     // let name = consume_until_delimiter(code, &mut *index, vec!["::", "("]);
     // if name == "new" {
     //     return Model::parse(index, code);
@@ -34,7 +36,7 @@ impl AccessibleValue for Model {
 }
 
 impl ParsableLiteral for Model {
-    fn parse<'a>(context: &mut Context) -> Option<Literal> {
+    fn parse<'a>(context: &mut Context) -> ParseResult<Option<Literal>> {
         todo!()
     }
 
@@ -1,7 +1,9 @@
+use crate::eight::common::diagnostics::ParseResult;
 use crate::eight::common::parsing::context::Context;
 use crate::eight::common::parsing::ParsableLiteral;
 use crate::eight::common::running::interpreter::EightInterpreter;
 use crate::eight::common::running::memory::memref::MemRef;
+use crate::eight::common::running::error::RuntimeError;
 use crate::eight::expressions::secondary::callfunc::CallFunc;
 use crate::eight::expressions::secondary::SecondaryExpression;
 use crate::eight::literals::basic::functions::args::FuncArgs;
@@ -10,31 +12,30 @@ use crate::eight::literals::basic::functions::signature::FuncSignature;
 use crate::eight::literals::basic::number::NumberVal;
 use crate::eight::literals::Literal;
 use crate::eight::values::namespaces::{NamespaceValue, NamespaceValueTypes};
+use crate::eight::values::methods::{MethodReceiver, MethodTable};
 use crate::eight::values::{Value, ValueTypes};
+use std::rc::Rc;
 
-pub fn parse_class_func(context: &mut Context) -> Option<SecondaryExpression> {
-    let (name, args) = match parse_function_call(context) {
+pub fn parse_class_func(context: &mut Context) -> ParseResult<Option<SecondaryExpression>> {
+    let (name, args) = match parse_function_call(context)? {
         Some(n) => n,
         None => {
-            let sym = context.get().to_str();
-            panic!(
-                "{}",
-                context.get_panic_smessage(format!("Invalid namespace member {:?}", sym))
-            );
+            let sym = context.get()?.to_str();
+            return Err(context.error(format!("Invalid namespace member {:?}", sym)));
         }
     };
 
     match name.as_str() {
         "new" => {
             match ThreadPool::create_class_func_call(&name, args) {
-                Ok(cf) => return Some(SecondaryExpression::CallResult(cf)),
+                Ok(cf) => return Ok(Some(SecondaryExpression::CallResult(cf))),
                 Err(err_str) => {
-                    panic!("{}", context.get_panic_smessage(err_str));
+                    return Err(context.error(err_str));
                 }
             }
         },
         o => {
-            panic!("{}", context.get_panic_smessage(format!(
+            return Err(context.error(format!(
                     "Invalid member name '{:?}' for namespace 'ThreadPool', member not found", o)));
         }
     }
@@ -46,7 +47,7 @@ pub struct ThreadPool {
 }
 
 impl ParsableLiteral for ThreadPool {
-    fn parse<'a>(context: &mut Context) -> Option<Literal> {
+    fn parse<'a>(context: &mut Context) -> ParseResult<Option<Literal>> {
         todo!()
     }
 
@@ -59,7 +60,7 @@ impl ParsableLiteral for ThreadPool {
 
             fn builtin<'d>(interpreter: &'d mut EightInterpreter) -> MemRef {
                 let thread_count: &NumberVal = interpreter.get_val_typed_from_frame::<NumberVal>("$0");
-                let val = Value::NamespaceVal(NamespaceValue::ThreadPool(ThreadPool::new(thread_count.value)));
+                let val = Value::NamespaceVal(Rc::new(NamespaceValue::ThreadPool(ThreadPool::new(thread_count.value))));
                 interpreter.add_val_to_stack(val)
             }
 
@@ -71,9 +72,27 @@ impl ParsableLiteral for ThreadPool {
 }
 
 impl ThreadPool {
-    pub fn new(count: i32) -> Self {
+    pub fn new(count: i64) -> Self {
         ThreadPool {
             thread_count: count as u8
         }
     }
+
+    pub fn register_methods(table: &mut MethodTable) {
+        table.register(
+            MethodReceiver::Namespace(NamespaceValueTypes::ThreadPoolType),
+            "size",
+            |_| FuncSignature::new(vec![], ValueTypes::NumberType),
+            |pool, _| {
+                let count = match pool {
+                    Value::NamespaceVal(n) => match n.as_ref() {
+                        NamespaceValue::ThreadPool(p) => p.thread_count,
+                        o => RuntimeError::new(format!("Expected a ThreadPool, found {:?}", o)).raise(),
+                    },
+                    o => RuntimeError::new(format!("Expected a ThreadPool, found {:?}", o)).raise(),
+                };
+                Value::Literal(Literal::Number(NumberVal { value: count as i64 }))
+            },
+        );
+    }
 }
\ No newline at end of file
@@ -1,11 +1,14 @@
+mod benchmarks;
 mod console;
 mod eight;
 mod networking;
 mod testing;
 mod webui;
 
+use eight::common::diagnostics::render_all;
 use eight::common::logging::EightLogger;
 use eight::common::parsing::ast::EightAST;
+use eight::common::running::output::OutputSink;
 use log;
 use log::{info, LevelFilter};
 use std::process::exit;
@@ -24,6 +27,12 @@ struct CommandLineArgs {
 
     #[structopt(name = "namespace")]
     namespace: String,
+
+    #[structopt(long = "bench-lexer")]
+    bench_lexer: bool,
+
+    #[structopt(long = "iterations", default_value = "20")]
+    iterations: u32,
 }
 
 static LOGGER: EightLogger = EightLogger;
@@ -47,7 +56,9 @@ fn main() {
     // testing::test_dropborrow();
     // exit(0);
 
-    if opts.is_master {
+    if opts.bench_lexer {
+        benchmarks::bench_lexer(opts.filename.as_str(), opts.iterations);
+    } else if opts.is_master {
         let _web_thread = webui::index::start_webserver();
         console::commands::wait_for_commands();
         _web_thread.join().unwrap();
@@ -56,10 +67,20 @@ fn main() {
     } else if !opts.filename.is_empty() {
         let error_msg = format!("Error reading file '{}'!", opts.filename);
 
-        let file_data = std::fs::read_to_string(opts.filename).expect(error_msg.as_str());
+        let file_data = std::fs::read_to_string(&opts.filename).expect(error_msg.as_str());
 
-        let exprs = EightAST::new(eight::start_parse(file_data)).optimize_ast();
+        let parsed = match eight::start_parse(opts.filename.clone(), file_data.clone()) {
+            Ok(p) => p,
+            Err(diagnostics) => {
+                eprint!("{}", render_all(&diagnostics, opts.filename.as_str(), file_data.as_str()));
+                exit(1);
+            }
+        };
+        let exprs = EightAST::new(parsed).optimize_ast();
 
-        eight::runners::local::run(exprs);
+        if let Err(e) = eight::runners::local::run(exprs, OutputSink::stdout(), OutputSink::stderr()) {
+            eprintln!("{}", e);
+            exit(1);
+        }
     }
 }
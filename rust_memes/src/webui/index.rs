@@ -6,6 +6,6 @@ pub fn say_hello(state: State) -> (State, &'static str) {
 }
 
 pub fn start_webserver() -> JoinHandle<()> {
-    let thread = std::thread::spawn(|| gotham::start("127.0.0.1:8000", || Ok(say_hello)));
+    let thread = std::thread::spawn(|| gotham::start("127.0.0.1:8000", || Ok(say_hello)).unwrap());
     return thread;
 }